@@ -0,0 +1,247 @@
+//! 命令行前端：`needyclash daemon` 以无界面模式启动，其余子命令通过
+//! embedded server 的控制 API 操作已运行的实例。
+
+use std::time::Duration;
+
+use anyhow::{Result, anyhow, bail};
+use reqwest::{Client, Method};
+use serde_json::{Value, json};
+
+use crate::{
+    config::IVerge,
+    utils::{control_api::API_PREFIX, dirs},
+};
+
+const USAGE: &str = "\
+Usage: needyclash [COMMAND]
+
+Commands:
+  daemon                     Run without window and tray (alias: --headless)
+  status                     Show status of the running instance
+  profile list               List profiles
+  profile update [UID]       Update a profile, or all remote profiles
+  profile select <UID>       Activate a profile
+  mode set <MODE>            Switch clash mode (rule/global/direct)
+  core restart               Restart the core
+  help                       Show this message
+
+Without a command the GUI is started.";
+
+/// 单次请求超时，订阅更新可能较慢
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+enum CliCommand {
+    Status,
+    ProfileList,
+    ProfileUpdate(Option<String>),
+    ProfileSelect(String),
+    ModeSet(String),
+    CoreRestart,
+    Help,
+}
+
+/// 处理命令行参数
+///
+/// 返回 `None` 表示继续启动 GUI，否则返回进程退出码
+pub fn run(args: &[String]) -> Option<i32> {
+    if args.get(1).is_some_and(|arg| arg == "daemon")
+        || args.iter().skip(1).any(|arg| arg == "--headless")
+    {
+        return Some(crate::utils::resolve::headless::run());
+    }
+
+    let command = match parse(args.get(1..).unwrap_or_default()) {
+        Ok(Some(command)) => command,
+        Ok(None) => return None,
+        Err(e) => {
+            attach_console();
+            eprintln!("error: {e}\n\n{USAGE}");
+            return Some(2);
+        }
+    };
+
+    attach_console();
+    if let CliCommand::Help = command {
+        println!("{USAGE}");
+        return Some(0);
+    }
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: failed to create runtime: {e}");
+            return Some(1);
+        }
+    };
+    match runtime.block_on(execute(command)) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("error: {e}");
+            Some(1)
+        }
+    }
+}
+
+/// 解析子命令，无参数或非 CLI 参数（如 `--no-tray`、`clash://` 链接）返回 `None`
+fn parse(args: &[String]) -> Result<Option<CliCommand>> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let command = match args.as_slice() {
+        ["help" | "--help" | "-h", ..] => CliCommand::Help,
+        ["status"] => CliCommand::Status,
+        ["profile", "list"] => CliCommand::ProfileList,
+        ["profile", "update"] => CliCommand::ProfileUpdate(None),
+        ["profile", "update", uid] => CliCommand::ProfileUpdate(Some((*uid).into())),
+        ["profile", "select", uid] => CliCommand::ProfileSelect((*uid).into()),
+        ["mode", "set", mode] => CliCommand::ModeSet((*mode).into()),
+        ["core", "restart"] => CliCommand::CoreRestart,
+        ["status" | "profile" | "mode" | "core", ..] => bail!("invalid arguments"),
+        _ => return Ok(None),
+    };
+    Ok(Some(command))
+}
+
+async fn execute(command: CliCommand) -> Result<()> {
+    match command {
+        CliCommand::Status => {
+            let status = request(Method::GET, "status", None).await?;
+            print_status(&status);
+        }
+        CliCommand::ProfileList => {
+            let profiles = request(Method::GET, "profiles", None).await?;
+            print_profiles(&profiles);
+        }
+        CliCommand::ProfileUpdate(uid) => {
+            let path = match uid {
+                Some(uid) => format!("profiles/{uid}/update"),
+                None => "profiles/update".into(),
+            };
+            let results = request(Method::POST, &path, None).await?;
+            let mut failed = 0;
+            for result in results.as_array().into_iter().flatten() {
+                let uid = result["uid"].as_str().unwrap_or_default();
+                match result["error"].as_str() {
+                    Some(error) => {
+                        failed += 1;
+                        println!("{uid}\tfailed: {error}");
+                    }
+                    None => println!("{uid}\tupdated"),
+                }
+            }
+            if failed > 0 {
+                bail!("{failed} profile(s) failed to update");
+            }
+        }
+        CliCommand::ProfileSelect(uid) => {
+            request(Method::POST, &format!("profiles/{uid}/select"), None).await?;
+            println!("profile {uid} activated");
+        }
+        CliCommand::ModeSet(mode) => {
+            request(Method::PUT, "mode", Some(json!({ "mode": mode }))).await?;
+            println!("mode set to {mode}");
+        }
+        CliCommand::CoreRestart => {
+            request(Method::POST, "core/restart", None).await?;
+            println!("core restarted");
+        }
+        CliCommand::Help => println!("{USAGE}"),
+    }
+    Ok(())
+}
+
+/// 从 verge 配置中读取控制 API 令牌
+fn read_api_token() -> Result<String> {
+    let path = dirs::app_home_dir_standalone()?.join(dirs::VERGE_CONFIG);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("failed to read {}: {e}", path.display()))?;
+    let verge: serde_yaml_ng::Value = serde_yaml_ng::from_str(&content)?;
+    verge
+        .get("control_api_token")
+        .and_then(|token| token.as_str())
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+        .ok_or_else(|| anyhow!("control api token is not set, start the app once to generate it"))
+}
+
+/// 调用运行中实例的控制 API，返回 `data` 字段
+async fn request(method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+    let port = IVerge::get_singleton_port();
+    let token = read_api_token()?;
+    let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+    let mut request = client
+        .request(
+            method,
+            format!("http://127.0.0.1:{port}/{API_PREFIX}/{path}"),
+        )
+        .bearer_auth(token);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let response = request.send().await.map_err(|e| {
+        if e.is_connect() {
+            anyhow!("no running instance found on port {port}")
+        } else {
+            anyhow!(e)
+        }
+    })?;
+
+    let mut reply: Value = response.json().await?;
+    if reply["ok"].as_bool() != Some(true) {
+        bail!(
+            "{}",
+            reply["error"]
+                .as_str()
+                .unwrap_or("unknown error from running instance")
+        );
+    }
+    Ok(reply["data"].take())
+}
+
+fn print_status(status: &Value) {
+    let text = |key: &str| status[key].as_str().unwrap_or("-").to_string();
+    let flag = |key: &str| {
+        if status[key].as_bool() == Some(true) {
+            "on"
+        } else {
+            "off"
+        }
+    };
+
+    println!("version:        {}", text("version"));
+    println!("running mode:   {}", text("running_mode"));
+    println!("clash mode:     {}", text("clash_mode"));
+    println!(
+        "profile:        {} ({})",
+        text("current_profile_name"),
+        text("current_profile")
+    );
+    println!("system proxy:   {}", flag("system_proxy"));
+    println!("tun mode:       {}", flag("tun_mode"));
+    println!("mixed port:     {}", status["mixed_port"]);
+}
+
+fn print_profiles(profiles: &Value) {
+    for profile in profiles.as_array().into_iter().flatten() {
+        let marker = if profile["current"].as_bool() == Some(true) {
+            "*"
+        } else {
+            " "
+        };
+        println!(
+            "{marker} {}\t{}\t{}",
+            profile["uid"].as_str().unwrap_or_default(),
+            profile["type"].as_str().unwrap_or("-"),
+            profile["name"].as_str().unwrap_or("-"),
+        );
+    }
+}
+
+/// release 构建为 windows 子系统程序，需要附加到父进程控制台才能输出
+fn attach_console() {
+    #[cfg(windows)]
+    unsafe {
+        winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+    }
+}
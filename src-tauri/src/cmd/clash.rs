@@ -1,10 +1,14 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, path::PathBuf};
 
 use super::CmdResult;
 use crate::{
     cmd::StringifyErr,
     config::Config,
-    core::{CoreManager, handle},
+    core::{
+        CoreManager, RunningMode, handle,
+        log_store::{CoreLogPage, CoreLogQuery, CoreLogStore},
+    },
+    process::AsyncHandler,
 };
 use crate::{config::*, feat, logging, utils::logging::Type, wrap_err};
 use compact_str::CompactString;
@@ -108,14 +112,50 @@ pub async fn restart_core() -> CmdResult {
 /// URL延迟测试
 #[tauri::command]
 pub async fn test_delay(url: String) -> CmdResult<u32> {
-    let result = match feat::test_delay(url).await {
-        Ok(delay) => delay,
-        Err(e) => {
-            log::error!(target: "app", "{}", e);
-            10000u32
-        }
-    };
-    Ok(result)
+    feat::test_delay(url).await.stringify_err()
+}
+
+/// URL多次采样延迟测试
+#[tauri::command]
+pub async fn test_latency(url: String, samples: Option<u32>) -> CmdResult<feat::LatencyReport> {
+    feat::test_latency(url, samples).await.stringify_err()
+}
+
+/// 通过本地代理测试下载与上传带宽
+#[tauri::command]
+pub async fn test_bandwidth(options: feat::BandwidthOptions) -> CmdResult<feat::BandwidthReport> {
+    feat::test_bandwidth(options).await.stringify_err()
+}
+
+/// 取消正在进行的带宽测试
+#[tauri::command]
+pub fn cancel_bandwidth_test() -> CmdResult {
+    feat::cancel_bandwidth_test();
+    Ok(())
+}
+
+/// 后台延迟探测得到的节点稳定性，按评分降序
+#[tauri::command]
+pub async fn get_node_stability() -> CmdResult<Vec<feat::NodeStability>> {
+    Ok(feat::DelayProber::global().stabilities().await)
+}
+
+/// 节点的后台延迟探测记录
+#[tauri::command]
+pub async fn get_delay_history(node: String) -> CmdResult<Vec<feat::DelaySample>> {
+    Ok(feat::DelayProber::global().history(&node).await)
+}
+
+/// 按稳定性排序的收藏节点
+#[tauri::command]
+pub async fn get_favorite_suggestions() -> CmdResult<Vec<String>> {
+    let favorites = Config::verge()
+        .await
+        .latest_ref()
+        .favorite_proxies
+        .clone()
+        .unwrap_or_default();
+    Ok(feat::DelayProber::global().sort_by_score(&favorites).await)
 }
 
 /// 保存DNS配置到单独文件
@@ -281,3 +321,37 @@ pub async fn get_clash_logs() -> CmdResult<VecDeque<CompactString>> {
         .unwrap_or_default();
     Ok(logs)
 }
+
+/// 按条件查询持久化的内核日志
+#[tauri::command]
+pub async fn query_clash_logs(query: CoreLogQuery) -> CmdResult<CoreLogPage> {
+    if CoreManager::global().get_running_mode() == RunningMode::Service {
+        // 服务模式下先同步一次，确保最新日志已入库
+        let _ = CoreManager::global().get_clash_logs().await;
+    }
+    AsyncHandler::spawn_blocking(move || CoreLogStore::global().query(&query))
+        .await
+        .stringify_err()?
+        .stringify_err()
+}
+
+/// 将满足条件的内核日志导出到指定文件，返回导出条数
+#[tauri::command]
+pub async fn export_clash_logs(query: CoreLogQuery, path: String) -> CmdResult<usize> {
+    let target = PathBuf::from(path);
+    AsyncHandler::spawn_blocking(move || CoreLogStore::global().export(&query, &target))
+        .await
+        .stringify_err()?
+        .stringify_err_log(|e| {
+            logging!(error, Type::Cmd, "Failed to export clash logs: {e}");
+        })
+}
+
+/// 清空持久化的内核日志
+#[tauri::command]
+pub async fn clear_clash_logs() -> CmdResult {
+    AsyncHandler::spawn_blocking(|| CoreLogStore::global().clear())
+        .await
+        .stringify_err()?
+        .stringify_err()
+}
@@ -0,0 +1,647 @@
+//! 声明式解锁检测
+//!
+//! 简单的内置检测以同样的格式随应用打包（`builtin/unlock_checks.yaml`）。
+//! 用户可在应用目录的 `unlock_checks.yaml` 中追加检测项，与内置检测一起运行，
+//! 同名检测项会覆盖内置实现。正则在解析时编译。格式示例：
+//!
+//! ```yaml
+//! checks:
+//!   - name: Example
+//!     requests:
+//!       - url: https://example.com/cdn-cgi/trace
+//!       - url: https://example.com/title/1
+//!         method: GET
+//!         headers:
+//!           Accept-Language: en
+//!         follow_redirects: false
+//!     rules:                 # 按顺序匹配，第一条命中的规则决定结果
+//!       - request: 1
+//!         status: [403, 451]
+//!         result: "No"
+//!       - request: 1
+//!         location: "/login"
+//!         result: "No"
+//!       - request: 1
+//!         body: "(?i)not available"
+//!         result: "No"
+//!       - request: 1
+//!         status: ["2xx"]    # 状态码或 "2xx" 形式的状态类别
+//!         result: "Yes"
+//!     region:                # 单条或多条，按顺序取第一个提取到的地区
+//!       - request: 1
+//!         source: url        # body / url / header
+//!         pattern: "^https?://[^/]+/([a-z]{2})/"
+//!       - request: 0
+//!         pattern: "loc=([A-Z]{2})"
+//!     blocked_regions: [CN]
+//! ```
+
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, Method, header::LOCATION};
+use serde::{Deserialize, Deserializer, de};
+
+use crate::{logging, utils::dirs, utils::logging::Type};
+
+use super::UnlockItem;
+use super::utils::{country_code_to_emoji, get_local_date_string};
+
+const CHECKS_FILE: &str = "unlock_checks.yaml";
+const BUILTIN_CHECKS: &str = include_str!("./builtin/unlock_checks.yaml");
+
+static BUILTIN: Lazy<Vec<CheckDefinition>> =
+    Lazy::new(|| parse_checks(BUILTIN_CHECKS).unwrap_or_default());
+
+fn default_method() -> String {
+    "GET".into()
+}
+
+fn default_result() -> String {
+    "Failed".into()
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+/// 逐项解析，单个检测项无效时不影响其余检测项
+#[derive(Debug, Default, Deserialize)]
+struct CheckFile {
+    #[serde(default)]
+    checks: Vec<serde_yaml_ng::Value>,
+}
+
+/// 解析时编译的正则
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map(Self).map_err(de::Error::custom)
+    }
+}
+
+/// 状态码条件，`200` 匹配单个状态码，`"2xx"` 匹配整个类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusMatcher {
+    Code(u16),
+    Class(u16),
+}
+
+impl StatusMatcher {
+    fn matches(self, status: u16) -> bool {
+        match self {
+            Self::Code(code) => status == code,
+            Self::Class(class) => status / 100 == class,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for StatusMatcher {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Code(u16),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Code(code) => Ok(Self::Code(code)),
+            Raw::Text(text) => {
+                let class = text
+                    .to_ascii_lowercase()
+                    .strip_suffix("xx")
+                    .and_then(|class| class.parse::<u16>().ok())
+                    .filter(|class| (1..=5).contains(class));
+                class
+                    .map(Self::Class)
+                    .ok_or_else(|| de::Error::custom(format!("invalid status: {text}")))
+            }
+        }
+    }
+}
+
+/// 单条或多条地区提取规则
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<RegionRule>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        One(RegionRule),
+        Many(Vec<RegionRule>),
+    }
+
+    Ok(match Raw::deserialize(deserializer)? {
+        Raw::One(rule) => vec![rule],
+        Raw::Many(rules) => rules,
+    })
+}
+
+/// 单个检测项
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckDefinition {
+    pub name: String,
+    pub requests: Vec<CheckRequest>,
+    #[serde(default)]
+    pub rules: Vec<CheckRule>,
+    /// 按顺序尝试，取第一个提取到的地区
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub region: Vec<RegionRule>,
+    /// 地区在列表中时，`Yes` 改判为 `No`
+    #[serde(default)]
+    pub blocked_regions: Vec<String>,
+    /// 没有规则命中时的结果
+    #[serde(default = "default_result")]
+    pub default: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRequest {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// 为 false 时不跟随跳转，可用 `location` 规则匹配跳转目标
+    #[serde(default = "default_true")]
+    pub follow_redirects: bool,
+}
+
+/// 结果规则，所有给出的条件都满足才算命中
+#[derive(Debug, Clone, Deserialize)]
+pub struct CheckRule {
+    /// 规则作用的请求序号
+    #[serde(default)]
+    pub request: usize,
+    #[serde(default)]
+    pub status: Vec<StatusMatcher>,
+    /// 匹配响应内容的正则
+    #[serde(default)]
+    pub body: Option<Pattern>,
+    /// 匹配最终地址的正则
+    #[serde(default)]
+    pub url: Option<Pattern>,
+    /// 匹配 `Location` 响应头的正则
+    #[serde(default)]
+    pub location: Option<Pattern>,
+    /// 为 true 时仅在请求失败时命中
+    #[serde(default)]
+    pub error: bool,
+    pub result: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegionSource {
+    #[default]
+    Body,
+    Url,
+    Header,
+}
+
+/// 地区提取规则，取正则第一个捕获组
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegionRule {
+    #[serde(default)]
+    pub request: usize,
+    #[serde(default)]
+    pub source: RegionSource,
+    /// `source` 为 header 时的响应头名称
+    #[serde(default)]
+    pub header: Option<String>,
+    pub pattern: Pattern,
+}
+
+/// 请求结果
+#[derive(Debug, Default, Clone)]
+pub struct CheckResponse {
+    pub status: u16,
+    pub url: String,
+    pub location: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+type Outcome = Result<CheckResponse, String>;
+
+impl CheckDefinition {
+    /// 校验名称、请求方法与请求序号，正则已在解析时编译
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("unlock check name is empty");
+        }
+        if self.requests.is_empty() {
+            bail!("unlock check {} has no requests", self.name);
+        }
+        for request in &self.requests {
+            request.method.parse::<Method>()?;
+        }
+        let max_index = self
+            .rules
+            .iter()
+            .map(|rule| rule.request)
+            .chain(self.region.iter().map(|region| region.request))
+            .max()
+            .unwrap_or_default();
+        if max_index >= self.requests.len() {
+            bail!(
+                "unlock check {} refers to request {} out of range",
+                self.name,
+                max_index
+            );
+        }
+        Ok(())
+    }
+
+    /// 依次发送请求并根据规则得出结果
+    pub async fn run(&self, client: &Client, no_redirect_client: &Client) -> UnlockItem {
+        let mut outcomes = Vec::with_capacity(self.requests.len());
+        for request in &self.requests {
+            let client = if request.follow_redirects {
+                client
+            } else {
+                no_redirect_client
+            };
+            outcomes.push(request.send(client).await);
+        }
+        self.evaluate(&outcomes)
+    }
+
+    fn rule_matches(rule: &CheckRule, outcomes: &[Outcome]) -> bool {
+        match outcomes.get(rule.request) {
+            Some(Ok(response)) => {
+                !rule.error
+                    && (rule.status.is_empty()
+                        || rule
+                            .status
+                            .iter()
+                            .any(|status| status.matches(response.status)))
+                    && rule
+                        .body
+                        .as_ref()
+                        .is_none_or(|pattern| pattern.is_match(&response.body))
+                    && rule
+                        .url
+                        .as_ref()
+                        .is_none_or(|pattern| pattern.is_match(&response.url))
+                    && rule.location.as_ref().is_none_or(|pattern| {
+                        response
+                            .location
+                            .as_deref()
+                            .is_some_and(|location| pattern.is_match(location))
+                    })
+            }
+            Some(Err(_)) => rule.error,
+            None => false,
+        }
+    }
+
+    fn region_code(&self, outcomes: &[Outcome]) -> Option<String> {
+        self.region
+            .iter()
+            .find_map(|region| region.extract(outcomes))
+    }
+
+    /// 根据请求结果得出检测结果
+    pub fn evaluate(&self, outcomes: &[Outcome]) -> UnlockItem {
+        let code = self.region_code(outcomes);
+        let matched = self
+            .rules
+            .iter()
+            .find(|rule| Self::rule_matches(rule, outcomes))
+            .map(|rule| rule.result.clone());
+        let mut status = match matched {
+            Some(status) => status,
+            None if outcomes.iter().any(Result::is_err) => "Failed".into(),
+            None => self.default.clone(),
+        };
+        if status == "Yes"
+            && let Some(code) = &code
+            && self
+                .blocked_regions
+                .iter()
+                .any(|blocked| blocked.eq_ignore_ascii_case(code))
+        {
+            status = "No".into();
+        }
+
+        UnlockItem {
+            name: self.name.clone(),
+            status,
+            region: code.map(|code| {
+                if code.len() == 2 {
+                    format!("{}{code}", country_code_to_emoji(&code))
+                } else {
+                    code
+                }
+            }),
+            check_time: Some(get_local_date_string()),
+        }
+    }
+}
+
+impl RegionRule {
+    fn extract(&self, outcomes: &[Outcome]) -> Option<String> {
+        let Some(Ok(response)) = outcomes.get(self.request) else {
+            return None;
+        };
+        let text = match self.source {
+            RegionSource::Body => response.body.as_str(),
+            RegionSource::Url => response.url.as_str(),
+            RegionSource::Header => {
+                let name = self.header.as_deref()?.to_ascii_lowercase();
+                response.headers.get(&name)?.as_str()
+            }
+        };
+        let captures = self.pattern.0.captures(text)?;
+        let code = captures.get(1)?.as_str().trim().to_uppercase();
+        (!code.is_empty()).then_some(code)
+    }
+}
+
+impl CheckRequest {
+    async fn send(&self, client: &Client) -> Outcome {
+        let method = self.method.parse::<Method>().map_err(|e| e.to_string())?;
+        let mut builder = client.request(method, &self.url);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &self.body {
+            builder = builder.body(body.clone());
+        }
+
+        let response = builder.send().await.map_err(|e| e.to_string())?;
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let status = response.status().as_u16();
+        let url = response.url().to_string();
+        let body = response.text().await.unwrap_or_default();
+        Ok(CheckResponse {
+            status,
+            url,
+            location,
+            headers,
+            body,
+        })
+    }
+}
+
+/// 解析检测定义，无效的检测项会被跳过
+pub fn parse_checks(content: &str) -> Result<Vec<CheckDefinition>> {
+    let file: CheckFile = serde_yaml_ng::from_str(content)?;
+    Ok(file
+        .checks
+        .into_iter()
+        .filter_map(|value| {
+            let check = serde_yaml_ng::from_value::<CheckDefinition>(value)
+                .map_err(anyhow::Error::from)
+                .and_then(|check| check.validate().map(|()| check));
+            match check {
+                Ok(check) => Some(check),
+                Err(e) => {
+                    logging!(warn, Type::Network, "忽略无效的解锁检测项: {}", e);
+                    None
+                }
+            }
+        })
+        .collect())
+}
+
+/// 内置与用户定义的检测项，同名时使用用户的定义
+pub fn load_checks() -> Vec<CheckDefinition> {
+    let mut checks = load_user_checks();
+    for check in BUILTIN.iter() {
+        if !checks.iter().any(|c| c.name == check.name) {
+            checks.push(check.clone());
+        }
+    }
+    checks
+}
+
+/// 读取用户定义的检测项，文件不存在时为空
+fn load_user_checks() -> Vec<CheckDefinition> {
+    let Ok(path) = dirs::app_home_dir().map(|dir| dir.join(CHECKS_FILE)) else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    parse_checks(&content).unwrap_or_else(|e| {
+        logging!(
+            error,
+            Type::Network,
+            "解析解锁检测文件失败 {}: {}",
+            path.display(),
+            e
+        );
+        Vec::new()
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        time::Duration,
+    };
+
+    const CHECKS: &str = r#"
+checks:
+  - name: Mock
+    requests:
+      - url: "{base}/trace"
+      - url: "{base}/title"
+        follow_redirects: false
+    rules:
+      - request: 1
+        status: [403]
+        result: "No"
+      - request: 1
+        location: "/login"
+        result: "No"
+      - request: 1
+        body: "(?i)watch now"
+        result: "Yes"
+    region:
+      request: 0
+      pattern: "loc=([A-Z]{2})"
+    blocked_regions: [CN]
+  - name: Broken
+    requests:
+      - url: "{base}/trace"
+    rules:
+      - body: "("
+        result: "Yes"
+"#;
+
+    /// 最简 HTTP 服务，按路径返回预设响应
+    fn mock_server(routes: Vec<(&'static str, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 4096];
+                let len = stream.read(&mut buf).unwrap_or_default();
+                let request = String::from_utf8_lossy(&buf[..len]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let response = routes
+                    .iter()
+                    .find(|(route, _)| *route == path)
+                    .map_or("HTTP/1.1 404 Not Found\r\n", |(_, response)| *response);
+                let (head, body) = response.split_once("\r\n\r\n").unwrap_or((response, ""));
+                let reply = format!(
+                    "{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    head.trim_end(),
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(reply.as_bytes());
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    async fn run_mock(title: &'static str, trace: &'static str) -> UnlockItem {
+        let base = mock_server(vec![("/trace", trace), ("/title", title)]);
+        let checks = parse_checks(&CHECKS.replace("{base}", &base)).unwrap_or_default();
+        assert_eq!(checks.len(), 1);
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .no_proxy()
+            .build()
+            .unwrap_or_default();
+        let no_redirect_client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .no_proxy()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        checks[0].run(&client, &no_redirect_client).await
+    }
+
+    #[tokio::test]
+    async fn test_declarative_check_with_mock_server() {
+        let ok = run_mock(
+            "HTTP/1.1 200 OK\r\n\r\nWatch now",
+            "HTTP/1.1 200 OK\r\n\r\nloc=US",
+        )
+        .await;
+        assert_eq!(ok.status, "Yes");
+        assert_eq!(ok.region.as_deref(), Some("🇺🇸US"));
+
+        let forbidden = run_mock("HTTP/1.1 403 Forbidden\r\n\r\n", "HTTP/1.1 200 OK\r\n\r\n").await;
+        assert_eq!(forbidden.status, "No");
+        assert_eq!(forbidden.region, None);
+
+        let redirect = run_mock(
+            "HTTP/1.1 302 Found\r\nLocation: /login\r\n\r\n",
+            "HTTP/1.1 200 OK\r\n\r\n",
+        )
+        .await;
+        assert_eq!(redirect.status, "No");
+
+        let blocked = run_mock(
+            "HTTP/1.1 200 OK\r\n\r\nwatch now",
+            "HTTP/1.1 200 OK\r\n\r\nloc=CN",
+        )
+        .await;
+        assert_eq!(blocked.status, "No");
+
+        let unmatched = run_mock("HTTP/1.1 500 Error\r\n\r\n", "HTTP/1.1 200 OK\r\n\r\n").await;
+        assert_eq!(unmatched.status, "Failed");
+    }
+
+    #[test]
+    fn test_builtin_checks() {
+        let names = BUILTIN
+            .iter()
+            .map(|check| check.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["哔哩哔哩大陆", "哔哩哔哩港澳台", "Spotify", "TikTok"]
+        );
+
+        let bilibili = &BUILTIN[0];
+        let response = |body: &str| {
+            Ok(CheckResponse {
+                status: 200,
+                body: body.into(),
+                ..CheckResponse::default()
+            })
+        };
+        let result = |body| bilibili.evaluate(&[response(body)]).status;
+        assert_eq!(result(r#"{"code":0,"result":{}}"#), "Yes");
+        assert_eq!(result(r#"{"code":-10403,"message":"x"}"#), "No");
+        assert_eq!(result(r#"{"code":-404}"#), "Failed");
+
+        let spotify = &BUILTIN[2];
+        let response = |status, url: &str, body: &str| {
+            Ok(CheckResponse {
+                status,
+                url: url.into(),
+                body: body.into(),
+                ..CheckResponse::default()
+            })
+        };
+        let api = "https://www.spotify.com/api/content/v1/country-selector";
+        let redirected = spotify.evaluate(&[response(204, "https://www.spotify.com/hk-en/x", "")]);
+        assert_eq!(redirected.status, "Yes");
+        assert_eq!(redirected.region.as_deref(), Some("🇭🇰HK"));
+        let body = spotify.evaluate(&[response(200, api, r#"{"countryCode":"us"}"#)]);
+        assert_eq!(body.region.as_deref(), Some("🇺🇸US"));
+        let unavailable = spotify.evaluate(&[response(200, api, "Not available in your country")]);
+        assert_eq!(unavailable.status, "No");
+        assert_eq!(spotify.evaluate(&[response(451, api, "")]).status, "No");
+        assert_eq!(spotify.evaluate(&[response(500, api, "")]).status, "Failed");
+    }
+
+    #[test]
+    fn test_status_matcher() {
+        let parse = |value: &str| serde_yaml_ng::from_str::<StatusMatcher>(value);
+        assert_eq!(parse("200").unwrap(), StatusMatcher::Code(200));
+        assert_eq!(parse("\"2XX\"").unwrap(), StatusMatcher::Class(2));
+        assert!(parse("\"9xx\"").is_err());
+        assert!(StatusMatcher::Class(2).matches(204));
+        assert!(!StatusMatcher::Class(2).matches(301));
+    }
+
+    #[test]
+    fn test_evaluate_request_error() {
+        let checks =
+            parse_checks(&CHECKS.replace("{base}", "http://127.0.0.1:1")).unwrap_or_default();
+        let outcomes = vec![
+            Err("connection refused".to_string()),
+            Ok(CheckResponse::default()),
+        ];
+        let item = checks[0].evaluate(&outcomes);
+        assert_eq!(item.status, "Failed");
+        assert_eq!(item.region, None);
+    }
+}
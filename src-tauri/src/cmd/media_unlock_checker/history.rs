@@ -0,0 +1,139 @@
+//! 解锁检测历史的定时复检与查询，记录保存在 `core::unlock_history`
+
+use tauri::command;
+
+use crate::{
+    config::Config,
+    core::{
+        handle,
+        unlock_history::{UnlockHistory, UnlockRecord, refresh_unlock_groups},
+    },
+    logging,
+    utils::logging::Type,
+};
+
+use super::{UnlockItem, matrix, run_unlock_checks};
+
+/// 沿嵌套分组的当前选择查找实际节点时的最大深度，防止循环引用
+const MAX_GROUP_DEPTH: usize = 8;
+
+/// 记录一次检测结果
+pub(super) async fn record(node: Option<&str>, items: &[UnlockItem]) {
+    let results = items.iter().map(UnlockItem::as_result).collect::<Vec<_>>();
+    UnlockHistory::global().record(node, &results).await;
+}
+
+/// 复检的代理组：设置中的分组，未设置时全局模式为 GLOBAL，否则为配置中的第一个代理组
+async fn recheck_group() -> Option<String> {
+    let group = Config::verge()
+        .await
+        .latest_ref()
+        .unlock_recheck_group
+        .clone()
+        .filter(|group| !group.is_empty());
+    if group.is_some() {
+        return group;
+    }
+
+    let global = Config::clash()
+        .await
+        .latest_ref()
+        .0
+        .get("mode")
+        .and_then(|mode| mode.as_str())
+        == Some("global");
+    if global {
+        return Some("GLOBAL".into());
+    }
+    let runtime = Config::runtime().await;
+    let runtime = runtime.latest_ref();
+    runtime
+        .config
+        .as_ref()?
+        .get("proxy-groups")?
+        .as_sequence()?
+        .iter()
+        .find_map(|group| group.get("name")?.as_str().map(str::to_string))
+}
+
+/// 代理组当前实际使用的节点，选中的是分组时继续查找该分组的选择
+async fn selected_node(group: &str) -> Result<String, String> {
+    let proxies = handle::Handle::mihomo()
+        .await
+        .get_proxies()
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(group_data) = proxies.proxies.get(group) else {
+        return Err(format!("proxy group not found: {group}"));
+    };
+    let mut node = group_data.now.clone().unwrap_or_default();
+    for _ in 0..MAX_GROUP_DEPTH {
+        match proxies
+            .proxies
+            .get(&node)
+            .and_then(|proxy| proxy.now.clone())
+        {
+            Some(now) if !now.is_empty() => node = now,
+            _ => break,
+        }
+    }
+    if node.is_empty() {
+        return Err(format!("proxy group {group} has no selected node"));
+    }
+    Ok(node)
+}
+
+/// 定时复检：经检测入站检测复检代理组当前选中的节点，按节点记录，结果变化时发出通知
+///
+/// 无界面守护模式下无法切换检测分组，按当前的代理设置检测，出口节点未知。
+pub async fn recheck_unlock() {
+    if handle::Handle::try_app_handle().is_none() {
+        match run_unlock_checks(None).await {
+            Ok(items) => {
+                record(None, &items).await;
+                refresh_unlock_groups().await;
+            }
+            Err(e) => {
+                logging!(warn, Type::Network, "定时解锁检测失败: {}", e);
+            }
+        }
+        return;
+    }
+
+    let Some(group) = recheck_group().await else {
+        logging!(warn, Type::Network, "定时解锁检测失败: 没有可用的代理组");
+        return;
+    };
+    let result = match selected_node(&group).await {
+        Ok(node) => matrix::recheck_node(&node).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(result) => {
+            if let Some(e) = result.error {
+                logging!(
+                    warn,
+                    Type::Network,
+                    "定时解锁检测节点 {} 失败: {}",
+                    result.node,
+                    e
+                );
+            }
+        }
+        Err(e) => {
+            logging!(warn, Type::Network, "定时解锁检测失败: {}", e);
+        }
+    }
+}
+
+/// 查询某服务的解锁历史，可按节点过滤
+#[command]
+pub async fn get_unlock_history(
+    service: String,
+    node: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<UnlockRecord>, String> {
+    Ok(UnlockHistory::global()
+        .query(&service, node.as_deref(), limit)
+        .await)
+}
@@ -0,0 +1,258 @@
+//! 按代理组逐个节点检测解锁情况
+//!
+//! 检测期间在运行时配置中注入一个只转发到隐藏检测分组的本地入站（见
+//! `enhance::use_unlock_check_listener`），依次在检测分组中选中组内的每个节点，再经由该入站
+//! 运行全部检测，得到节点 × 服务的结果矩阵。检测结束后移除该入站，用户代理组的选择与分流规则
+//! 都不受影响。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::{Emitter, command};
+use tokio::sync::Notify;
+
+use crate::{
+    config::Config,
+    core::{CoreManager, handle},
+    enhance::{UNLOCK_CHECK_GROUP, set_unlock_check_active, unlock_check_proxy},
+    logging,
+    process::AsyncHandler,
+    utils::logging::Type,
+};
+
+use super::{UnlockItem, history, run_unlock_checks};
+
+static MATRIX_RUNNING: AtomicBool = AtomicBool::new(false);
+static MATRIX_CANCELLED: AtomicBool = AtomicBool::new(false);
+static MATRIX_CANCEL_NOTIFY: Notify = Notify::const_new();
+
+/// 单个节点的检测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeUnlockResult {
+    pub node: String,
+    pub items: Vec<UnlockItem>,
+    pub error: Option<String>,
+}
+
+/// 节点 × 服务的检测矩阵
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UnlockMatrix {
+    pub group: String,
+    /// 列顺序，按首次出现的服务名排列
+    pub services: Vec<String>,
+    pub nodes: Vec<NodeUnlockResult>,
+    pub cancelled: bool,
+}
+
+impl UnlockMatrix {
+    fn push(&mut self, result: NodeUnlockResult) {
+        for item in &result.items {
+            if !self.services.contains(&item.name) {
+                self.services.push(item.name.clone());
+            }
+        }
+        self.nodes.push(result);
+    }
+}
+
+/// 结束、出错或被取消时清除运行标记
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        MATRIX_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// 检测期间注入检测入站，结束时重新生成运行时配置将其移除
+struct ListenerSession {
+    finished: bool,
+}
+
+impl ListenerSession {
+    /// 注入检测入站并返回其代理地址
+    async fn start() -> Result<(Self, String), String> {
+        set_unlock_check_active(true);
+        let session = Self { finished: false };
+        match CoreManager::global().update_config().await {
+            Ok((true, _)) => {}
+            Ok((false, msg)) => return Err(msg),
+            Err(e) => return Err(e.to_string()),
+        }
+        let proxy = check_proxy().await?;
+        Ok((session, proxy))
+    }
+
+    /// 移除检测入站，同时按最新的检测结果更新解锁代理组
+    async fn finish(mut self) {
+        self.finished = true;
+        Self::restore().await;
+    }
+
+    async fn restore() {
+        set_unlock_check_active(false);
+        match CoreManager::global().update_config().await {
+            Ok((true, _)) => handle::Handle::refresh_clash(),
+            Ok((false, msg)) => {
+                logging!(warn, Type::Network, "移除解锁检测入站失败: {}", msg);
+            }
+            Err(e) => {
+                logging!(warn, Type::Network, "移除解锁检测入站失败: {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for ListenerSession {
+    fn drop(&mut self) {
+        if !self.finished {
+            AsyncHandler::spawn(Self::restore);
+        }
+    }
+}
+
+async fn group_nodes(group: &str) -> Result<Vec<String>, String> {
+    let proxies = handle::Handle::mihomo()
+        .await
+        .get_proxies()
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(group_data) = proxies.proxies.get(group) else {
+        return Err(format!("proxy group not found: {group}"));
+    };
+    let nodes = group_data.all.clone().unwrap_or_default();
+    if nodes.is_empty() {
+        return Err(format!("proxy group {group} has no nodes"));
+    }
+    Ok(nodes)
+}
+
+/// 检测入站的代理地址，运行时配置中没有检测入站时返回错误
+async fn check_proxy() -> Result<String, String> {
+    let runtime = Config::runtime().await;
+    runtime
+        .latest_ref()
+        .config
+        .as_ref()
+        .and_then(unlock_check_proxy)
+        .ok_or_else(|| "unlock check listener is not available".into())
+}
+
+async fn check_node(proxy: &str, node: &str) -> NodeUnlockResult {
+    let selected = handle::Handle::mihomo()
+        .await
+        .select_node_for_group(UNLOCK_CHECK_GROUP, node)
+        .await;
+    let (items, error) = match selected {
+        Ok(_) => match run_unlock_checks(Some(proxy)).await {
+            Ok(items) => {
+                history::record(Some(node), &items).await;
+                (items, None)
+            }
+            Err(e) => (Vec::new(), Some(e)),
+        },
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+    NodeUnlockResult {
+        node: node.to_string(),
+        items,
+        error,
+    }
+}
+
+/// 逐个检测代理组中的节点的解锁情况
+#[command]
+pub async fn check_group_media_unlock(group: String) -> Result<UnlockMatrix, String> {
+    if MATRIX_RUNNING.swap(true, Ordering::AcqRel) {
+        return Err("unlock matrix check is already running".into());
+    }
+    let _guard = RunningGuard;
+    MATRIX_CANCELLED.store(false, Ordering::Release);
+
+    let nodes = group_nodes(&group).await?;
+    let (session, proxy) = ListenerSession::start().await?;
+
+    let mut matrix = UnlockMatrix {
+        group: group.clone(),
+        ..UnlockMatrix::default()
+    };
+    for node in nodes {
+        if MATRIX_CANCELLED.load(Ordering::Acquire) {
+            matrix.cancelled = true;
+            break;
+        }
+        logging!(
+            info,
+            Type::Network,
+            "检测代理组 {} 节点 {} 的解锁情况",
+            group,
+            node
+        );
+        let result = tokio::select! {
+            result = check_node(&proxy, &node) => result,
+            _ = MATRIX_CANCEL_NOTIFY.notified() => {
+                matrix.cancelled = true;
+                break;
+            }
+        };
+        let _ = handle::Handle::app_handle().emit("verge://unlock-matrix-progress", &result);
+        matrix.push(result);
+    }
+
+    session.finish().await;
+    Ok(matrix)
+}
+
+/// 定时复检单个节点，与逐节点检测互斥
+pub(super) async fn recheck_node(node: &str) -> Result<NodeUnlockResult, String> {
+    if MATRIX_RUNNING.swap(true, Ordering::AcqRel) {
+        return Err("unlock matrix check is already running".into());
+    }
+    let _guard = RunningGuard;
+
+    let (session, proxy) = ListenerSession::start().await?;
+    let result = check_node(&proxy, node).await;
+    session.finish().await;
+    Ok(result)
+}
+
+/// 取消正在进行的节点解锁检测
+#[command]
+pub async fn cancel_group_media_unlock() -> Result<(), String> {
+    if MATRIX_RUNNING.load(Ordering::Acquire) {
+        MATRIX_CANCELLED.store(true, Ordering::Release);
+        MATRIX_CANCEL_NOTIFY.notify_waiters();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, status: &str) -> UnlockItem {
+        UnlockItem {
+            name: name.into(),
+            status: status.into(),
+            region: None,
+            check_time: None,
+        }
+    }
+
+    #[test]
+    fn test_matrix_columns() {
+        let mut matrix = UnlockMatrix::default();
+        matrix.push(NodeUnlockResult {
+            node: "HK".into(),
+            items: vec![item("Netflix", "Yes"), item("Claude", "No")],
+            error: None,
+        });
+        matrix.push(NodeUnlockResult {
+            node: "US".into(),
+            items: vec![item("Claude", "Yes"), item("Spotify", "Yes")],
+            error: None,
+        });
+        assert_eq!(matrix.services, ["Netflix", "Claude", "Spotify"]);
+        assert_eq!(matrix.nodes.len(), 2);
+    }
+}
@@ -4,88 +4,112 @@ use reqwest::Client;
 use tauri::command;
 use tokio::{sync::Mutex, task::JoinSet};
 
-use crate::{logging, utils::logging::Type};
+use crate::{core::unlock_history::refresh_unlock_groups, logging, utils::logging::Type};
 
 mod bahamut;
-mod bilibili;
 mod chatgpt;
 mod claude;
+mod declarative;
 mod disney_plus;
 mod gemini;
+mod history;
+mod matrix;
 mod netflix;
 mod prime_video;
-mod spotify;
-mod tiktok;
 mod types;
 mod utils;
 mod youtube;
 
+pub use history::{get_unlock_history, recheck_unlock};
+pub use matrix::{cancel_group_media_unlock, check_group_media_unlock};
 pub use types::UnlockItem;
 
 use bahamut::check_bahamut_anime;
-use bilibili::{check_bilibili_china_mainland, check_bilibili_hk_mc_tw};
 use chatgpt::check_chatgpt_combined;
 use claude::check_claude;
 use disney_plus::check_disney_plus;
 use gemini::check_gemini;
 use netflix::check_netflix;
 use prime_video::check_prime_video;
-use spotify::check_spotify;
-use tiktok::check_tiktok;
 use youtube::check_youtube_premium;
 
 #[command]
 pub async fn get_unlock_items() -> Result<Vec<UnlockItem>, String> {
-    Ok(types::default_unlock_items())
+    let mut items = types::default_unlock_items();
+    for check in declarative::load_checks() {
+        if !items.iter().any(|item| item.name == check.name) {
+            items.push(UnlockItem::pending(&check.name));
+        }
+    }
+    Ok(items)
 }
 
-#[command]
-pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
-    let client = match Client::builder()
+/// `proxy` 为空时使用系统代理环境，否则所有请求经由该代理发出
+fn build_client(follow_redirects: bool, proxy: Option<&str>) -> Result<Client, String> {
+    let redirect = if follow_redirects {
+        reqwest::redirect::Policy::default()
+    } else {
+        reqwest::redirect::Policy::none()
+    };
+    let mut builder = Client::builder();
+    if let Some(proxy) = proxy {
+        let proxy = reqwest::Proxy::all(proxy).map_err(|e| format!("无效的代理地址: {e}"))?;
+        builder = builder.proxy(proxy);
+    }
+    builder
         .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36")
         .timeout(std::time::Duration::from_secs(30))
         .danger_accept_invalid_certs(true)
         .danger_accept_invalid_hostnames(true)
         .tcp_keepalive(std::time::Duration::from_secs(60))
         .connection_verbose(true)
-        .build() {
-        Ok(client) => client,
-        Err(e) => return Err(format!("创建HTTP客户端失败: {e}")),
+        .redirect(redirect)
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {e}"))
+}
+
+#[command]
+pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
+    let results = run_unlock_checks(None).await?;
+    // 按当前代理设置检测时无法确定出口节点
+    history::record(None, &results).await;
+    refresh_unlock_groups().await;
+    Ok(results)
+}
+
+/// 运行全部检测，`proxy` 为空时按当前的代理设置访问
+pub(crate) async fn run_unlock_checks(proxy: Option<&str>) -> Result<Vec<UnlockItem>, String> {
+    let client = build_client(true, proxy)?;
+    let no_redirect_client = Arc::new(build_client(false, proxy)?);
+
+    // 用户定义的同名检测项覆盖内置实现
+    let checks = declarative::load_checks();
+    let builtin_enabled = |names: &[&str]| {
+        !names
+            .iter()
+            .all(|name| checks.iter().any(|c| c.name == *name))
     };
 
     let results = Arc::new(Mutex::new(Vec::new()));
     let mut tasks = JoinSet::new();
     let client_arc = Arc::new(client);
 
-    {
-        let client = Arc::clone(&client_arc);
-        let results = Arc::clone(&results);
-        tasks.spawn(async move {
-            let result = check_bilibili_china_mainland(&client).await;
-            results.lock().await.push(result);
-        });
-    }
-
-    {
-        let client = Arc::clone(&client_arc);
-        let results = Arc::clone(&results);
-        tasks.spawn(async move {
-            let result = check_bilibili_hk_mc_tw(&client).await;
-            results.lock().await.push(result);
-        });
-    }
-
-    {
+    if builtin_enabled(&["ChatGPT iOS", "ChatGPT Web"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
+        let overridden: Vec<String> = checks.iter().map(|check| check.name.clone()).collect();
         tasks.spawn(async move {
             let chatgpt_results = check_chatgpt_combined(&client).await;
             let mut results = results.lock().await;
-            results.extend(chatgpt_results);
+            results.extend(
+                chatgpt_results
+                    .into_iter()
+                    .filter(|item| !overridden.contains(&item.name)),
+            );
         });
     }
 
-    {
+    if builtin_enabled(&["Claude"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
@@ -94,7 +118,7 @@ pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
         });
     }
 
-    {
+    if builtin_enabled(&["Gemini"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
@@ -103,7 +127,7 @@ pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
         });
     }
 
-    {
+    if builtin_enabled(&["Youtube Premium"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
@@ -112,7 +136,7 @@ pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
         });
     }
 
-    {
+    if builtin_enabled(&["Bahamut Anime"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
@@ -121,7 +145,7 @@ pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
         });
     }
 
-    {
+    if builtin_enabled(&["Netflix"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
@@ -130,7 +154,7 @@ pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
         });
     }
 
-    {
+    if builtin_enabled(&["Disney+"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
@@ -139,29 +163,21 @@ pub async fn check_media_unlock() -> Result<Vec<UnlockItem>, String> {
         });
     }
 
-    {
+    if builtin_enabled(&["Prime Video"]) {
         let client = Arc::clone(&client_arc);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
-            let result = check_spotify(&client).await;
-            results.lock().await.push(result);
-        });
-    }
-
-    {
-        let client = Arc::clone(&client_arc);
-        let results = Arc::clone(&results);
-        tasks.spawn(async move {
-            let result = check_tiktok(&client).await;
+            let result = check_prime_video(&client).await;
             results.lock().await.push(result);
         });
     }
 
-    {
+    for check in checks.iter().cloned() {
         let client = Arc::clone(&client_arc);
+        let no_redirect_client = Arc::clone(&no_redirect_client);
         let results = Arc::clone(&results);
         tasks.spawn(async move {
-            let result = check_prime_video(&client).await;
+            let result = check.run(&client, &no_redirect_client).await;
             results.lock().await.push(result);
         });
     }
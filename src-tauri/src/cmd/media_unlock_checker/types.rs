@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::core::unlock_history::UnlockResult;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnlockItem {
     pub name: String,
@@ -17,6 +19,14 @@ impl UnlockItem {
             check_time: None,
         }
     }
+
+    pub fn as_result(&self) -> UnlockResult<'_> {
+        UnlockResult {
+            service: &self.name,
+            status: &self.status,
+            region: self.region.as_deref(),
+        }
+    }
 }
 
 const DEFAULT_UNLOCK_ITEM_NAMES: [&str; 13] = [
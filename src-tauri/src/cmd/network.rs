@@ -1,5 +1,10 @@
 use super::CmdResult;
-use crate::core::{EventDrivenProxyManager, async_proxy_query::AsyncProxyQuery};
+use crate::core::{
+    EventDrivenProxyManager,
+    async_proxy_query::AsyncProxyQuery,
+    guard_history::{GuardEvent, ProxyGuardHistory},
+    network_switch::{self, NetworkSnapshot, NetworkSwitchRecord, NetworkSwitcher},
+};
 use crate::process::AsyncHandler;
 use crate::wrap_err;
 use network_interface::NetworkInterface;
@@ -94,3 +99,28 @@ pub fn get_network_interfaces_info() -> CmdResult<Vec<NetworkInterface>> {
 
     Ok(result)
 }
+
+/// 获取当前网络环境（网卡、默认网关、SSID），用于编写自动切换规则
+#[tauri::command]
+pub async fn get_network_snapshot() -> CmdResult<NetworkSnapshot> {
+    Ok(network_switch::detect_network().await)
+}
+
+/// 获取网络环境自动切换记录
+#[tauri::command]
+pub fn get_network_switch_history(limit: Option<usize>) -> CmdResult<Vec<NetworkSwitchRecord>> {
+    Ok(NetworkSwitcher::global().history(limit))
+}
+
+/// 获取代理守护检测到的设置篡改记录
+#[tauri::command]
+pub fn get_proxy_guard_history(limit: Option<usize>) -> CmdResult<Vec<GuardEvent>> {
+    Ok(ProxyGuardHistory::global().history(limit))
+}
+
+/// 清空代理守护篡改记录
+#[tauri::command]
+pub fn clear_proxy_guard_history() -> CmdResult {
+    ProxyGuardHistory::global().clear();
+    Ok(())
+}
@@ -18,3 +18,15 @@ pub async fn sync_tray_proxy_selection() -> CmdResult<()> {
         }
     }
 }
+
+/// 获取已写入代理设置的开发工具
+#[tauri::command]
+pub fn get_tool_proxy_status() -> CmdResult<Vec<String>> {
+    use crate::core::tool_proxy::ToolProxy;
+
+    Ok(ToolProxy::global()
+        .applied()
+        .into_iter()
+        .map(String::from)
+        .collect())
+}
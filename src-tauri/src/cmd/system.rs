@@ -1,6 +1,9 @@
 use super::CmdResult;
 use crate::{
-    core::{CoreManager, handle},
+    core::{
+        CoreManager, handle,
+        resource_monitor::{CoreResourceMonitor, CoreResourceSample},
+    },
     logging,
     module::sysinfo::PlatformSpecification,
     utils::logging::Type,
@@ -49,6 +52,12 @@ pub async fn get_running_mode() -> Result<String, String> {
     Ok(CoreManager::global().get_running_mode().to_string())
 }
 
+/// 获取内核进程资源采样记录，`limit` 为返回的最近采样数量
+#[tauri::command]
+pub fn get_core_resource_history(limit: Option<usize>) -> CmdResult<Vec<CoreResourceSample>> {
+    Ok(CoreResourceMonitor::global().history(limit))
+}
+
 /// 获取应用的运行时间（毫秒）
 #[tauri::command]
 pub fn get_app_uptime() -> CmdResult<i64> {
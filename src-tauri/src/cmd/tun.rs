@@ -103,3 +103,78 @@ pub async fn toggle_tun_mode() -> CmdResult<bool> {
 
     Ok(new_state)
 }
+
+/// 内核二进制是否已具备无 root 运行 TUN 所需的能力（仅 Linux）
+#[tauri::command]
+pub async fn check_core_capabilities() -> CmdResult<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(crate::utils::capability::core_has_tun_capabilities().await)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    Ok(false)
+}
+
+/// TUN 启用前检查，返回各项 pass/warn/fail 结果与修复建议（仅 Linux）
+#[tauri::command]
+pub async fn tun_preflight() -> CmdResult<serde_json::Value> {
+    #[cfg(target_os = "linux")]
+    {
+        let checks = crate::core::tun_preflight::run_preflight().await;
+        serde_json::to_value(checks).map_err(|err| err.to_string())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    Ok(serde_json::Value::Array(Vec::new()))
+}
+
+/// 为内核二进制授予网络能力，并以能力模式重启内核（仅 Linux）
+#[tauri::command]
+pub async fn grant_core_capabilities() -> CmdResult {
+    #[cfg(target_os = "linux")]
+    {
+        use crate::core::{CoreManager, RunningMode, handle};
+
+        crate::utils::capability::grant_tun_capabilities()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        if CoreManager::global().get_running_mode() != RunningMode::Service {
+            CoreManager::global()
+                .restart_core()
+                .await
+                .map_err(|err| err.to_string())?;
+            handle::Handle::refresh_clash();
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    Err("Capability mode is only supported on Linux".into())
+}
+
+/// 移除内核二进制上的网络能力（仅 Linux）
+#[tauri::command]
+pub async fn revoke_core_capabilities() -> CmdResult {
+    #[cfg(target_os = "linux")]
+    {
+        use crate::core::{CoreManager, RunningMode, handle};
+
+        crate::utils::capability::revoke_tun_capabilities()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        if CoreManager::global().get_running_mode() == RunningMode::Capability {
+            CoreManager::global()
+                .restart_core()
+                .await
+                .map_err(|err| err.to_string())?;
+            handle::Handle::refresh_clash();
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    Err("Capability mode is only supported on Linux".into())
+}
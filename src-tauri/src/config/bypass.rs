@@ -0,0 +1,381 @@
+use std::{net::IpAddr, str::FromStr};
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_yaml_ng::{Mapping, Value};
+
+use crate::utils::cidr::IpCidr;
+
+/// 从规则推导的绕过条目上限，避免系统代理设置过长
+const MAX_DERIVED_ENTRIES: usize = 256;
+
+/// 绕过条目类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BypassKind {
+    /// 完整域名，如 `example.com`
+    Domain,
+    /// 含 `*` 的通配，如 `*.example.com`、`192.168.*`
+    Wildcard,
+    /// IP 网段或单个 IP
+    Cidr,
+    /// 本地地址（不含点的主机名）
+    Local,
+}
+
+/// 系统代理绕过条目
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IBypassEntry {
+    pub kind: BypassKind,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// 生成绕过字符串的目标平台
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BypassPlatform {
+    Windows,
+    Macos,
+    Gnome,
+    Kde,
+}
+
+impl BypassPlatform {
+    pub const fn current() -> Self {
+        #[cfg(target_os = "windows")]
+        {
+            Self::Windows
+        }
+        #[cfg(target_os = "macos")]
+        {
+            Self::Macos
+        }
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        {
+            Self::Gnome
+        }
+    }
+
+    const fn separator(self) -> &'static str {
+        match self {
+            Self::Windows => ";",
+            _ => ",",
+        }
+    }
+}
+
+fn is_domain_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_domain(value: &str) -> bool {
+    value.len() <= 253 && value.split('.').all(is_domain_label)
+}
+
+impl IBypassEntry {
+    pub fn new(kind: BypassKind, value: impl Into<String>) -> Self {
+        Self {
+            kind,
+            value: value.into(),
+        }
+    }
+
+    /// 校验条目格式
+    pub fn validate(&self) -> Result<()> {
+        let value = self.value.trim();
+        match self.kind {
+            BypassKind::Domain => {
+                if !is_domain(value) {
+                    bail!("invalid bypass domain: {value:?}");
+                }
+            }
+            BypassKind::Wildcard => {
+                let pattern = value.replace('*', "x");
+                if !value.contains('*') || !is_domain(pattern.trim_start_matches('.')) {
+                    bail!("invalid bypass wildcard: {value:?}");
+                }
+            }
+            BypassKind::Cidr => {
+                value.parse::<IpCidr>()?;
+            }
+            BypassKind::Local => {}
+        }
+        Ok(())
+    }
+
+    /// 按平台生成条目，部分平台不支持的写法会被展开或跳过
+    fn render(&self, platform: BypassPlatform) -> Vec<String> {
+        let value = self.value.trim().to_ascii_lowercase();
+        match (self.kind, platform) {
+            (BypassKind::Local, BypassPlatform::Windows | BypassPlatform::Macos) => {
+                vec!["<local>".into()]
+            }
+            (BypassKind::Local, _) => vec!["localhost".into(), "127.0.0.0/8".into(), "::1".into()],
+            (BypassKind::Wildcard, BypassPlatform::Kde) => match value.strip_prefix("*.") {
+                // KIO 以 `.example.com` 表示后缀匹配
+                Some(suffix) => vec![format!(".{suffix}")],
+                None => vec![value],
+            },
+            (BypassKind::Cidr, BypassPlatform::Windows) => value
+                .parse::<IpCidr>()
+                .map(windows_cidr_patterns)
+                .unwrap_or_default(),
+            _ => vec![value],
+        }
+    }
+}
+
+/// Windows 不支持 CIDR，按字节边界展开为 `10.*` 形式的通配
+fn windows_cidr_patterns(cidr: IpCidr) -> Vec<String> {
+    let IpAddr::V4(addr) = cidr.addr() else {
+        // IPv6 网段无法用通配表示
+        return Vec::new();
+    };
+    let prefix = u32::from(cidr.prefix());
+    if prefix == 0 {
+        return vec!["*".into()];
+    }
+    // 前缀向上取整到字节边界，最多展开 128 条
+    let octets = prefix.div_ceil(8);
+    let count = 1u32 << (octets * 8 - prefix);
+    let base = u32::from(addr) & u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+    let step = 1u32 << (32 - octets * 8);
+
+    (0..count)
+        .map(|index| {
+            let network = (base + index * step).to_be_bytes();
+            let mut parts: Vec<String> = network
+                .iter()
+                .take(octets as usize)
+                .map(u8::to_string)
+                .collect();
+            if octets < 4 {
+                parts.push("*".into());
+            }
+            parts.join(".")
+        })
+        .collect()
+}
+
+impl FromStr for IBypassEntry {
+    type Err = anyhow::Error;
+
+    /// 按内容推断类型，用于兼容旧的字符串格式
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let entry = if value.eq_ignore_ascii_case("<local>") {
+            Self::new(BypassKind::Local, "")
+        } else if value.contains('*') {
+            Self::new(BypassKind::Wildcard, value)
+        } else if value.contains('/') || value.parse::<IpAddr>().is_ok() {
+            Self::new(BypassKind::Cidr, value)
+        } else {
+            Self::new(BypassKind::Domain, value)
+        };
+        entry.validate()?;
+        Ok(entry)
+    }
+}
+
+/// 校验条目列表，返回第一条错误
+pub fn validate_bypass(entries: &[IBypassEntry]) -> Result<()> {
+    entries.iter().try_for_each(IBypassEntry::validate)
+}
+
+/// 生成指定平台的绕过字符串，重复条目只保留一次
+pub fn render_bypass(entries: &[IBypassEntry], platform: BypassPlatform) -> String {
+    let mut items: Vec<String> = Vec::new();
+    for item in entries.iter().flat_map(|entry| entry.render(platform)) {
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    }
+    items.join(platform.separator())
+}
+
+/// 解析逗号或分号分隔的绕过字符串，忽略无法识别的条目
+pub fn parse_bypass(bypass: &str) -> Vec<IBypassEntry> {
+    bypass
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| item.parse().ok())
+        .collect()
+}
+
+/// 严格校验逗号或分号分隔的绕过字符串，任一条目无法识别时返回错误
+pub fn validate_bypass_str(bypass: &str) -> Result<()> {
+    bypass
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .try_for_each(|item| item.parse::<IBypassEntry>().map(|_| ()))
+}
+
+/// 从运行时配置中 DIRECT 策略的规则推导绕过条目
+///
+/// 前缀为 0 的网段（如 `0.0.0.0/0`）会让所有地址绕过代理，不予推导
+pub fn derive_bypass_from_rules(config: Option<&Mapping>) -> Vec<IBypassEntry> {
+    let rules = config
+        .and_then(|config| config.get("rules"))
+        .and_then(Value::as_sequence)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    rules
+        .iter()
+        .filter_map(Value::as_str)
+        .filter_map(|rule| {
+            let parts: Vec<&str> = rule.split(',').map(str::trim).collect();
+            let (kind, payload, policy) = (parts.first()?, parts.get(1)?, parts.get(2)?);
+            if !policy.eq_ignore_ascii_case("DIRECT") {
+                return None;
+            }
+            let entry = match kind.to_ascii_uppercase().as_str() {
+                "DOMAIN" => IBypassEntry::new(BypassKind::Domain, *payload),
+                "DOMAIN-SUFFIX" => IBypassEntry::new(BypassKind::Wildcard, format!("*.{payload}")),
+                "IP-CIDR" | "IP-CIDR6" => {
+                    if payload.parse::<IpCidr>().ok()?.prefix() == 0 {
+                        return None;
+                    }
+                    IBypassEntry::new(BypassKind::Cidr, *payload)
+                }
+                _ => return None,
+            };
+            entry.validate().ok().map(|_| entry)
+        })
+        .take(MAX_DERIVED_ENTRIES)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<IBypassEntry> {
+        vec![
+            IBypassEntry::new(BypassKind::Domain, "example.com"),
+            IBypassEntry::new(BypassKind::Wildcard, "*.Example.org"),
+            IBypassEntry::new(BypassKind::Cidr, "172.16.0.0/12"),
+            IBypassEntry::new(BypassKind::Local, ""),
+        ]
+    }
+
+    #[test]
+    fn test_validate_bypass() {
+        assert!(validate_bypass(&entries()).is_ok());
+        assert!(
+            IBypassEntry::new(BypassKind::Domain, "exa mple.com")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            IBypassEntry::new(BypassKind::Domain, "*.example.com")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            IBypassEntry::new(BypassKind::Wildcard, "example.com")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            IBypassEntry::new(BypassKind::Wildcard, "192.168.*")
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            IBypassEntry::new(BypassKind::Cidr, "10.0.0.0/40")
+                .validate()
+                .is_err()
+        );
+        assert!(
+            IBypassEntry::new(BypassKind::Domain, "")
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_bypass_str() {
+        assert!(validate_bypass_str("localhost,127.*;10.0.0.0/8, <local>,").is_ok());
+        assert!(validate_bypass_str("example.com,exa mple.com").is_err());
+        assert!(validate_bypass_str("10.0.0.0/40").is_err());
+    }
+
+    #[test]
+    fn test_render_bypass() {
+        let entries = entries();
+        let windows = render_bypass(&entries, BypassPlatform::Windows);
+        assert!(windows.starts_with("example.com;*.example.org;172.16.*;172.17.*;"));
+        assert!(windows.ends_with("172.31.*;<local>"));
+
+        assert_eq!(
+            render_bypass(&entries, BypassPlatform::Macos),
+            "example.com,*.example.org,172.16.0.0/12,<local>"
+        );
+        assert_eq!(
+            render_bypass(&entries, BypassPlatform::Gnome),
+            "example.com,*.example.org,172.16.0.0/12,localhost,127.0.0.0/8,::1"
+        );
+        assert_eq!(
+            render_bypass(&entries, BypassPlatform::Kde),
+            "example.com,.example.org,172.16.0.0/12,localhost,127.0.0.0/8,::1"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_windows_cidr_patterns() {
+        let patterns = |cidr: &str| windows_cidr_patterns(cidr.parse().unwrap());
+        assert_eq!(patterns("10.0.0.0/8"), vec!["10.*"]);
+        assert_eq!(patterns("192.168.1.0/24"), vec!["192.168.1.*"]);
+        assert_eq!(patterns("192.168.1.7"), vec!["192.168.1.7"]);
+        assert_eq!(patterns("172.16.0.0/12").len(), 16);
+        assert!(patterns("fd00::/8").is_empty());
+    }
+
+    #[test]
+    fn test_parse_and_derive_bypass() {
+        let parsed = parse_bypass("localhost;127.*;10.0.0.0/8,<local>, ::1");
+        assert_eq!(
+            parsed.iter().map(|entry| entry.kind).collect::<Vec<_>>(),
+            vec![
+                BypassKind::Domain,
+                BypassKind::Wildcard,
+                BypassKind::Cidr,
+                BypassKind::Local,
+                BypassKind::Cidr
+            ]
+        );
+
+        let config: Mapping = serde_yaml_ng::from_str(
+            "
+rules:
+  - DOMAIN,intranet.corp,DIRECT
+  - DOMAIN-SUFFIX,cn,DIRECT
+  - IP-CIDR,10.0.0.0/8,DIRECT,no-resolve
+  - IP-CIDR,0.0.0.0/0,DIRECT
+  - IP-CIDR6,::/0,DIRECT
+  - DOMAIN-SUFFIX,google.com,Proxy
+  - GEOIP,CN,DIRECT
+  - MATCH,Proxy
+",
+        )
+        .unwrap_or_default();
+        assert_eq!(
+            derive_bypass_from_rules(Some(&config)),
+            vec![
+                IBypassEntry::new(BypassKind::Domain, "intranet.corp"),
+                IBypassEntry::new(BypassKind::Wildcard, "*.cn"),
+                IBypassEntry::new(BypassKind::Cidr, "10.0.0.0/8"),
+            ]
+        );
+    }
+}
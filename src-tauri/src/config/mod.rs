@@ -1,3 +1,4 @@
+mod bypass;
 mod clash;
 #[allow(clippy::module_inception)]
 mod config;
@@ -7,7 +8,9 @@ pub mod profiles;
 mod runtime;
 mod verge;
 
-pub use self::{clash::*, config::*, encrypt::*, prfitem::*, profiles::*, runtime::*, verge::*};
+pub use self::{
+    bypass::*, clash::*, config::*, encrypt::*, prfitem::*, profiles::*, runtime::*, verge::*,
+};
 
 pub const DEFAULT_PAC: &str = r#"function FindProxyForURL(url, host) {
   return "PROXY 127.0.0.1:%mixed-port%; SOCKS5 127.0.0.1:%mixed-port%; DIRECT;";
@@ -1,5 +1,5 @@
 use crate::{
-    config::{DEFAULT_PAC, deserialize_encrypted, serialize_encrypted},
+    config::{DEFAULT_PAC, IBypassEntry, deserialize_encrypted, serialize_encrypted},
     logging,
     utils::{dirs, help, i18n, logging::Type},
 };
@@ -65,6 +65,9 @@ pub struct IVerge {
     /// clash tun mode
     pub enable_tun_mode: Option<bool>,
 
+    /// TUN 按用户、应用、网卡与路由的包含/排除设置
+    pub tun_filter: Option<ITunFilter>,
+
     /// can the app auto startup
     pub enable_auto_launch: Option<bool>,
 
@@ -86,6 +89,12 @@ pub struct IVerge {
     /// set system proxy bypass
     pub system_proxy_bypass: Option<String>,
 
+    /// typed bypass list, takes precedence over `system_proxy_bypass` when set
+    pub bypass_entries: Option<Vec<IBypassEntry>>,
+
+    /// append bypass entries derived from the profile's DIRECT rules
+    pub bypass_from_rules: Option<bool>,
+
     /// proxy guard duration
     pub proxy_guard_duration: Option<u64>,
 
@@ -95,6 +104,9 @@ pub struct IVerge {
     /// pac script content
     pub pac_file_content: Option<String>,
 
+    /// generate pac script from runtime rules instead of `pac_file_content`
+    pub pac_from_rules: Option<bool>,
+
     /// proxy host address
     pub proxy_host: Option<String>,
 
@@ -222,6 +234,42 @@ pub struct IVerge {
     /// 流量配额提醒设置
     pub traffic_quota_reminder: Option<ITrafficQuotaReminder>,
 
+    /// 内核资源监控设置
+    pub core_resource_monitor: Option<ICoreResourceMonitor>,
+
+    /// 本地控制 API 的访问令牌
+    pub control_api_token: Option<String>,
+
+    /// 按网络环境自动切换订阅与模式
+    pub network_switch: Option<INetworkSwitch>,
+
+    /// 代理守护频繁被篡改时的提醒设置
+    pub proxy_guard_alert: Option<IProxyGuardAlert>,
+
+    /// Linux 系统代理的存储后端与环境变量文件设置
+    pub linux_proxy: Option<ILinuxProxy>,
+
+    /// 局域网 PAC 与连接说明服务
+    pub lan_pac_server: Option<ILanPacServer>,
+
+    /// 随系统代理写入开发工具（git、npm 等）的代理设置
+    pub tool_proxy: Option<IToolProxy>,
+
+    /// 解锁检测定时复检间隔（分钟），为空或 0 表示关闭
+    pub unlock_recheck_interval: Option<u64>,
+
+    /// 定时复检的代理组，复检该组当前选中的节点；为空时使用第一个代理组（全局模式为 GLOBAL）
+    pub unlock_recheck_group: Option<String>,
+
+    /// 按解锁检测结果自动生成的代理组
+    pub unlock_groups: Option<IUnlockGroups>,
+
+    /// 后台节点延迟探测间隔（分钟），随 enable_auto_delay_detection 启用，默认 10
+    pub delay_probe_interval: Option<u64>,
+
+    /// 按节点稳定性评分自动排列收藏的节点
+    pub sort_favorites_by_stability: Option<bool>,
+
     /// 使用系统标题栏（默认为 false，即使用自定义标题栏）
     pub window_use_system_titlebar: Option<bool>,
 }
@@ -236,6 +284,120 @@ pub struct ITrafficQuotaReminder {
     pub last_reminder: Option<u64>,
 }
 
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct ICoreResourceMonitor {
+    /// 是否启用采样，默认启用
+    pub enabled: Option<bool>,
+    /// 采样间隔（秒）
+    pub interval: Option<u64>,
+    /// 内存阈值（MB），为空或 0 表示不检测
+    pub memory_limit: Option<u64>,
+    /// 超过阈值持续多久后触发（秒）
+    pub sustained_secs: Option<u64>,
+    /// 触发后的动作：`warn` | `restart`
+    pub action: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct IProxyGuardAlert {
+    /// 是否启用提醒，默认关闭
+    pub enabled: Option<bool>,
+    /// 时间窗口内的篡改次数阈值
+    pub max_events: Option<usize>,
+    /// 统计时间窗口（秒）
+    pub window_secs: Option<u64>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct ILinuxProxy {
+    /// 代理存储：`auto` | `gsettings` | `kde` | `env`，默认 `auto` 按桌面环境检测
+    pub backend: Option<String>,
+    /// 同时写入 `~/.config/environment.d/` 下的环境变量文件
+    pub environment_d: Option<bool>,
+    /// 额外管理的 `/etc/environment` 风格文件路径
+    pub environment_file: Option<String>,
+}
+
+/// 未设置的字段保留订阅中的值，设置为空列表时移除订阅中的值
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct ITunFilter {
+    /// 只代理这些用户，`1000` 或 `1000-1999` 形式，仅 Linux/Android
+    pub include_uid: Option<Vec<String>>,
+    /// 不代理这些用户，格式同 `include_uid`
+    pub exclude_uid: Option<Vec<String>>,
+    /// 只代理这些应用包名，仅 Android
+    pub include_package: Option<Vec<String>>,
+    /// 不代理这些应用包名，仅 Android
+    pub exclude_package: Option<Vec<String>>,
+    /// 只代理这些网卡的流量，与 `exclude_interface` 互斥
+    pub include_interface: Option<Vec<String>>,
+    /// 不代理这些网卡的流量
+    pub exclude_interface: Option<Vec<String>>,
+    /// 不经过 TUN 路由的网段
+    pub route_exclude_address: Option<Vec<String>>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct IToolProxy {
+    /// 是否启用，默认关闭
+    pub enabled: Option<bool>,
+    /// 写入的工具：`git` | `npm` | `pnpm` | `pip` | `cargo` | `docker` | `apt`，
+    /// 未设置时为 git、npm、pnpm、pip、cargo
+    pub tools: Option<Vec<String>>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct IUnlockGroups {
+    /// 是否启用，默认关闭
+    pub enabled: Option<bool>,
+    /// 生成分组的服务名，与解锁检测项名称一致，如 `Netflix`、`ChatGPT Web`
+    pub services: Option<Vec<String>>,
+    /// 分组类型：`select` | `url-test`，默认 `select`
+    pub group_type: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct ILanPacServer {
+    /// 是否启用，默认关闭；仅在 allow-lan 开启时监听
+    pub enabled: Option<bool>,
+    /// 监听端口，默认 33332
+    pub port: Option<u16>,
+    /// 允许访问的网段，为空时只允许私有网段
+    pub allowed_cidrs: Option<Vec<String>>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct INetworkSwitch {
+    /// 是否启用，默认关闭
+    pub enabled: Option<bool>,
+    /// 检测间隔（秒）
+    pub interval: Option<u64>,
+    /// 切换规则，按顺序匹配，命中第一条即停止
+    pub rules: Option<Vec<INetworkSwitchRule>>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct INetworkSwitchRule {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+    /// 匹配条件：存在任一网卡（按名称）
+    pub interfaces: Option<Vec<String>>,
+    /// 匹配条件：默认网关地址
+    pub gateway: Option<String>,
+    /// 匹配条件：当前 Wi-Fi SSID
+    pub ssid: Option<String>,
+    /// 匹配条件：探测主机可达，格式 `host:port`，省略端口时为 80
+    pub probe_host: Option<String>,
+    /// 命中后切换到的订阅 uid
+    pub profile: Option<String>,
+    /// 命中后切换到的 Clash 模式：`rule` | `global` | `direct`
+    pub mode: Option<String>,
+    /// 命中后的系统代理开关
+    pub system_proxy: Option<bool>,
+    /// 命中后的 TUN 开关
+    pub tun: Option<bool>,
+}
+
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct IVergeTestItem {
     pub uid: Option<String>,
@@ -499,6 +661,7 @@ impl IVerge {
         patch!(tun_tray_icon);
 
         patch!(enable_tun_mode);
+        patch!(tun_filter);
         patch!(enable_auto_launch);
         patch!(enable_silent_start);
         patch!(enable_hover_jump_navigator);
@@ -519,9 +682,12 @@ impl IVerge {
         patch!(enable_proxy_guard);
         patch!(use_default_bypass);
         patch!(system_proxy_bypass);
+        patch!(bypass_entries);
+        patch!(bypass_from_rules);
         patch!(proxy_guard_duration);
         patch!(proxy_auto_config);
         patch!(pac_file_content);
+        patch!(pac_from_rules);
         patch!(proxy_host);
         patch!(theme_setting);
         patch!(web_ui_list);
@@ -552,6 +718,18 @@ impl IVerge {
         patch!(enable_external_controller);
         patch!(favorite_proxies);
         patch!(traffic_quota_reminder);
+        patch!(core_resource_monitor);
+        patch!(control_api_token);
+        patch!(network_switch);
+        patch!(proxy_guard_alert);
+        patch!(linux_proxy);
+        patch!(lan_pac_server);
+        patch!(tool_proxy);
+        patch!(unlock_recheck_interval);
+        patch!(unlock_recheck_group);
+        patch!(unlock_groups);
+        patch!(delay_probe_interval);
+        patch!(sort_favorites_by_stability);
     }
 
     /// 在初始化前尝试拿到单例端口的值
@@ -602,6 +780,7 @@ pub struct IVergeResponse {
     pub sysproxy_tray_icon: Option<bool>,
     pub tun_tray_icon: Option<bool>,
     pub enable_tun_mode: Option<bool>,
+    pub tun_filter: Option<ITunFilter>,
     pub enable_auto_launch: Option<bool>,
     pub enable_silent_start: Option<bool>,
     pub enable_system_proxy: Option<bool>,
@@ -609,9 +788,12 @@ pub struct IVergeResponse {
     pub enable_global_hotkey: Option<bool>,
     pub use_default_bypass: Option<bool>,
     pub system_proxy_bypass: Option<String>,
+    pub bypass_entries: Option<Vec<IBypassEntry>>,
+    pub bypass_from_rules: Option<bool>,
     pub proxy_guard_duration: Option<u64>,
     pub proxy_auto_config: Option<bool>,
     pub pac_file_content: Option<String>,
+    pub pac_from_rules: Option<bool>,
     pub proxy_host: Option<String>,
     pub theme_setting: Option<IVergeTheme>,
     pub web_ui_list: Option<Vec<String>>,
@@ -653,6 +835,18 @@ pub struct IVergeResponse {
     pub enable_external_controller: Option<bool>,
     pub favorite_proxies: Option<Vec<String>>,
     pub traffic_quota_reminder: Option<ITrafficQuotaReminder>,
+    pub core_resource_monitor: Option<ICoreResourceMonitor>,
+    pub control_api_token: Option<String>,
+    pub network_switch: Option<INetworkSwitch>,
+    pub proxy_guard_alert: Option<IProxyGuardAlert>,
+    pub linux_proxy: Option<ILinuxProxy>,
+    pub lan_pac_server: Option<ILanPacServer>,
+    pub tool_proxy: Option<IToolProxy>,
+    pub unlock_recheck_interval: Option<u64>,
+    pub unlock_recheck_group: Option<String>,
+    pub unlock_groups: Option<IUnlockGroups>,
+    pub delay_probe_interval: Option<u64>,
+    pub sort_favorites_by_stability: Option<bool>,
     pub window_use_system_titlebar: Option<bool>,
 }
 
@@ -680,6 +874,7 @@ impl From<IVerge> for IVergeResponse {
             sysproxy_tray_icon: verge.sysproxy_tray_icon,
             tun_tray_icon: verge.tun_tray_icon,
             enable_tun_mode: verge.enable_tun_mode,
+            tun_filter: verge.tun_filter,
             enable_auto_launch: verge.enable_auto_launch,
             enable_silent_start: verge.enable_silent_start,
             enable_system_proxy: verge.enable_system_proxy,
@@ -687,9 +882,12 @@ impl From<IVerge> for IVergeResponse {
             enable_global_hotkey: verge.enable_global_hotkey,
             use_default_bypass: verge.use_default_bypass,
             system_proxy_bypass: verge.system_proxy_bypass,
+            bypass_entries: verge.bypass_entries,
+            bypass_from_rules: verge.bypass_from_rules,
             proxy_guard_duration: verge.proxy_guard_duration,
             proxy_auto_config: verge.proxy_auto_config,
             pac_file_content: verge.pac_file_content,
+            pac_from_rules: verge.pac_from_rules,
             proxy_host: verge.proxy_host,
             theme_setting: verge.theme_setting,
             web_ui_list: verge.web_ui_list,
@@ -731,6 +929,18 @@ impl From<IVerge> for IVergeResponse {
             enable_external_controller: verge.enable_external_controller,
             favorite_proxies: verge.favorite_proxies,
             traffic_quota_reminder: verge.traffic_quota_reminder,
+            core_resource_monitor: verge.core_resource_monitor,
+            control_api_token: verge.control_api_token,
+            network_switch: verge.network_switch,
+            proxy_guard_alert: verge.proxy_guard_alert,
+            linux_proxy: verge.linux_proxy,
+            lan_pac_server: verge.lan_pac_server,
+            tool_proxy: verge.tool_proxy,
+            unlock_recheck_interval: verge.unlock_recheck_interval,
+            unlock_recheck_group: verge.unlock_recheck_group,
+            unlock_groups: verge.unlock_groups,
+            delay_probe_interval: verge.delay_probe_interval,
+            sort_favorites_by_stability: verge.sort_favorites_by_stability,
             window_use_system_titlebar: verge.window_use_system_titlebar,
         }
     }
@@ -1,12 +1,12 @@
-#[cfg(target_os = "windows")]
+#[cfg(target_os = "linux")]
+use crate::core::linux_sysproxy::LinuxProxy;
+#[cfg(not(target_os = "macos"))]
 use crate::process::AsyncHandler;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use tokio::time::{Duration, timeout};
 
-#[cfg(target_os = "linux")]
-use anyhow::anyhow;
-#[cfg(not(target_os = "windows"))]
+#[cfg(target_os = "macos")]
 use tokio::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -206,55 +206,13 @@ impl AsyncProxyQuery {
 
     #[cfg(target_os = "linux")]
     async fn get_auto_proxy_impl() -> Result<AsyncAutoproxy> {
-        // Linux: 检查环境变量和GNOME设置
-
-        // 首先检查环境变量
-        if let Ok(auto_proxy) = std::env::var("auto_proxy")
-            && !auto_proxy.is_empty()
-        {
-            return Ok(AsyncAutoproxy {
-                enable: true,
-                url: auto_proxy,
-            });
-        }
-
-        // 尝试使用 gsettings 获取 GNOME 代理设置
-        let output = Command::new("gsettings")
-            .args(["get", "org.gnome.system.proxy", "mode"])
-            .output()
-            .await;
-
-        if let Ok(output) = output
-            && output.status.success()
-        {
-            let mode: String = String::from_utf8_lossy(&output.stdout).trim().into();
-            if mode.contains("auto") {
-                // 获取 PAC URL
-                let pac_output = Command::new("gsettings")
-                    .args(["get", "org.gnome.system.proxy", "autoconfig-url"])
-                    .output()
-                    .await;
-
-                if let Ok(pac_output) = pac_output
-                    && pac_output.status.success()
-                {
-                    let pac_url: String = String::from_utf8_lossy(&pac_output.stdout)
-                        .trim()
-                        .trim_matches('\'')
-                        .trim_matches('"')
-                        .into();
-
-                    if !pac_url.is_empty() {
-                        return Ok(AsyncAutoproxy {
-                            enable: true,
-                            url: pac_url,
-                        });
-                    }
-                }
-            }
-        }
-
-        Ok(AsyncAutoproxy::default())
+        // Linux: 按桌面环境读取 gsettings 或 kioslaverc
+        let proxy =
+            AsyncHandler::spawn_blocking(|| LinuxProxy::global().get_auto_proxy()).await??;
+        Ok(AsyncAutoproxy {
+            enable: proxy.enable && !proxy.url.is_empty(),
+            url: proxy.url,
+        })
     }
 
     #[cfg(target_os = "windows")]
@@ -428,105 +386,14 @@ impl AsyncProxyQuery {
 
     #[cfg(target_os = "linux")]
     async fn get_system_proxy_impl() -> Result<AsyncSysproxy> {
-        // Linux: 检查环境变量和桌面环境设置
-
-        // 首先检查环境变量
-        if let Ok(http_proxy) = std::env::var("http_proxy")
-            && let Ok(proxy_info) = Self::parse_proxy_url(&http_proxy)
-        {
-            return Ok(proxy_info);
-        }
-
-        if let Ok(https_proxy) = std::env::var("https_proxy")
-            && let Ok(proxy_info) = Self::parse_proxy_url(&https_proxy)
-        {
-            return Ok(proxy_info);
-        }
-
-        // 尝试使用 gsettings 获取 GNOME 代理设置
-        let mode_output = Command::new("gsettings")
-            .args(["get", "org.gnome.system.proxy", "mode"])
-            .output()
-            .await;
-
-        if let Ok(mode_output) = mode_output
-            && mode_output.status.success()
-        {
-            let mode: String = String::from_utf8_lossy(&mode_output.stdout).trim().into();
-            if mode.contains("manual") {
-                // 获取HTTP代理设置
-                let host_result = Command::new("gsettings")
-                    .args(["get", "org.gnome.system.proxy.http", "host"])
-                    .output()
-                    .await;
-
-                let port_result = Command::new("gsettings")
-                    .args(["get", "org.gnome.system.proxy.http", "port"])
-                    .output()
-                    .await;
-
-                if let (Ok(host_output), Ok(port_output)) = (host_result, port_result)
-                    && host_output.status.success()
-                    && port_output.status.success()
-                {
-                    let host: String = String::from_utf8_lossy(&host_output.stdout)
-                        .trim()
-                        .trim_matches('\'')
-                        .trim_matches('"')
-                        .into();
-
-                    let port = String::from_utf8_lossy(&port_output.stdout)
-                        .trim()
-                        .parse::<u16>()
-                        .unwrap_or(8080);
-
-                    if !host.is_empty() {
-                        return Ok(AsyncSysproxy {
-                            enable: true,
-                            host,
-                            port,
-                            bypass: String::new(),
-                        });
-                    }
-                }
-            }
-        }
-
-        Ok(AsyncSysproxy::default())
-    }
-
-    #[cfg(target_os = "linux")]
-    fn parse_proxy_url(proxy_url: &str) -> Result<AsyncSysproxy> {
-        // 解析形如 "http://proxy.example.com:8080" 的URL
-        let url = proxy_url.trim();
-
-        // 移除协议前缀
-        let url = if let Some(stripped) = url.strip_prefix("http://") {
-            stripped
-        } else if let Some(stripped) = url.strip_prefix("https://") {
-            stripped
-        } else {
-            url
-        };
-
-        // 解析主机和端口
-        let (host, port) = if let Some(colon_pos) = url.rfind(':') {
-            let host: String = url[..colon_pos].into();
-            let port = url[colon_pos + 1..].parse::<u16>().unwrap_or(8080);
-            (host, port)
-        } else {
-            (url.into(), 8080)
-        };
-
-        if host.is_empty() {
-            return Err(anyhow!("无效的代理URL"));
-        }
-
+        // Linux: 按桌面环境读取对应存储，无桌面时读取环境变量文件
+        let proxy =
+            AsyncHandler::spawn_blocking(|| LinuxProxy::global().get_system_proxy()).await??;
         Ok(AsyncSysproxy {
-            enable: true,
-            host,
-            port,
-            bypass: std::env::var("no_proxy").unwrap_or_default(),
+            enable: proxy.enable && !proxy.host.is_empty(),
+            host: proxy.host,
+            port: proxy.port,
+            bypass: proxy.bypass,
         })
     }
 }
@@ -1,4 +1,5 @@
 use crate::AsyncHandler;
+use crate::core::log_store::CoreLogStore;
 use crate::core::logger::ClashLogger;
 use crate::process::CommandChildGuard;
 use crate::utils::init::sidecar_writer;
@@ -22,7 +23,11 @@ use flexi_logger::DeferredNow;
 use log::Level;
 use parking_lot::Mutex;
 use std::collections::VecDeque;
-use std::{fmt, path::PathBuf, sync::Arc};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use tauri_plugin_shell::ShellExt;
 
 // 模式切换性能和内核启动参数配置
@@ -31,6 +36,8 @@ use tauri_plugin_shell::ShellExt;
 pub struct CoreManager {
     running: Arc<Mutex<RunningMode>>,
     child_sidecar: Arc<Mutex<Option<CommandChildGuard>>>,
+    /// 无界面守护模式下直接启动的内核进程
+    child_process: Arc<Mutex<Option<std::process::Child>>>,
 }
 
 /// 内核运行模式
@@ -40,6 +47,9 @@ pub enum RunningMode {
     Service,
     /// Sidecar 模式运行
     Sidecar,
+    /// Sidecar 模式运行，内核二进制具备网络能力（仅 Linux），可无 root 运行 TUN
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    Capability,
     /// 未运行
     NotRunning,
 }
@@ -49,6 +59,7 @@ impl fmt::Display for RunningMode {
         match self {
             RunningMode::Service => write!(f, "Service"),
             RunningMode::Sidecar => write!(f, "Sidecar"),
+            RunningMode::Capability => write!(f, "Capability"),
             RunningMode::NotRunning => write!(f, "NotRunning"),
         }
     }
@@ -240,26 +251,46 @@ impl CoreManager {
         let clash_core = Config::verge().await.latest_ref().get_valid_clash_core();
         logging!(info, Type::Config, "使用内核: {}", clash_core);
 
-        let app_handle = handle::Handle::app_handle();
         let app_dir = dirs::app_home_dir()?;
         let app_dir_str = dirs::path_to_str(&app_dir)?;
         logging!(info, Type::Config, "验证目录: {}", app_dir_str);
 
         // 使用子进程运行clash验证配置
-        let output = app_handle
-            .shell()
-            .sidecar(clash_core)?
-            .args(["-t", "-d", app_dir_str, "-f", config_path])
-            .output()
-            .await?;
+        let args = ["-t", "-d", app_dir_str, "-f", config_path];
+        let (success, code, stdout, stderr) = match handle::Handle::try_app_handle() {
+            Some(app_handle) => {
+                let output = app_handle
+                    .shell()
+                    .sidecar(clash_core)?
+                    .args(args)
+                    .output()
+                    .await?;
+                (
+                    output.status.success(),
+                    output.status.code(),
+                    output.stdout,
+                    output.stderr,
+                )
+            }
+            None => {
+                let mut command = std::process::Command::new(dirs::core_binary_path(&clash_core)?);
+                command.args(args);
+                let output = AsyncHandler::spawn_blocking(move || command.output()).await??;
+                (
+                    output.status.success(),
+                    output.status.code(),
+                    output.stdout,
+                    output.stderr,
+                )
+            }
+        };
 
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&stderr);
+        let stdout = String::from_utf8_lossy(&stdout);
 
         // 检查进程退出状态和错误输出
         let error_keywords = ["FATA", "fatal", "Parse config error", "level=fatal"];
-        let has_error =
-            !output.status.success() || error_keywords.iter().any(|&kw| stderr.contains(kw));
+        let has_error = !success || error_keywords.iter().any(|&kw| stderr.contains(kw));
 
         logging!(info, Type::Config, "-------- 验证结果 --------");
 
@@ -273,7 +304,7 @@ impl CoreManager {
                 stdout.into()
             } else if !stderr.is_empty() {
                 stderr.into()
-            } else if let Some(code) = output.status.code() {
+            } else if let Some(code) = code {
                 format!("验证进程异常退出，退出码: {code}")
             } else {
                 "验证进程被终止".into()
@@ -361,6 +392,24 @@ impl CoreManager {
             }
         }
     }
+    /// 绕过列表由规则推导时，运行时规则变化后同步系统代理
+    async fn refresh_rule_bypass() {
+        let (from_rules, sys_enable) = {
+            let verge = Config::verge().await;
+            let verge = verge.latest_ref();
+            (
+                verge.bypass_from_rules.unwrap_or(false),
+                verge.enable_system_proxy.unwrap_or(false),
+            )
+        };
+        if from_rules && sys_enable {
+            logging_error!(
+                Type::Core,
+                super::sysopt::Sysopt::global().update_sysproxy().await
+            );
+        }
+    }
+
     /// 更新proxies等配置
     pub async fn update_config(&self) -> Result<(bool, String)> {
         // 检查程序是否正在退出，如果是则跳过完整验证流程
@@ -379,7 +428,14 @@ impl CoreManager {
                 // 4. 验证通过后，生成正式的运行时配置
                 logging!(info, Type::Config, "配置验证通过, 生成运行时配置");
                 let run_path = Config::generate_file(ConfigType::Run).await?;
-                logging_error!(Type::Config, self.put_configs_force(run_path).await);
+                let put_result = self.put_configs_force(run_path).await;
+                if put_result.is_ok() {
+                    super::pac::PacManager::global().refresh().await;
+                    Self::refresh_rule_bypass().await;
+                    // 订阅与代理组可能已变化，重新注册带参数的快捷键
+                    super::hotkey::Hotkey::global().refresh_targets().await;
+                }
+                logging_error!(Type::Config, put_result);
                 Ok((true, "something".into()))
             }
             Ok((false, error_msg)) => {
@@ -395,6 +451,16 @@ impl CoreManager {
         }
     }
     pub async fn put_configs_force(&self, path_buf: PathBuf) -> Result<(), String> {
+        // 无界面守护模式没有控制器客户端，重启内核以加载新配置
+        if handle::Handle::try_app_handle().is_none() {
+            Config::runtime().await.apply();
+            return self.restart_core().await.map_err(|e| {
+                let msg = e.to_string();
+                logging_error!(Type::Core, "Failed to restart core: {}", msg);
+                msg
+            });
+        }
+
         let run_path_str = dirs::path_to_str(&path_buf).map_err(|e| {
             let msg = e.to_string();
             logging_error!(Type::Core, "{}", msg);
@@ -770,13 +836,15 @@ impl CoreManager {
         }
     }
 
-    async fn start_core_by_sidecar(&self) -> Result<()> {
+    async fn start_core_by_sidecar(&self, mode: RunningMode) -> Result<()> {
         logging!(info, Type::Core, "Running core by sidecar");
 
         let config_file = &Config::generate_file(ConfigType::Run).await?;
-        let app_handle = handle::Handle::app_handle();
         let clash_core = Config::verge().await.latest_ref().get_valid_clash_core();
         let config_dir = dirs::app_home_dir()?;
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return self.start_core_by_process(&clash_core, &config_dir, config_file, mode);
+        };
 
         let (mut rx, child) = app_handle
             .shell()
@@ -792,10 +860,18 @@ impl CoreManager {
         let pid = child.pid();
         logging!(trace, Type::Core, "Started core by sidecar pid: {}", pid);
         *self.child_sidecar.lock() = Some(CommandChildGuard::new(child));
-        self.set_running_mode(RunningMode::Sidecar);
+        self.set_running_mode(mode);
 
         let shared_writer: SharedWriter =
             Arc::new(tokio::sync::Mutex::new(sidecar_writer().await?));
+        {
+            let verge = Config::verge().await;
+            let verge = verge.latest_ref();
+            CoreLogStore::global().set_limits(
+                verge.app_log_max_size.unwrap_or(128),
+                verge.app_log_max_count.unwrap_or(8),
+            );
+        }
 
         AsyncHandler::spawn(|| async move {
             while let Some(event) = rx.recv().await {
@@ -806,6 +882,7 @@ impl CoreManager {
                             CompactString::from(String::from_utf8_lossy(&line).into_owned());
                         let w = shared_writer.lock().await;
                         write_sidecar_log(w, &mut now, Level::Error, &message);
+                        CoreLogStore::global().append_line(&message);
                         ClashLogger::global().append_log(message);
                     }
                     tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
@@ -814,6 +891,7 @@ impl CoreManager {
                             CompactString::from(String::from_utf8_lossy(&line).into_owned());
                         let w = shared_writer.lock().await;
                         write_sidecar_log(w, &mut now, Level::Error, &message);
+                        CoreLogStore::global().append_line(&message);
                         ClashLogger::global().append_log(message);
                     }
                     tauri_plugin_shell::process::CommandEvent::Terminated(term) => {
@@ -827,6 +905,7 @@ impl CoreManager {
                         };
                         let w = shared_writer.lock().await;
                         write_sidecar_log(w, &mut now, Level::Info, &message);
+                        CoreLogStore::global().append_line(&message);
                         ClashLogger::global().clear_logs();
                         break;
                     }
@@ -837,6 +916,58 @@ impl CoreManager {
 
         Ok(())
     }
+    /// 无界面守护模式没有 Tauri shell 插件，直接启动内核进程
+    fn start_core_by_process(
+        &self,
+        clash_core: &str,
+        config_dir: &Path,
+        config_file: &Path,
+        mode: RunningMode,
+    ) -> Result<()> {
+        use std::io::{BufRead, BufReader, Read};
+        use std::process::{Command, Stdio};
+
+        let mut child = Command::new(dirs::core_binary_path(clash_core)?)
+            .args([
+                "-d",
+                dirs::path_to_str(config_dir)?,
+                "-f",
+                dirs::path_to_str(config_file)?,
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        logging!(
+            trace,
+            Type::Core,
+            "Started core process pid: {}",
+            child.id()
+        );
+
+        let streams: [Option<Box<dyn Read + Send>>; 2] = [
+            child
+                .stdout
+                .take()
+                .map(|stdout| Box::new(stdout) as Box<dyn Read + Send>),
+            child
+                .stderr
+                .take()
+                .map(|stderr| Box::new(stderr) as Box<dyn Read + Send>),
+        ];
+        for stream in streams.into_iter().flatten() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                    CoreLogStore::global().append_line(&line);
+                    ClashLogger::global().append_log(CompactString::from(line));
+                }
+            });
+        }
+
+        *self.child_process.lock() = Some(child);
+        self.set_running_mode(mode);
+        Ok(())
+    }
+
     fn stop_core_by_sidecar(&self) -> Result<()> {
         logging!(info, Type::Core, "Stopping core by sidecar");
 
@@ -845,6 +976,12 @@ impl CoreManager {
             drop(child);
             logging!(trace, Type::Core, "Stopped core by sidecar pid: {:?}", pid);
         }
+        if let Some(mut child) = self.child_process.lock().take() {
+            let pid = child.id();
+            let _ = child.kill();
+            let _ = child.wait();
+            logging!(trace, Type::Core, "Stopped core process pid: {}", pid);
+        }
         self.set_running_mode(RunningMode::NotRunning);
         Ok(())
     }
@@ -872,6 +1009,7 @@ impl Default for CoreManager {
         CoreManager {
             running: Arc::new(Mutex::new(RunningMode::NotRunning)),
             child_sidecar: Arc::new(Mutex::new(None)),
+            child_process: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -908,11 +1046,25 @@ impl CoreManager {
         (*guard).clone()
     }
 
+    /// 获取 sidecar 模式下内核进程的 PID
+    pub fn get_core_pid(&self) -> Option<u32> {
+        self.child_sidecar
+            .lock()
+            .as_ref()
+            .and_then(|child| child.pid())
+            .or_else(|| self.child_process.lock().as_ref().map(|child| child.id()))
+    }
+
     pub async fn prestart_core(&self) -> Result<()> {
-        match SERVICE_MANAGER.lock().await.current() {
+        let service_status = SERVICE_MANAGER.lock().await.current();
+        match service_status {
             ServiceStatus::Ready => {
                 self.set_running_mode(RunningMode::Service);
             }
+            #[cfg(target_os = "linux")]
+            _ if crate::utils::capability::core_has_tun_capabilities().await => {
+                self.set_running_mode(RunningMode::Capability);
+            }
             _ => {
                 self.set_running_mode(RunningMode::Sidecar);
             }
@@ -928,8 +1080,17 @@ impl CoreManager {
             RunningMode::Service => {
                 logging_error!(Type::Core, self.start_core_by_service().await);
             }
+            RunningMode::Capability => {
+                logging_error!(
+                    Type::Core,
+                    self.start_core_by_sidecar(RunningMode::Capability).await
+                );
+            }
             RunningMode::NotRunning | RunningMode::Sidecar => {
-                logging_error!(Type::Core, self.start_core_by_sidecar().await);
+                logging_error!(
+                    Type::Core,
+                    self.start_core_by_sidecar(RunningMode::Sidecar).await
+                );
             }
         };
 
@@ -939,8 +1100,14 @@ impl CoreManager {
     pub async fn get_clash_logs(&self) -> Result<VecDeque<CompactString>> {
         logging!(info, Type::Core, "get clash logs");
         let logs = match self.get_running_mode() {
-            RunningMode::Service => service::get_clash_logs_by_service().await?,
-            RunningMode::Sidecar => ClashLogger::global().get_logs().clone(),
+            RunningMode::Service => {
+                let logs = service::get_clash_logs_by_service().await?;
+                CoreLogStore::global().sync_service_lines(&logs);
+                logs
+            }
+            RunningMode::Sidecar | RunningMode::Capability => {
+                ClashLogger::global().get_logs().clone()
+            }
             _ => VecDeque::new(),
         };
         Ok(logs)
@@ -951,7 +1118,7 @@ impl CoreManager {
         ClashLogger::global().clear_logs();
         match self.get_running_mode() {
             RunningMode::Service => self.stop_core_by_service().await,
-            RunningMode::Sidecar => self.stop_core_by_sidecar(),
+            RunningMode::Sidecar | RunningMode::Capability => self.stop_core_by_sidecar(),
             RunningMode::NotRunning => Ok(()),
         }
     }
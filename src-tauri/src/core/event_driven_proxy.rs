@@ -5,7 +5,13 @@ use tokio::time::{Duration, sleep, timeout};
 use tokio_stream::{StreamExt, wrappers::UnboundedReceiverStream};
 
 use crate::config::{Config, IVerge};
-use crate::core::{async_proxy_query::AsyncProxyQuery, handle};
+#[cfg(not(target_os = "windows"))]
+use crate::core::sysopt::ProxyStore;
+use crate::core::{
+    async_proxy_query::AsyncProxyQuery,
+    guard_history::{GuardEvent, GuardTarget, ProxyGuardHistory},
+    handle, sysopt,
+};
 use crate::logging_error;
 use crate::process::AsyncHandler;
 use crate::utils::logging::Type;
@@ -371,7 +377,7 @@ impl EventDrivenProxyManager {
                 }
             }
 
-            if let Some(e) = last_error {
+            if let Some(e) = &last_error {
                 log::error!(target: "app", "恢复PAC代理失败，已重试 {} 次: {}", MAX_RETRIES, e);
             }
 
@@ -394,6 +400,17 @@ impl EventDrivenProxyManager {
                 log::warn!(target: "app", "PAC代理恢复验证失败: enable={}, url={}", 
                     restored.enable, restored.url);
             }
+
+            ProxyGuardHistory::global()
+                .record(GuardEvent::new(
+                    GuardTarget::Pac,
+                    (&expected).into(),
+                    (&current).into(),
+                    (&restored).into(),
+                    is_restored,
+                    last_error.map(|e| e.to_string()),
+                ))
+                .await;
         }
     }
 
@@ -439,7 +456,7 @@ impl EventDrivenProxyManager {
                 }
             }
 
-            if let Some(e) = last_error {
+            if let Some(e) = &last_error {
                 log::error!(target: "app", "恢复系统代理失败，已重试 {} 次: {}", MAX_RETRIES, e);
             }
 
@@ -463,6 +480,17 @@ impl EventDrivenProxyManager {
                 log::warn!(target: "app", "系统代理恢复验证失败: enable={}, {}:{}", 
                     restored.enable, restored.host, restored.port);
             }
+
+            ProxyGuardHistory::global()
+                .record(GuardEvent::new(
+                    GuardTarget::Sysproxy,
+                    (&expected).into(),
+                    (&current).into(),
+                    (&restored).into(),
+                    is_restored,
+                    last_error.map(|e| e.to_string()),
+                ))
+                .await;
         }
     }
 
@@ -499,8 +527,8 @@ impl EventDrivenProxyManager {
             let disabled_sys = Sysproxy::default();
             let disabled_auto = Autoproxy::default();
 
-            logging_error!(Type::System, disabled_auto.set_auto_proxy());
-            logging_error!(Type::System, disabled_sys.set_system_proxy());
+            logging_error!(Type::System, disabled_auto.store());
+            logging_error!(Type::System, disabled_sys.store());
         }
     }
 
@@ -514,6 +542,9 @@ impl EventDrivenProxyManager {
 
         if to_pac {
             let disabled_sys = Sysproxy::default();
+            #[cfg(not(target_os = "windows"))]
+            logging_error!(Type::System, disabled_sys.store());
+            #[cfg(target_os = "windows")]
             logging_error!(Type::System, disabled_sys.set_system_proxy());
 
             let expected = Self::get_expected_pac_config().await;
@@ -522,6 +553,9 @@ impl EventDrivenProxyManager {
             }
         } else {
             let disabled_auto = Autoproxy::default();
+            #[cfg(not(target_os = "windows"))]
+            logging_error!(Type::System, disabled_auto.store());
+            #[cfg(target_os = "windows")]
             logging_error!(Type::System, disabled_auto.set_auto_proxy());
 
             let expected = Self::get_expected_sys_proxy().await;
@@ -613,36 +647,7 @@ impl EventDrivenProxyManager {
             enable: true,
             host: proxy_host,
             port,
-            bypass: Self::get_bypass_config().await,
-        }
-    }
-
-    async fn get_bypass_config() -> String {
-        let (use_default, custom_bypass) = {
-            let verge_config = Config::verge().await;
-            let verge = verge_config.latest_ref();
-            (
-                verge.use_default_bypass.unwrap_or(true),
-                verge.system_proxy_bypass.clone().unwrap_or_default(),
-            )
-        };
-
-        #[cfg(target_os = "windows")]
-        let default_bypass = "localhost;127.*;192.168.*;10.*;172.16.*;172.17.*;172.18.*;172.19.*;172.20.*;172.21.*;172.22.*;172.23.*;172.24.*;172.25.*;172.26.*;172.27.*;172.28.*;172.29.*;172.30.*;172.31.*;<local>";
-
-        #[cfg(target_os = "linux")]
-        let default_bypass =
-            "localhost,127.0.0.1,192.168.0.0/16,10.0.0.0/8,172.16.0.0/12,172.29.0.0/16,::1";
-
-        #[cfg(target_os = "macos")]
-        let default_bypass = "127.0.0.1,192.168.0.0/16,10.0.0.0/8,172.16.0.0/12,172.29.0.0/16,localhost,*.local,*.crashlytics.com,<local>";
-
-        if custom_bypass.is_empty() {
-            default_bypass.to_string()
-        } else if use_default {
-            format!("{default_bypass},{custom_bypass}")
-        } else {
-            custom_bypass
+            bypass: sysopt::get_bypass().await,
         }
     }
 
@@ -665,7 +670,7 @@ impl EventDrivenProxyManager {
             };
             // logging_error!(Type::System, true, new_autoproxy.set_auto_proxy());
             new_autoproxy
-                .set_auto_proxy()
+                .store()
                 .map_err(|e| anyhow::anyhow!("Failed to set auto proxy: {}", e))
         }
     }
@@ -686,7 +691,7 @@ impl EventDrivenProxyManager {
         {
             // logging_error!(Type::System, true, expected.set_system_proxy());
             expected
-                .set_system_proxy()
+                .store()
                 .map_err(|e| anyhow::anyhow!("Failed to set system proxy: {}", e))
         }
     }
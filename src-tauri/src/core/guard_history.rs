@@ -0,0 +1,284 @@
+use std::collections::VecDeque;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use sysproxy::{Autoproxy, Sysproxy};
+
+use crate::{
+    config::{Config, IProxyGuardAlert},
+    core::handle,
+    logging, singleton_lazy,
+    utils::{
+        logging::Type,
+        notification::{NotificationEvent, notify_event},
+    },
+};
+
+/// 保留的篡改记录数量
+const HISTORY_CAPACITY: usize = 500;
+const DEFAULT_ALERT_MAX_EVENTS: usize = 5;
+const DEFAULT_ALERT_WINDOW_SECS: u64 = 600;
+
+/// 被守护的代理类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardTarget {
+    Sysproxy,
+    Pac,
+}
+
+/// 检测到偏离后采取的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardAction {
+    /// 已恢复并验证成功
+    Restored,
+    /// 恢复失败或验证未通过
+    RestoreFailed,
+}
+
+/// 代理设置快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum ProxySnapshot {
+    Sysproxy {
+        enable: bool,
+        host: String,
+        port: u16,
+        bypass: String,
+    },
+    Autoproxy {
+        enable: bool,
+        url: String,
+    },
+}
+
+impl From<&Sysproxy> for ProxySnapshot {
+    fn from(proxy: &Sysproxy) -> Self {
+        Self::Sysproxy {
+            enable: proxy.enable,
+            host: proxy.host.clone(),
+            port: proxy.port,
+            bypass: proxy.bypass.clone(),
+        }
+    }
+}
+
+impl From<&Autoproxy> for ProxySnapshot {
+    fn from(proxy: &Autoproxy) -> Self {
+        Self::Autoproxy {
+            enable: proxy.enable,
+            url: proxy.url.clone(),
+        }
+    }
+}
+
+/// 一次代理偏离记录
+#[derive(Debug, Clone, Serialize)]
+pub struct GuardEvent {
+    /// 毫秒时间戳
+    pub time: i64,
+    pub target: GuardTarget,
+    pub expected: ProxySnapshot,
+    pub actual: ProxySnapshot,
+    pub action: GuardAction,
+    /// 恢复后的实际设置
+    pub restored: ProxySnapshot,
+    pub error: Option<String>,
+}
+
+impl GuardEvent {
+    pub fn new(
+        target: GuardTarget,
+        expected: ProxySnapshot,
+        actual: ProxySnapshot,
+        restored: ProxySnapshot,
+        success: bool,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            time: chrono::Local::now().timestamp_millis(),
+            target,
+            expected,
+            actual,
+            action: if success {
+                GuardAction::Restored
+            } else {
+                GuardAction::RestoreFailed
+            },
+            restored,
+            error,
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistoryState {
+    events: VecDeque<GuardEvent>,
+    /// 上次发出频率提醒的时间，同一时间窗口内只提醒一次
+    last_alert: Option<i64>,
+}
+
+/// 代理守护篡改记录
+pub struct ProxyGuardHistory {
+    state: RwLock<HistoryState>,
+}
+
+impl Default for ProxyGuardHistory {
+    fn default() -> Self {
+        Self {
+            state: RwLock::new(HistoryState {
+                events: VecDeque::with_capacity(HISTORY_CAPACITY),
+                last_alert: None,
+            }),
+        }
+    }
+}
+
+impl ProxyGuardHistory {
+    /// 记录一次偏离，并在超过设定频率时提醒
+    pub async fn record(&self, event: GuardEvent) {
+        logging!(
+            info,
+            Type::ProxyMode,
+            "代理守护检测到设置被修改: {:?}, 期望: {:?}, 实际: {:?}, 动作: {:?}",
+            event.target,
+            event.expected,
+            event.actual,
+            event.action
+        );
+
+        let alert = Config::verge()
+            .await
+            .latest_ref()
+            .proxy_guard_alert
+            .clone()
+            .unwrap_or_default();
+        let (time, target) = (event.time, event.target);
+        self.push(event);
+
+        if alert.enabled.unwrap_or(false)
+            && let Some(count) = self.check_rate(&alert, time)
+        {
+            let window = alert.window_secs.unwrap_or(DEFAULT_ALERT_WINDOW_SECS);
+            logging!(
+                warn,
+                Type::ProxyMode,
+                "代理设置被频繁篡改: {:?} 在 {} 秒内被修改 {} 次",
+                target,
+                window,
+                count
+            );
+            if let Some(app_handle) = handle::Handle::try_app_handle() {
+                notify_event(
+                    app_handle.clone(),
+                    NotificationEvent::ProxyGuardTampered {
+                        pac: target == GuardTarget::Pac,
+                        count: &count.to_string(),
+                        window: &window.to_string(),
+                    },
+                )
+                .await;
+            }
+        }
+    }
+
+    fn push(&self, event: GuardEvent) {
+        let mut state = self.state.write();
+        if state.events.len() >= HISTORY_CAPACITY {
+            state.events.pop_front();
+        }
+        state.events.push_back(event);
+    }
+
+    /// 时间窗口内的记录数达到阈值且未提醒过时返回记录数
+    fn check_rate(&self, alert: &IProxyGuardAlert, now: i64) -> Option<usize> {
+        let max_events = alert.max_events.unwrap_or(DEFAULT_ALERT_MAX_EVENTS).max(1);
+        let window_ms = alert
+            .window_secs
+            .unwrap_or(DEFAULT_ALERT_WINDOW_SECS)
+            .saturating_mul(1000) as i64;
+        let since = now.saturating_sub(window_ms);
+
+        let mut state = self.state.write();
+        if state.last_alert.is_some_and(|last| last > since) {
+            return None;
+        }
+        let count = state
+            .events
+            .iter()
+            .rev()
+            .take_while(|event| event.time > since)
+            .count();
+        if count < max_events {
+            return None;
+        }
+        state.last_alert = Some(now);
+        Some(count)
+    }
+
+    /// 获取最近的记录，按时间从旧到新排列
+    pub fn history(&self, limit: Option<usize>) -> Vec<GuardEvent> {
+        let state = self.state.read();
+        let skip = limit.map_or(0, |limit| state.events.len().saturating_sub(limit));
+        state.events.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.write();
+        state.events.clear();
+        state.last_alert = None;
+    }
+}
+
+singleton_lazy!(
+    ProxyGuardHistory,
+    PROXY_GUARD_HISTORY,
+    ProxyGuardHistory::default
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_at(time: i64) -> GuardEvent {
+        let snapshot = ProxySnapshot::Autoproxy {
+            enable: true,
+            url: "http://127.0.0.1:33331/commands/pac".into(),
+        };
+        GuardEvent {
+            time,
+            target: GuardTarget::Pac,
+            expected: snapshot.clone(),
+            actual: snapshot.clone(),
+            action: GuardAction::Restored,
+            restored: snapshot,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_rate_alert_once_per_window() {
+        let history = ProxyGuardHistory::default();
+        let alert = IProxyGuardAlert {
+            enabled: Some(true),
+            max_events: Some(3),
+            window_secs: Some(60),
+        };
+
+        history.push(event_at(1_000));
+        history.push(event_at(2_000));
+        assert_eq!(history.check_rate(&alert, 2_000), None);
+
+        history.push(event_at(3_000));
+        assert_eq!(history.check_rate(&alert, 3_000), Some(3));
+
+        // 同一窗口内不再重复提醒
+        history.push(event_at(4_000));
+        assert_eq!(history.check_rate(&alert, 4_000), None);
+
+        // 窗口过去后旧记录不再计数
+        history.push(event_at(70_000));
+        assert_eq!(history.check_rate(&alert, 70_000), None);
+    }
+}
@@ -278,6 +278,11 @@ impl Handle {
         APP_HANDLE.get().expect("failed to get global app handle")
     }
 
+    /// 获取 AppHandle，无界面守护模式下没有 Tauri 应用，返回 None
+    pub fn try_app_handle() -> Option<&'static AppHandle> {
+        APP_HANDLE.get()
+    }
+
     pub async fn mihomo() -> RwLockReadGuard<'static, Mihomo> {
         Self::app_handle().mihomo().read().await
     }
@@ -288,7 +293,7 @@ impl Handle {
     }
 
     pub fn get_window() -> Option<WebviewWindow> {
-        let app_handle = Self::app_handle();
+        let app_handle = Self::try_app_handle()?;
         let window: Option<WebviewWindow> = app_handle.get_webview_window("main");
         if window.is_none() {
             log::debug!(target:"app", "main window not found");
@@ -4,14 +4,16 @@ use crate::{
     config::Config, core::handle, feat, logging, logging_error,
     module::lightweight::entry_lightweight_mode, singleton_with_logging, utils::logging::Type,
 };
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
 use parking_lot::Mutex;
 use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
 use tauri::{AppHandle, Manager};
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, ShortcutState};
 
 /// Enum representing all available hotkey functions
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Parameterized functions are written as `function:argument`, e.g. `switch_profile:<uid>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum HotkeyFunction {
     OpenOrCloseDashboard,
     ClashModeRule,
@@ -20,6 +22,15 @@ pub enum HotkeyFunction {
     ToggleSystemProxy,
     ToggleTunMode,
     EntryLightweightMode,
+    CopyEnv,
+    NextProfile,
+    PreviousProfile,
+    /// Switch to the profile with the given uid
+    SwitchProfile(String),
+    /// Select the next node of the given proxy group
+    SelectNextNode(String),
+    /// Run a latency test on the given proxy group
+    TestGroupDelay(String),
     Quit,
     #[cfg(target_os = "macos")]
     Hide,
@@ -35,6 +46,12 @@ impl fmt::Display for HotkeyFunction {
             HotkeyFunction::ToggleSystemProxy => "toggle_system_proxy",
             HotkeyFunction::ToggleTunMode => "toggle_tun_mode",
             HotkeyFunction::EntryLightweightMode => "entry_lightweight_mode",
+            HotkeyFunction::CopyEnv => "copy_env",
+            HotkeyFunction::NextProfile => "next_profile",
+            HotkeyFunction::PreviousProfile => "previous_profile",
+            HotkeyFunction::SwitchProfile(uid) => return write!(f, "switch_profile:{uid}"),
+            HotkeyFunction::SelectNextNode(group) => return write!(f, "select_next_node:{group}"),
+            HotkeyFunction::TestGroupDelay(group) => return write!(f, "test_group_delay:{group}"),
             HotkeyFunction::Quit => "quit",
             #[cfg(target_os = "macos")]
             HotkeyFunction::Hide => "hide",
@@ -47,7 +64,21 @@ impl FromStr for HotkeyFunction {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.trim() {
+        let s = s.trim();
+        if let Some((name, arg)) = s.split_once(':') {
+            let arg = arg.trim();
+            if arg.is_empty() {
+                bail!("missing argument for hotkey function: {}", name);
+            }
+            return match name.trim() {
+                "switch_profile" => Ok(HotkeyFunction::SwitchProfile(arg.into())),
+                "select_next_node" => Ok(HotkeyFunction::SelectNextNode(arg.into())),
+                "test_group_delay" => Ok(HotkeyFunction::TestGroupDelay(arg.into())),
+                _ => bail!("invalid hotkey function: {}", s),
+            };
+        }
+
+        match s {
             "open_or_close_dashboard" => Ok(HotkeyFunction::OpenOrCloseDashboard),
             "clash_mode_rule" => Ok(HotkeyFunction::ClashModeRule),
             "clash_mode_global" => Ok(HotkeyFunction::ClashModeGlobal),
@@ -55,6 +86,12 @@ impl FromStr for HotkeyFunction {
             "toggle_system_proxy" => Ok(HotkeyFunction::ToggleSystemProxy),
             "toggle_tun_mode" => Ok(HotkeyFunction::ToggleTunMode),
             "entry_lightweight_mode" => Ok(HotkeyFunction::EntryLightweightMode),
+            "copy_env" => Ok(HotkeyFunction::CopyEnv),
+            "next_profile" => Ok(HotkeyFunction::NextProfile),
+            "previous_profile" => Ok(HotkeyFunction::PreviousProfile),
+            "switch_profile" | "select_next_node" | "test_group_delay" => {
+                bail!("missing argument for hotkey function: {}", s)
+            }
             "quit" => Ok(HotkeyFunction::Quit),
             #[cfg(target_os = "macos")]
             "hide" => Ok(HotkeyFunction::Hide),
@@ -161,6 +198,87 @@ impl Hotkey {
                     notify_event(app_handle, NotificationEvent::LightweightModeEntered).await;
                 });
             }
+            HotkeyFunction::CopyEnv => {
+                AsyncHandler::spawn(async move || {
+                    feat::copy_clash_env().await;
+                    notify_event(app_handle, NotificationEvent::ClashEnvCopied).await;
+                });
+            }
+            HotkeyFunction::NextProfile | HotkeyFunction::PreviousProfile => {
+                let forward = function == HotkeyFunction::NextProfile;
+                AsyncHandler::spawn(async move || {
+                    if let Some(profile) = feat::switch_adjacent_profile(forward).await {
+                        notify_event(
+                            app_handle,
+                            NotificationEvent::ProfileSwitched { profile: &profile },
+                        )
+                        .await;
+                    }
+                });
+            }
+            HotkeyFunction::SwitchProfile(uid) => {
+                AsyncHandler::spawn(async move || {
+                    let profile = Config::profiles()
+                        .await
+                        .latest_ref()
+                        .get_item(&uid)
+                        .ok()
+                        .map(|item| item.name.clone().unwrap_or_else(|| uid.clone()));
+                    let Some(profile) = profile else {
+                        logging!(warn, Type::Hotkey, "Profile not found: {}", uid);
+                        return;
+                    };
+                    feat::toggle_proxy_profile(uid).await;
+                    notify_event(
+                        app_handle,
+                        NotificationEvent::ProfileSwitched { profile: &profile },
+                    )
+                    .await;
+                });
+            }
+            HotkeyFunction::SelectNextNode(group) => {
+                AsyncHandler::spawn(async move || match feat::select_next_node(&group).await {
+                    Ok(node) => {
+                        notify_event(
+                            app_handle,
+                            NotificationEvent::NodeSelected {
+                                group: &group,
+                                node: &node,
+                            },
+                        )
+                        .await;
+                    }
+                    Err(e) => {
+                        logging!(
+                            error,
+                            Type::Hotkey,
+                            "Failed to select next node for {}: {}",
+                            group,
+                            e
+                        );
+                    }
+                });
+            }
+            HotkeyFunction::TestGroupDelay(group) => {
+                AsyncHandler::spawn(async move || match feat::test_group_delay(&group).await {
+                    Ok(()) => {
+                        notify_event(
+                            app_handle,
+                            NotificationEvent::GroupDelayTested { group: &group },
+                        )
+                        .await;
+                    }
+                    Err(e) => {
+                        logging!(
+                            error,
+                            Type::Hotkey,
+                            "Failed to test latency for {}: {}",
+                            group,
+                            e
+                        );
+                    }
+                });
+            }
             HotkeyFunction::Quit => {
                 AsyncHandler::spawn(async move || {
                     notify_event(app_handle, NotificationEvent::AppQuit).await;
@@ -226,7 +344,7 @@ impl Hotkey {
         let _ = manager.on_shortcut(hotkey, move |app_handle, hotkey_event, event| {
             let hotkey_event_owned = *hotkey_event;
             let event_owned = event;
-            let function_owned = function;
+            let function_owned = function.clone();
             let is_quit_owned = is_quit;
 
             let app_handle_cloned = app_handle.clone();
@@ -314,12 +432,8 @@ impl Hotkey {
             );
 
             for hotkey in hotkeys.iter() {
-                let mut iter = hotkey.split(',');
-                let func = iter.next();
-                let key = iter.next();
-
-                match (key, func) {
-                    (Some(key), Some(func)) => {
+                match Self::split_binding(hotkey) {
+                    Some((func, key)) => {
                         logging!(
                             debug,
                             Type::Hotkey,
@@ -346,15 +460,12 @@ impl Hotkey {
                             );
                         }
                     }
-                    _ => {
-                        let key = key.unwrap_or("None");
-                        let func = func.unwrap_or("None");
+                    None => {
                         logging!(
                             error,
                             Type::Hotkey,
-                            "Invalid hotkey configuration: `{}`:`{}`",
-                            key,
-                            func
+                            "Invalid hotkey configuration: `{}`",
+                            hotkey
                         );
                     }
                 }
@@ -376,7 +487,31 @@ impl Hotkey {
 
     /// Register a hotkey with string-based function (backward compatibility)
     pub async fn register(&self, hotkey: &str, func: &str) -> Result<()> {
+        if Self::is_unsupported(func) {
+            logging!(
+                warn,
+                Type::Hotkey,
+                "Hotkey function {} is not supported on this platform, skip {}",
+                func,
+                hotkey
+            );
+            return Ok(());
+        }
         let function = HotkeyFunction::from_str(func)?;
+        if let Some(target) = Self::missing_target(&function).await {
+            logging!(
+                warn,
+                Type::Hotkey,
+                "Hotkey {} refers to missing {}, skip registering",
+                hotkey,
+                target
+            );
+            let manager = handle::Handle::app_handle().global_shortcut();
+            if manager.is_registered(hotkey) {
+                manager.unregister(hotkey)?;
+            }
+            return Ok(());
+        }
         self.register_hotkey_with_function(hotkey, function).await
     }
 
@@ -413,19 +548,102 @@ impl Hotkey {
         let mut map = HashMap::new();
 
         hotkeys.iter().for_each(|hotkey| {
-            let mut iter = hotkey.split(',');
-            let func = iter.next();
-            let key = iter.next();
-
-            if let (Some(func), Some(key)) = (func, key) {
-                let func = func.trim();
-                let key = key.trim();
+            if let Some((func, key)) = Self::split_binding(hotkey) {
                 map.insert(key, func);
             }
         });
         map
     }
 
+    /// Split a `function[:argument],key` binding into function and key
+    ///
+    /// Keys never contain commas, so the binding is split at the last one
+    /// and arguments such as group names may contain commas.
+    pub fn split_binding(binding: &str) -> Option<(&str, &str)> {
+        let (func, key) = binding.rsplit_once(',')?;
+        let (func, key) = (func.trim(), key.trim());
+        (!func.is_empty() && !key.is_empty()).then_some((func, key))
+    }
+
+    /// Validate bindings that were added or changed, arguments must refer to
+    /// existing profiles and proxy groups
+    ///
+    /// Unchanged bindings whose target has disappeared are kept, they are
+    /// skipped with a warning when registering.
+    pub async fn validate(old_hotkeys: &[String], new_hotkeys: &[String]) -> Result<()> {
+        for binding in new_hotkeys.iter().filter(|b| !old_hotkeys.contains(b)) {
+            let (func, _) = Self::split_binding(binding)
+                .ok_or_else(|| anyhow!("invalid hotkey binding: {}", binding))?;
+            if Self::is_unsupported(func) {
+                continue;
+            }
+            let function = func.parse::<HotkeyFunction>()?;
+            if let Some(target) = Self::missing_target(&function).await {
+                bail!("hotkey refers to unknown {}", target);
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-register bindings with arguments against the current profiles and
+    /// runtime config, called after the runtime config is regenerated
+    pub async fn refresh_targets(&self) {
+        if handle::Handle::try_app_handle().is_none() {
+            return;
+        }
+        let enable_global_hotkey = Config::verge()
+            .await
+            .latest_ref()
+            .enable_global_hotkey
+            .unwrap_or(true);
+        if !enable_global_hotkey {
+            return;
+        }
+
+        let hotkeys = self.current.lock().clone();
+        for binding in &hotkeys {
+            if let Some((func, key)) = Self::split_binding(binding)
+                && func.contains(':')
+            {
+                logging_error!(Type::Hotkey, self.register(key, func).await);
+            }
+        }
+    }
+
+    /// Functions that only exist on other platforms
+    fn is_unsupported(func: &str) -> bool {
+        cfg!(not(target_os = "macos")) && func == "hide"
+    }
+
+    /// The profile or proxy group the function refers to, if it no longer exists
+    async fn missing_target(function: &HotkeyFunction) -> Option<String> {
+        match function {
+            HotkeyFunction::SwitchProfile(uid) => {
+                let exists = Config::profiles().await.latest_ref().get_item(uid).is_ok();
+                (!exists).then(|| format!("profile {uid}"))
+            }
+            HotkeyFunction::SelectNextNode(group) | HotkeyFunction::TestGroupDelay(group) => {
+                if group == "GLOBAL" {
+                    return None;
+                }
+                let exists = Config::runtime()
+                    .await
+                    .latest_ref()
+                    .config
+                    .as_ref()
+                    .and_then(|config| config.get("proxy-groups"))
+                    .and_then(|groups| groups.as_sequence())
+                    .is_some_and(|groups| {
+                        groups.iter().any(|item| {
+                            item.get("name").and_then(|name| name.as_str()) == Some(group)
+                        })
+                    });
+                (!exists).then(|| format!("proxy group {group}"))
+            }
+            _ => None,
+        }
+    }
+
     fn get_diff<'a>(
         old_map: HashMap<&'a str, &'a str>,
         new_map: HashMap<&'a str, &'a str>,
@@ -468,3 +686,54 @@ impl Drop for Hotkey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_binding() {
+        assert_eq!(
+            Hotkey::split_binding("clash_mode_rule,CMD+R"),
+            Some(("clash_mode_rule", "CMD+R"))
+        );
+        assert_eq!(
+            Hotkey::split_binding("select_next_node:A, B ,ALT+N"),
+            Some(("select_next_node:A, B", "ALT+N"))
+        );
+        assert_eq!(Hotkey::split_binding("clash_mode_rule"), None);
+        assert_eq!(Hotkey::split_binding("clash_mode_rule, "), None);
+    }
+
+    #[test]
+    fn test_parse_hotkey_function() {
+        for func in [
+            "copy_env",
+            "next_profile",
+            "switch_profile:RmXYZ",
+            "select_next_node:Proxy",
+            "test_group_delay:Auto: HK",
+        ] {
+            assert_eq!(
+                func.parse::<HotkeyFunction>().map(|f| f.to_string()).ok(),
+                Some(func.to_string())
+            );
+        }
+        assert!("switch_profile".parse::<HotkeyFunction>().is_err());
+        assert!("switch_profile: ".parse::<HotkeyFunction>().is_err());
+        assert!("copy_env:x".parse::<HotkeyFunction>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_changed_bindings() {
+        let old = vec!["unknown_function,CMD+U".to_string()];
+        let mut new = old.clone();
+        new.push("copy_env,CMD+C".into());
+        new.push("hide,CMD+H".into());
+        assert!(Hotkey::validate(&old, &new).await.is_ok());
+
+        new.push("copy_env:x,CMD+E".into());
+        assert!(Hotkey::validate(&old, &new).await.is_err());
+        assert!(Hotkey::validate(&[], &["copy_env".into()]).await.is_err());
+    }
+}
@@ -0,0 +1,825 @@
+//! Linux 系统代理后端
+//!
+//! 按桌面环境读写对应的代理存储：GNOME / Cinnamon / XFCE 使用 gsettings
+//! `org.gnome.system.proxy`，KDE Plasma 5/6 使用 `kioslaverc`。
+//! 无桌面会话可额外写入 `~/.config/environment.d/` 与 `/etc/environment` 风格的文件。
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Result, bail};
+use parking_lot::RwLock;
+use sysproxy::{Autoproxy, Sysproxy};
+
+use crate::{
+    config::{BypassPlatform, ILinuxProxy, parse_bypass, render_bypass},
+    logging, singleton_lazy,
+    utils::logging::Type,
+};
+
+const GNOME_SCHEMA: &str = "org.gnome.system.proxy";
+const KDE_GROUP: &str = "[Proxy Settings]";
+const ENVIRONMENT_D_FILE: &str = "90-needyclash-proxy.conf";
+const ENV_BLOCK_BEGIN: &str = "# >>> needyclash proxy >>>";
+const ENV_BLOCK_END: &str = "# <<< needyclash proxy <<<";
+
+/// 当前会话的桌面环境
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Desktop {
+    Gnome,
+    Cinnamon,
+    Xfce,
+    /// KDE Plasma 5/6，均使用 kioslaverc
+    Kde,
+    Other,
+}
+
+impl Desktop {
+    pub fn detect() -> Self {
+        let current = env::var("XDG_CURRENT_DESKTOP")
+            .or_else(|_| env::var("DESKTOP_SESSION"))
+            .unwrap_or_default();
+        Self::from_env(&current, env::var("KDE_SESSION_VERSION").ok().as_deref())
+    }
+
+    /// 根据 `XDG_CURRENT_DESKTOP`（冒号分隔）判断，旧会话仅设置 `KDE_SESSION_VERSION`
+    fn from_env(current: &str, kde_version: Option<&str>) -> Self {
+        for name in current
+            .split(':')
+            .map(|name| name.trim().to_ascii_lowercase())
+        {
+            match name.as_str() {
+                "kde" | "plasma" => return Self::Kde,
+                "x-cinnamon" | "cinnamon" => return Self::Cinnamon,
+                "xfce" => return Self::Xfce,
+                "gnome" | "gnome-classic" | "gnome-flashback" | "unity" | "budgie" | "pantheon" => {
+                    return Self::Gnome;
+                }
+                _ => {}
+            }
+        }
+        if kde_version.is_some_and(|version| !version.trim().is_empty()) {
+            return Self::Kde;
+        }
+        Self::Other
+    }
+}
+
+/// 代理设置实际写入的存储
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Store {
+    Gsettings,
+    Kioslaverc,
+    /// 仅使用环境变量文件
+    Env,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ProxyMode {
+    #[default]
+    None,
+    Manual,
+    Auto,
+}
+
+/// 各存储通用的代理状态
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ProxyState {
+    mode: ProxyMode,
+    host: String,
+    port: u16,
+    /// 逗号分隔
+    bypass: String,
+    pac_url: String,
+}
+
+impl ProxyState {
+    fn apply_sys(&mut self, sys: &Sysproxy) {
+        self.host = sys.host.clone();
+        self.port = sys.port;
+        self.bypass = sys.bypass.clone();
+        if sys.enable {
+            self.mode = ProxyMode::Manual;
+        } else if self.mode == ProxyMode::Manual {
+            self.mode = ProxyMode::None;
+        }
+    }
+
+    fn apply_auto(&mut self, auto: &Autoproxy) {
+        self.pac_url = auto.url.clone();
+        if auto.enable {
+            self.mode = ProxyMode::Auto;
+        } else if self.mode == ProxyMode::Auto {
+            self.mode = ProxyMode::None;
+        }
+    }
+
+    fn to_sys(&self) -> Sysproxy {
+        Sysproxy {
+            enable: self.mode == ProxyMode::Manual,
+            host: self.host.clone(),
+            port: self.port,
+            bypass: self.bypass.clone(),
+        }
+    }
+
+    fn to_auto(&self) -> Autoproxy {
+        Autoproxy {
+            enable: self.mode == ProxyMode::Auto,
+            url: self.pac_url.clone(),
+        }
+    }
+}
+
+/// 解析 `http://host:port`、`http://host port` 形式的代理地址
+pub fn parse_proxy_url(proxy_url: &str) -> Option<(String, u16)> {
+    let url = proxy_url.trim();
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.trim_end_matches('/');
+
+    let (host, port) = match url.rsplit_once([':', ' ']) {
+        Some((host, port)) => (host.trim(), port.trim().parse().ok()?),
+        None => (url, 8080),
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.into(), port))
+}
+
+fn split_bypass(bypass: &str) -> impl Iterator<Item = &str> {
+    bypass
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|host| !host.is_empty())
+}
+
+// ---------- gsettings ----------
+
+fn gvariant_string(value: &str) -> String {
+    value.trim().trim_matches('\'').trim_matches('"').into()
+}
+
+/// 解析 `['a', 'b']` 或 `@as []`
+fn gvariant_strv(value: &str) -> Vec<String> {
+    let value = value.trim().trim_start_matches("@as").trim();
+    let value = value.trim_start_matches('[').trim_end_matches(']');
+    value
+        .split(',')
+        .map(gvariant_string)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn render_gvariant_strv(bypass: &str) -> String {
+    let items: Vec<String> = split_bypass(bypass)
+        .map(|host| format!("'{}'", host.replace('\'', "")))
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// 解析 `gsettings list-recursively org.gnome.system.proxy` 的输出
+fn parse_gsettings(output: &str) -> ProxyState {
+    let mut values = HashMap::new();
+    for line in output.lines() {
+        let mut parts = line.splitn(3, ' ');
+        if let (Some(schema), Some(key), Some(value)) = (parts.next(), parts.next(), parts.next()) {
+            values.insert((schema, key), value.trim());
+        }
+    }
+    let get = |schema: &str, key: &str| values.get(&(schema, key)).copied().unwrap_or_default();
+    let http = format!("{GNOME_SCHEMA}.http");
+
+    ProxyState {
+        mode: match gvariant_string(get(GNOME_SCHEMA, "mode")).as_str() {
+            "manual" => ProxyMode::Manual,
+            "auto" => ProxyMode::Auto,
+            _ => ProxyMode::None,
+        },
+        host: gvariant_string(get(&http, "host")),
+        port: get(&http, "port").trim().parse().unwrap_or(0),
+        bypass: gvariant_strv(get(GNOME_SCHEMA, "ignore-hosts")).join(","),
+        pac_url: gvariant_string(get(GNOME_SCHEMA, "autoconfig-url")),
+    }
+}
+
+/// 生成写入代理状态的 `gsettings set` 参数，模式最后设置
+fn gsettings_commands(state: &ProxyState) -> Vec<[String; 3]> {
+    let mut commands = Vec::new();
+    for kind in ["http", "https", "socks"] {
+        let schema = format!("{GNOME_SCHEMA}.{kind}");
+        commands.push([schema.clone(), "host".into(), format!("'{}'", state.host)]);
+        commands.push([schema, "port".into(), state.port.to_string()]);
+    }
+    commands.push([
+        GNOME_SCHEMA.into(),
+        "ignore-hosts".into(),
+        render_gvariant_strv(&state.bypass),
+    ]);
+    commands.push([
+        GNOME_SCHEMA.into(),
+        "autoconfig-url".into(),
+        format!("'{}'", state.pac_url),
+    ]);
+    let mode = match state.mode {
+        ProxyMode::None => "'none'",
+        ProxyMode::Manual => "'manual'",
+        ProxyMode::Auto => "'auto'",
+    };
+    commands.push([GNOME_SCHEMA.into(), "mode".into(), mode.into()]);
+    commands
+}
+
+fn read_gsettings() -> Result<ProxyState> {
+    let output = Command::new("gsettings")
+        .args(["list-recursively", GNOME_SCHEMA])
+        .output()?;
+    if !output.status.success() {
+        bail!("gsettings list-recursively {GNOME_SCHEMA} failed");
+    }
+    Ok(parse_gsettings(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn write_gsettings(state: &ProxyState) -> Result<()> {
+    for [schema, key, value] in gsettings_commands(state) {
+        let status = Command::new("gsettings")
+            .args(["set", &schema, &key, &value])
+            .status()?;
+        if !status.success() {
+            bail!("gsettings set {schema} {key} failed");
+        }
+    }
+    Ok(())
+}
+
+// ---------- kioslaverc ----------
+
+fn kioslaverc_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("kioslaverc"))
+}
+
+/// 读取 `[Proxy Settings]` 分组的键值
+fn kioslaverc_values(content: &str) -> HashMap<&str, &str> {
+    let mut values = HashMap::new();
+    let mut in_group = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_group = line == KDE_GROUP;
+        } else if in_group && let Some((key, value)) = line.split_once('=') {
+            values.insert(key.trim(), value.trim());
+        }
+    }
+    values
+}
+
+fn parse_kioslaverc(content: &str) -> ProxyState {
+    let values = kioslaverc_values(content);
+    let get = |key: &str| values.get(key).copied().unwrap_or_default();
+    let (host, port) = parse_proxy_url(get("httpProxy")).unwrap_or_default();
+
+    ProxyState {
+        // 0 不使用, 1 手动, 2 PAC, 3 WPAD, 4 环境变量
+        mode: match get("ProxyType") {
+            "1" => ProxyMode::Manual,
+            "2" => ProxyMode::Auto,
+            _ => ProxyMode::None,
+        },
+        host,
+        port,
+        bypass: get("NoProxyFor").into(),
+        pac_url: get("Proxy Config Script").into(),
+    }
+}
+
+/// 将代理状态写入 kioslaverc 内容，保留其它分组与键
+fn render_kioslaverc(content: &str, state: &ProxyState) -> String {
+    let proxy_type = match state.mode {
+        ProxyMode::None => "0",
+        ProxyMode::Manual => "1",
+        ProxyMode::Auto => "2",
+    };
+    // KIO 的后缀写法与其它平台不同，按条目重新生成
+    let bypass = render_bypass(&parse_bypass(&state.bypass), BypassPlatform::Kde);
+    let entries = [
+        ("ProxyType", proxy_type.to_string()),
+        ("httpProxy", format!("http://{} {}", state.host, state.port)),
+        (
+            "httpsProxy",
+            format!("http://{} {}", state.host, state.port),
+        ),
+        (
+            "socksProxy",
+            format!("socks://{} {}", state.host, state.port),
+        ),
+        ("NoProxyFor", bypass),
+        ("ReversedException", "false".into()),
+        ("Proxy Config Script", state.pac_url.clone()),
+    ];
+    let managed = |line: &str| {
+        line.split_once('=')
+            .is_some_and(|(key, _)| entries.iter().any(|(name, _)| *name == key.trim()))
+    };
+    let group: Vec<String> = entries
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect();
+
+    let mut lines = Vec::new();
+    let mut in_group = false;
+    let mut written = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if in_group && !written {
+                insert_before_blank(&mut lines, &group);
+                written = true;
+            }
+            in_group = trimmed == KDE_GROUP;
+        } else if in_group && managed(trimmed) {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if in_group && !written {
+        insert_before_blank(&mut lines, &group);
+        written = true;
+    }
+    if !written {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(KDE_GROUP.into());
+        lines.extend(group);
+    }
+
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+/// 插入到分组末尾的空行之前，保持分组间的空行
+fn insert_before_blank(lines: &mut Vec<String>, group: &[String]) {
+    let mut at = lines.len();
+    while at > 0 && lines[at - 1].trim().is_empty() {
+        at -= 1;
+    }
+    lines.splice(at..at, group.iter().cloned());
+}
+
+fn read_kioslaverc() -> Result<ProxyState> {
+    let Some(path) = kioslaverc_path() else {
+        bail!("failed to locate kioslaverc");
+    };
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(parse_kioslaverc(&content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(ProxyState::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_kioslaverc(state: &ProxyState) -> Result<()> {
+    let Some(path) = kioslaverc_path() else {
+        bail!("failed to locate kioslaverc");
+    };
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, render_kioslaverc(&content, state))?;
+
+    // 通知已运行的 KIO 程序重新读取代理配置
+    let _ = Command::new("dbus-send")
+        .args([
+            "--type=signal",
+            "/KIO/Scheduler",
+            "org.kde.KIO.Scheduler.reparseSlaveConfiguration",
+            "string:",
+        ])
+        .status();
+    Ok(())
+}
+
+// ---------- 环境变量文件 ----------
+
+/// 生成代理环境变量，代理关闭时为空
+fn env_lines(sys: &Sysproxy) -> Vec<String> {
+    if !sys.enable {
+        return Vec::new();
+    }
+    let http = format!("http://{}:{}", sys.host, sys.port);
+    let socks = format!("socks5://{}:{}", sys.host, sys.port);
+    let no_proxy: Vec<&str> = split_bypass(&sys.bypass).collect();
+    let no_proxy = no_proxy.join(",");
+
+    let mut lines = Vec::new();
+    for (key, value) in [
+        ("http_proxy", &http),
+        ("https_proxy", &http),
+        ("all_proxy", &socks),
+        ("no_proxy", &no_proxy),
+    ] {
+        lines.push(format!("{key}={value}"));
+        lines.push(format!("{}={value}", key.to_ascii_uppercase()));
+    }
+    lines
+}
+
+/// 从环境变量文件内容中读取代理
+fn parse_env_proxy(content: &str) -> Option<Sysproxy> {
+    let values: HashMap<&str, &str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+        .collect();
+    let get = |key: &str| {
+        values
+            .get(key)
+            .or_else(|| values.get(key.to_ascii_uppercase().as_str()))
+            .copied()
+    };
+    let (host, port) = get("http_proxy")
+        .or_else(|| get("https_proxy"))
+        .and_then(parse_proxy_url)?;
+    Some(Sysproxy {
+        enable: true,
+        host,
+        port,
+        bypass: get("no_proxy").unwrap_or_default().into(),
+    })
+}
+
+/// 替换 `/etc/environment` 风格文件中由本程序管理的区块，`lines` 为空时移除区块
+fn render_env_block(content: &str, lines: &[String]) -> String {
+    let mut output = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        match line.trim() {
+            ENV_BLOCK_BEGIN => in_block = true,
+            ENV_BLOCK_END => in_block = false,
+            _ if !in_block => output.push(line.to_string()),
+            _ => {}
+        }
+    }
+    if !lines.is_empty() {
+        output.push(ENV_BLOCK_BEGIN.into());
+        output.extend(lines.iter().cloned());
+        output.push(ENV_BLOCK_END.into());
+    }
+
+    let mut output = output.join("\n");
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+fn environment_d_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("environment.d").join(ENVIRONMENT_D_FILE))
+}
+
+fn write_environment_d(sys: &Sysproxy) -> Result<()> {
+    let Some(path) = environment_d_path() else {
+        bail!("failed to locate environment.d");
+    };
+    let lines = env_lines(sys);
+    if lines.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        };
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut content = format!("# managed by {}\n", env!("CARGO_PKG_NAME"));
+    content.push_str(&lines.join("\n"));
+    content.push('\n');
+    fs::write(&path, content)?;
+    Ok(())
+}
+
+fn write_environment_file(path: &Path, sys: &Sysproxy) -> Result<()> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let rendered = render_env_block(&content, &env_lines(sys));
+    if rendered == content {
+        return Ok(());
+    }
+    match fs::write(path, &rendered) {
+        // 系统文件需要提权写入
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+            crate::utils::help::write_file_elevated(path, &rendered)
+        }
+        result => result.map_err(Into::into),
+    }
+}
+
+fn read_env_proxy() -> Sysproxy {
+    environment_d_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| parse_env_proxy(&content))
+        .or_else(|| {
+            let content: String = ["http_proxy", "https_proxy", "no_proxy"]
+                .iter()
+                .filter_map(|key| env::var(key).ok().map(|value| format!("{key}={value}\n")))
+                .collect();
+            parse_env_proxy(&content)
+        })
+        .unwrap_or_else(|| Sysproxy {
+            enable: false,
+            host: "127.0.0.1".into(),
+            port: 0,
+            bypass: String::new(),
+        })
+}
+
+/// Linux 系统代理读写入口
+#[derive(Default)]
+pub struct LinuxProxy {
+    settings: RwLock<ILinuxProxy>,
+}
+
+singleton_lazy!(LinuxProxy, LINUX_PROXY, LinuxProxy::default);
+
+impl LinuxProxy {
+    /// 更新后端设置，由系统代理设置流程在写入前调用
+    pub fn configure(&self, settings: ILinuxProxy) {
+        *self.settings.write() = settings;
+    }
+
+    fn store(&self) -> Store {
+        let backend = self.settings.read().backend.clone().unwrap_or_default();
+        match backend.as_str() {
+            "gsettings" => Store::Gsettings,
+            "kde" => Store::Kioslaverc,
+            "env" => Store::Env,
+            _ => match Desktop::detect() {
+                Desktop::Kde => Store::Kioslaverc,
+                Desktop::Other if self.manages_env_files() => Store::Env,
+                // XFCE 没有自己的代理设置，GTK 程序通过 GIO 读取 gsettings
+                _ => Store::Gsettings,
+            },
+        }
+    }
+
+    fn manages_env_files(&self) -> bool {
+        let settings = self.settings.read();
+        settings.environment_d.unwrap_or(false)
+            || settings
+                .environment_file
+                .as_ref()
+                .is_some_and(|path| !path.trim().is_empty())
+    }
+
+    fn read_state(&self, store: Store) -> Result<ProxyState> {
+        match store {
+            Store::Gsettings => read_gsettings(),
+            Store::Kioslaverc => read_kioslaverc(),
+            Store::Env => {
+                let sys = read_env_proxy();
+                let mut state = ProxyState::default();
+                state.apply_sys(&sys);
+                Ok(state)
+            }
+        }
+    }
+
+    fn write_state(&self, store: Store, state: &ProxyState) -> Result<()> {
+        match store {
+            Store::Gsettings => write_gsettings(state),
+            Store::Kioslaverc => write_kioslaverc(state),
+            Store::Env => Ok(()),
+        }
+    }
+
+    fn write_env_files(&self, sys: &Sysproxy) -> Result<()> {
+        let (environment_d, environment_file) = {
+            let settings = self.settings.read();
+            (
+                settings.environment_d.unwrap_or(false),
+                settings.environment_file.clone().unwrap_or_default(),
+            )
+        };
+        if environment_d {
+            write_environment_d(sys)?;
+        }
+        if !environment_file.trim().is_empty() {
+            write_environment_file(Path::new(environment_file.trim()), sys)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_system_proxy(&self) -> Result<Sysproxy> {
+        Ok(self.read_state(self.store())?.to_sys())
+    }
+
+    pub fn get_auto_proxy(&self) -> Result<Autoproxy> {
+        Ok(self.read_state(self.store())?.to_auto())
+    }
+
+    pub fn set_system_proxy(&self, sys: &Sysproxy) -> Result<()> {
+        let store = self.store();
+        logging!(
+            debug,
+            Type::System,
+            "写入 Linux 系统代理: {:?}, enable={}",
+            store,
+            sys.enable
+        );
+        let mut state = self.read_state(store).unwrap_or_default();
+        state.apply_sys(sys);
+        self.write_state(store, &state)?;
+        self.write_env_files(sys)
+    }
+
+    pub fn set_auto_proxy(&self, auto: &Autoproxy) -> Result<()> {
+        let store = self.store();
+        if store == Store::Env {
+            if auto.enable {
+                logging!(
+                    warn,
+                    Type::System,
+                    "环境变量文件不支持 PAC，跳过自动代理设置"
+                );
+            }
+            return Ok(());
+        }
+        let mut state = self.read_state(store).unwrap_or_default();
+        state.apply_auto(auto);
+        self.write_state(store, &state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GSETTINGS_FIXTURE: &str = "\
+org.gnome.system.proxy.ftp host ''
+org.gnome.system.proxy.ftp port 0
+org.gnome.system.proxy.socks host '127.0.0.1'
+org.gnome.system.proxy.socks port 7897
+org.gnome.system.proxy.http enabled false
+org.gnome.system.proxy.http host '127.0.0.1'
+org.gnome.system.proxy.http port 7897
+org.gnome.system.proxy.https host '127.0.0.1'
+org.gnome.system.proxy.https port 7897
+org.gnome.system.proxy autoconfig-url ''
+org.gnome.system.proxy ignore-hosts ['localhost', '127.0.0.0/8', '::1']
+org.gnome.system.proxy mode 'manual'
+org.gnome.system.proxy use-same-proxy true
+";
+
+    const KIOSLAVERC_FIXTURE: &str = "\
+[$Version]
+update_info=kioslave.upd:change-proxy-type
+
+[Proxy Settings]
+NoProxyFor=localhost,127.0.0.1
+Proxy Config Script=
+ProxyType=0
+httpProxy=http://10.0.0.2 3128
+
+[Cache Settings]
+CacheSize=256000
+";
+
+    fn sysproxy(enable: bool) -> Sysproxy {
+        Sysproxy {
+            enable,
+            host: "127.0.0.1".into(),
+            port: 7897,
+            bypass: "localhost,127.0.0.1".into(),
+        }
+    }
+
+    #[test]
+    fn test_detect_desktop() {
+        assert_eq!(Desktop::from_env("ubuntu:GNOME", None), Desktop::Gnome);
+        assert_eq!(Desktop::from_env("KDE", Some("6")), Desktop::Kde);
+        assert_eq!(Desktop::from_env("", Some("5")), Desktop::Kde);
+        assert_eq!(Desktop::from_env("X-Cinnamon", None), Desktop::Cinnamon);
+        assert_eq!(Desktop::from_env("XFCE", None), Desktop::Xfce);
+        assert_eq!(Desktop::from_env("sway", None), Desktop::Other);
+        assert_eq!(Desktop::from_env("", None), Desktop::Other);
+    }
+
+    #[test]
+    fn test_parse_proxy_url() {
+        assert_eq!(
+            parse_proxy_url("http://127.0.0.1:7897/"),
+            Some(("127.0.0.1".into(), 7897))
+        );
+        assert_eq!(
+            parse_proxy_url("http://10.0.0.2 3128"),
+            Some(("10.0.0.2".into(), 3128))
+        );
+        assert_eq!(
+            parse_proxy_url("proxy.lan"),
+            Some(("proxy.lan".into(), 8080))
+        );
+        assert_eq!(parse_proxy_url("http://"), None);
+    }
+
+    #[test]
+    fn test_gsettings_backend() {
+        let mut state = parse_gsettings(GSETTINGS_FIXTURE);
+        assert_eq!(state.mode, ProxyMode::Manual);
+        assert_eq!(state.host, "127.0.0.1");
+        assert_eq!(state.port, 7897);
+        assert_eq!(state.bypass, "localhost,127.0.0.0/8,::1");
+        assert!(
+            parse_gsettings("org.gnome.system.proxy ignore-hosts @as []")
+                .bypass
+                .is_empty()
+        );
+
+        // 关闭手动代理后回到 none
+        state.apply_sys(&sysproxy(false));
+        assert_eq!(state.mode, ProxyMode::None);
+
+        state.apply_auto(&Autoproxy {
+            enable: true,
+            url: "http://127.0.0.1:33331/commands/pac".into(),
+        });
+        let commands = gsettings_commands(&state);
+        assert_eq!(
+            commands.last(),
+            Some(&[GNOME_SCHEMA.into(), "mode".into(), "'auto'".into()])
+        );
+        assert!(commands.contains(&[
+            GNOME_SCHEMA.into(),
+            "ignore-hosts".into(),
+            "['localhost', '127.0.0.1']".into()
+        ]));
+        assert!(commands.contains(&[
+            GNOME_SCHEMA.into(),
+            "autoconfig-url".into(),
+            "'http://127.0.0.1:33331/commands/pac'".into()
+        ]));
+    }
+
+    #[test]
+    fn test_kioslaverc_backend() {
+        let state = parse_kioslaverc(KIOSLAVERC_FIXTURE);
+        assert_eq!(state.mode, ProxyMode::None);
+        assert_eq!((state.host.as_str(), state.port), ("10.0.0.2", 3128));
+
+        let mut state = state;
+        state.apply_sys(&sysproxy(true));
+        let rendered = render_kioslaverc(KIOSLAVERC_FIXTURE, &state);
+        assert!(rendered.contains("ProxyType=1\n"));
+        assert!(rendered.contains("httpProxy=http://127.0.0.1 7897\n"));
+        assert!(rendered.contains("[Cache Settings]\nCacheSize=256000\n"));
+        assert!(rendered.starts_with("[$Version]\nupdate_info=kioslave.upd:change-proxy-type\n"));
+        assert_eq!(rendered.matches("ProxyType=").count(), 1);
+        assert_eq!(parse_kioslaverc(&rendered), state);
+
+        // 文件不存在或没有代理分组时追加
+        let rendered = render_kioslaverc("[General]\nfoo=bar\n", &state);
+        assert!(rendered.starts_with("[General]\nfoo=bar\n\n[Proxy Settings]\n"));
+        assert_eq!(parse_kioslaverc(&rendered), state);
+    }
+
+    #[test]
+    fn test_environment_d_backend() {
+        let lines = env_lines(&sysproxy(true));
+        assert!(lines.contains(&"http_proxy=http://127.0.0.1:7897".to_string()));
+        assert!(lines.contains(&"ALL_PROXY=socks5://127.0.0.1:7897".to_string()));
+        assert!(lines.contains(&"NO_PROXY=localhost,127.0.0.1".to_string()));
+        assert!(env_lines(&sysproxy(false)).is_empty());
+
+        let parsed = parse_env_proxy(&lines.join("\n"));
+        assert_eq!(
+            parsed.map(|proxy| (proxy.host, proxy.port, proxy.bypass)),
+            Some(("127.0.0.1".into(), 7897, "localhost,127.0.0.1".into()))
+        );
+        assert!(parse_env_proxy("# http_proxy=http://1.1.1.1:80\nLANG=C").is_none());
+    }
+
+    #[test]
+    fn test_environment_file_block() {
+        let original = "PATH=\"/usr/local/bin:/usr/bin\"\nLANG=en_US.UTF-8\n";
+        let lines = env_lines(&sysproxy(true));
+
+        let enabled = render_env_block(original, &lines);
+        assert!(enabled.starts_with(original));
+        assert!(enabled.contains(ENV_BLOCK_BEGIN));
+        assert!(parse_env_proxy(&enabled).is_some());
+
+        // 重复写入不产生多个区块
+        let again = render_env_block(&enabled, &lines);
+        assert_eq!(again, enabled);
+
+        // 关闭后恢复原内容
+        assert_eq!(render_env_block(&enabled, &[]), original);
+        assert_eq!(render_env_block("", &[]), "");
+    }
+}
@@ -0,0 +1,545 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use compact_str::CompactString;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{logging, singleton_lazy, utils::dirs, utils::logging::Type};
+
+const STORE_DIR_NAME: &str = "store";
+const STORE_FILE_PREFIX: &str = "core_logs";
+const STORE_FILE_EXT: &str = "jsonl";
+const DEFAULT_MAX_SIZE_KB: u64 = 1024;
+const DEFAULT_MAX_FILES: usize = 8;
+const DEFAULT_PAGE_SIZE: usize = 200;
+const MAX_PAGE_SIZE: usize = 5000;
+
+/// mihomo 的 logfmt 输出：time="..." level=info msg="..."
+static LOGFMT_RE: Lazy<Option<Regex>> =
+    Lazy::new(|| Regex::new(r#"^time="([^"]+)"\s+level=(\w+)\s+msg="(.*)"\s*$"#).ok());
+
+/// 服务模式转发的纯文本输出：<time> <level> <message>
+static PLAIN_RE: Lazy<Option<Regex>> =
+    Lazy::new(|| Regex::new(r"^(\d{4}-\d{2}-\d{2}[T ]\S+)\s+(\w+)\s+(.*)$").ok());
+
+/// 消息开头的来源标记，例如 `[TCP]`、`[DNS]`
+static SOURCE_RE: Lazy<Option<Regex>> = Lazy::new(|| Regex::new(r"^\[([A-Za-z][\w\-]*)\]\s*").ok());
+
+/// 内核日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoreLogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl CoreLogLevel {
+    fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_lowercase().as_str() {
+            "debug" | "dbg" | "trace" => Some(Self::Debug),
+            "info" | "inf" => Some(Self::Info),
+            "warn" | "warning" | "wrn" => Some(Self::Warning),
+            "error" | "err" => Some(Self::Error),
+            "fatal" | "fata" | "panic" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+}
+
+/// 结构化的内核日志记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoreLogRecord {
+    /// 毫秒时间戳
+    pub time: i64,
+    pub level: CoreLogLevel,
+    /// 日志来源，如 `TCP`、`DNS`，无法识别时为 `core`
+    pub source: CompactString,
+    pub message: CompactString,
+}
+
+impl CoreLogRecord {
+    /// 解析一行内核输出，无法识别格式时按 info 级别整行保存
+    pub fn parse_line(line: &str, fallback_time: i64) -> Self {
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        let parsed = LOGFMT_RE
+            .as_ref()
+            .and_then(|re| re.captures(line))
+            .or_else(|| PLAIN_RE.as_ref().and_then(|re| re.captures(line)))
+            .and_then(|caps| {
+                let level = CoreLogLevel::parse(caps.get(2)?.as_str())?;
+                let time = parse_time(caps.get(1)?.as_str()).unwrap_or(fallback_time);
+                Some((time, level, caps.get(3)?.as_str().replace("\\\"", "\"")))
+            });
+
+        let (time, level, message) =
+            parsed.unwrap_or_else(|| (fallback_time, CoreLogLevel::Info, line.into()));
+
+        let (source, message) = match SOURCE_RE.as_ref().and_then(|re| re.captures(&message)) {
+            Some(caps) => {
+                let source = caps.get(1).map(|m| m.as_str()).unwrap_or("core");
+                let rest = caps.get(0).map(|m| m.end()).unwrap_or(0);
+                (
+                    CompactString::from(source),
+                    CompactString::from(&message[rest..]),
+                )
+            }
+            None => (
+                CompactString::const_new("core"),
+                CompactString::from(message),
+            ),
+        };
+
+        Self {
+            time,
+            level,
+            source,
+            message,
+        }
+    }
+}
+
+fn parse_time(raw: &str) -> Option<i64> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(raw) {
+        return Some(time.timestamp_millis());
+    }
+    chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .and_then(|time| time.and_local_timezone(Local).single())
+        .map(|time| time.timestamp_millis())
+}
+
+/// 日志查询条件，所有字段均为可选
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct CoreLogQuery {
+    /// 仅返回这些级别的日志
+    pub levels: Option<Vec<CoreLogLevel>>,
+    /// 起始时间（毫秒时间戳，包含）
+    pub start: Option<i64>,
+    /// 结束时间（毫秒时间戳，包含）
+    pub end: Option<i64>,
+    /// 不区分大小写的文本匹配
+    pub text: Option<String>,
+    /// 正则匹配（作用于来源和消息）
+    pub regex: Option<String>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CoreLogPage {
+    /// 满足条件的记录总数
+    pub total: usize,
+    pub records: Vec<CoreLogRecord>,
+}
+
+/// 由查询条件编译得到的过滤器
+struct CoreLogFilter {
+    levels: Option<Vec<CoreLogLevel>>,
+    start: Option<i64>,
+    end: Option<i64>,
+    text: Option<String>,
+    regex: Option<Regex>,
+}
+
+impl CoreLogFilter {
+    fn new(query: &CoreLogQuery) -> Result<Self> {
+        let regex = match query.regex.as_deref().filter(|r| !r.is_empty()) {
+            Some(pattern) => Some(
+                RegexBuilder::new(pattern)
+                    .size_limit(1 << 20)
+                    .build()
+                    .with_context(|| format!("invalid regex: {pattern}"))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            levels: query.levels.clone().filter(|levels| !levels.is_empty()),
+            start: query.start,
+            end: query.end,
+            text: query
+                .text
+                .as_deref()
+                .filter(|t| !t.is_empty())
+                .map(str::to_lowercase),
+            regex,
+        })
+    }
+
+    fn matches(&self, record: &CoreLogRecord) -> bool {
+        if let Some(levels) = &self.levels
+            && !levels.contains(&record.level)
+        {
+            return false;
+        }
+        if self.start.is_some_and(|start| record.time < start)
+            || self.end.is_some_and(|end| record.time > end)
+        {
+            return false;
+        }
+        if let Some(text) = &self.text
+            && !record.message.to_lowercase().contains(text)
+            && !record.source.to_lowercase().contains(text)
+        {
+            return false;
+        }
+        if let Some(regex) = &self.regex
+            && !regex.is_match(&record.message)
+            && !regex.is_match(&record.source)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+#[derive(Default)]
+struct StoreWriter {
+    file: Option<File>,
+    size: u64,
+    /// 服务模式下最后一条已入库的原始日志及其时间，用于增量同步
+    last_service_line: Option<(CompactString, i64)>,
+}
+
+/// 持久化的内核日志存储，按大小滚动写入 `sidecar_log_dir/store`
+pub struct CoreLogStore {
+    writer: Mutex<StoreWriter>,
+    max_size: AtomicU64,
+    max_files: AtomicUsize,
+}
+
+impl Default for CoreLogStore {
+    fn default() -> Self {
+        Self {
+            writer: Mutex::new(StoreWriter::default()),
+            max_size: AtomicU64::new(DEFAULT_MAX_SIZE_KB * 1024),
+            max_files: AtomicUsize::new(DEFAULT_MAX_FILES),
+        }
+    }
+}
+
+impl CoreLogStore {
+    fn store_dir() -> Result<PathBuf> {
+        let dir = dirs::sidecar_log_dir()?.join(STORE_DIR_NAME);
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    fn current_path(dir: &Path) -> PathBuf {
+        dir.join(format!("{STORE_FILE_PREFIX}.{STORE_FILE_EXT}"))
+    }
+
+    /// 更新滚动参数，`max_size_kb` 为单个文件的大小上限
+    pub fn set_limits(&self, max_size_kb: u64, max_files: usize) {
+        self.max_size
+            .store(max_size_kb.max(64) * 1024, Ordering::Relaxed);
+        self.max_files.store(max_files.max(1), Ordering::Relaxed);
+    }
+
+    /// 解析并写入一行内核输出
+    pub fn append_line(&self, line: &str) {
+        let record = CoreLogRecord::parse_line(line, Local::now().timestamp_millis());
+        if let Err(e) = self.append(&record) {
+            logging!(warn, Type::Core, "写入内核日志存储失败: {}", e);
+        }
+    }
+
+    /// 将服务模式返回的日志增量写入存储
+    pub fn sync_service_lines(&self, lines: &VecDeque<CompactString>) {
+        let now = Local::now().timestamp_millis();
+        let last = self.writer.lock().last_service_line.clone();
+        let start = unsynced_start(lines, last.as_ref(), now);
+
+        let mut synced = None;
+        for line in lines.iter().skip(start) {
+            let record = CoreLogRecord::parse_line(line, now);
+            if let Err(e) = self.append(&record) {
+                logging!(warn, Type::Core, "写入内核日志存储失败: {}", e);
+                break;
+            }
+            synced = Some((line.clone(), record.time));
+        }
+        if synced.is_some() {
+            self.writer.lock().last_service_line = synced;
+        }
+    }
+
+    fn append(&self, record: &CoreLogRecord) -> Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut writer = self.writer.lock();
+        if writer.file.is_none() {
+            let path = Self::current_path(&Self::store_dir()?);
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writer.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            writer.file = Some(file);
+        }
+
+        if writer.size + line.len() as u64 > self.max_size.load(Ordering::Relaxed) {
+            writer.file = None;
+            self.rotate()?;
+            let path = Self::current_path(&Self::store_dir()?);
+            writer.file = Some(OpenOptions::new().create(true).append(true).open(&path)?);
+            writer.size = 0;
+        }
+
+        if let Some(file) = writer.file.as_mut() {
+            file.write_all(line.as_bytes())?;
+        }
+        writer.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// 将当前文件归档，并清理超出数量的旧文件
+    fn rotate(&self) -> Result<()> {
+        let dir = Self::store_dir()?;
+        let current = Self::current_path(&dir);
+        if current.exists() {
+            let stamp = Local::now().format("%Y-%m-%d_%H-%M-%S%.3f");
+            fs::rename(
+                &current,
+                dir.join(format!("{STORE_FILE_PREFIX}_{stamp}.{STORE_FILE_EXT}")),
+            )?;
+        }
+
+        let archived = Self::archived_files(&dir)?;
+        let keep = self.max_files.load(Ordering::Relaxed).saturating_sub(1);
+        if archived.len() > keep {
+            for path in &archived[..archived.len() - keep] {
+                let _ = fs::remove_file(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// 已归档的文件，按时间从旧到新排序
+    fn archived_files(dir: &Path) -> Result<Vec<PathBuf>> {
+        let prefix = format!("{STORE_FILE_PREFIX}_");
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(STORE_FILE_EXT))
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// 所有存储文件，按时间从旧到新排序
+    fn all_files() -> Result<Vec<PathBuf>> {
+        let dir = Self::store_dir()?;
+        let mut files = Self::archived_files(&dir)?;
+        let current = Self::current_path(&dir);
+        if current.exists() {
+            files.push(current);
+        }
+        Ok(files)
+    }
+
+    fn for_each_match<F>(&self, query: &CoreLogQuery, mut f: F) -> Result<()>
+    where
+        F: FnMut(CoreLogRecord) -> Result<()>,
+    {
+        let filter = CoreLogFilter::new(query)?;
+
+        for path in Self::all_files()? {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    logging!(warn, Type::Core, "无法读取日志文件 {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            for line in BufReader::new(file).lines() {
+                let Ok(line) = line else { continue };
+                let Ok(record) = serde_json::from_str::<CoreLogRecord>(&line) else {
+                    continue;
+                };
+                if filter.matches(&record) {
+                    f(record)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 按条件分页查询日志，结果按时间从旧到新排列
+    pub fn query(&self, query: &CoreLogQuery) -> Result<CoreLogPage> {
+        let offset = query.offset.unwrap_or(0);
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let mut total = 0;
+        let mut records = Vec::new();
+        self.for_each_match(query, |record| {
+            if total >= offset && records.len() < limit {
+                records.push(record);
+            }
+            total += 1;
+            Ok(())
+        })?;
+
+        Ok(CoreLogPage { total, records })
+    }
+
+    /// 将满足条件的日志导出为文本文件，返回导出条数
+    pub fn export(&self, query: &CoreLogQuery, target: &Path) -> Result<usize> {
+        let mut out = std::io::BufWriter::new(File::create(target)?);
+        let mut count = 0;
+        self.for_each_match(query, |record| {
+            let time = DateTime::from_timestamp_millis(record.time)
+                .map(|t| {
+                    t.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S%.3f")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "{time} [{:?}] [{}] {}",
+                record.level, record.source, record.message
+            )?;
+            count += 1;
+            Ok(())
+        })?;
+        out.flush()?;
+        Ok(count)
+    }
+
+    /// 删除所有已持久化的日志
+    pub fn clear(&self) -> Result<()> {
+        let mut writer = self.writer.lock();
+        writer.file = None;
+        writer.size = 0;
+        for path in Self::all_files()? {
+            fs::remove_file(&path)?;
+        }
+        Ok(())
+    }
+}
+
+singleton_lazy!(CoreLogStore, CORE_LOG_STORE, CoreLogStore::default);
+
+/// 服务端缓冲区中第一条未入库日志的位置
+///
+/// 上次的最后一行仍在缓冲区中时从其后开始；已被滚出时按时间跳过不晚于它的行，
+/// 避免整个缓冲区被重复写入。
+fn unsynced_start(
+    lines: &VecDeque<CompactString>,
+    last: Option<&(CompactString, i64)>,
+    now: i64,
+) -> usize {
+    let Some((last_line, last_time)) = last else {
+        return 0;
+    };
+    if let Some(pos) = lines.iter().rposition(|line| line == last_line) {
+        return pos + 1;
+    }
+    lines
+        .iter()
+        .position(|line| CoreLogRecord::parse_line(line, now).time > *last_time)
+        .unwrap_or(lines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_logfmt_line() {
+        let record = CoreLogRecord::parse_line(
+            r#"time="2024-05-01T12:00:00.5+08:00" level=warning msg="[TCP] dial \"proxy\" failed""#,
+            0,
+        );
+        assert_eq!(record.level, CoreLogLevel::Warning);
+        assert_eq!(record.source, "TCP");
+        assert_eq!(record.message, r#"dial "proxy" failed"#);
+        assert_eq!(record.time, 1_714_536_000_500);
+    }
+
+    #[test]
+    fn test_parse_unknown_line() {
+        let record = CoreLogRecord::parse_line("Process terminated with code: 1", 42);
+        assert_eq!(record.level, CoreLogLevel::Info);
+        assert_eq!(record.source, "core");
+        assert_eq!(record.time, 42);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_filter_matches() {
+        let record = CoreLogRecord {
+            time: 100,
+            level: CoreLogLevel::Error,
+            source: "DNS".into(),
+            message: "resolve example.com failed".into(),
+        };
+
+        let query = CoreLogQuery {
+            levels: Some(vec![CoreLogLevel::Error]),
+            start: Some(50),
+            text: Some("EXAMPLE".into()),
+            regex: Some(r"^resolve \S+ failed$".into()),
+            ..Default::default()
+        };
+        assert!(CoreLogFilter::new(&query).unwrap().matches(&record));
+
+        let query = CoreLogQuery {
+            end: Some(99),
+            ..Default::default()
+        };
+        assert!(!CoreLogFilter::new(&query).unwrap().matches(&record));
+
+        let query = CoreLogQuery {
+            regex: Some("(".into()),
+            ..Default::default()
+        };
+        assert!(CoreLogFilter::new(&query).is_err());
+    }
+
+    #[test]
+    fn test_unsynced_start() {
+        let line = |second: u32| {
+            CompactString::from(format!(
+                r#"time="2024-05-01T12:00:0{second}+08:00" level=info msg="line {second}""#
+            ))
+        };
+        let lines = VecDeque::from([line(2), line(3), line(4)]);
+        let synced = |second: u32| {
+            let line = line(second);
+            let time = CoreLogRecord::parse_line(&line, 0).time;
+            (line, time)
+        };
+
+        assert_eq!(unsynced_start(&lines, None, 0), 0);
+        assert_eq!(unsynced_start(&lines, Some(&synced(3)), 0), 2);
+        assert_eq!(unsynced_start(&lines, Some(&synced(4)), 0), 3);
+        // 上次的最后一行已滚出缓冲区时按时间跳过
+        let rotated = VecDeque::from([line(5), line(6)]);
+        assert_eq!(unsynced_start(&rotated, Some(&synced(4)), 0), 0);
+        let mut overlapped = lines.clone();
+        overlapped.pop_front();
+        overlapped.push_back(line(5));
+        let stale = (CompactString::from("gone"), synced(3).1);
+        assert_eq!(unsynced_start(&overlapped, Some(&stale), 0), 1);
+    }
+}
@@ -4,15 +4,26 @@ pub mod backup;
 mod config_validator;
 mod core;
 pub mod event_driven_proxy;
+pub mod guard_history;
 pub mod handle;
 pub mod hotkey;
+#[cfg(target_os = "linux")]
+pub mod linux_sysproxy;
+pub mod log_store;
 pub mod logger;
+pub mod network_switch;
+pub mod pac;
 mod process_manager;
+pub mod resource_monitor;
 pub mod service;
 pub mod sysopt;
 pub mod timer;
+pub mod tool_proxy;
 pub mod tray;
 pub mod tun_manager;
+#[cfg(target_os = "linux")]
+pub mod tun_preflight;
+pub mod unlock_history;
 pub mod win_uwp;
 
 pub use self::{core::*, event_driven_proxy::EventDrivenProxyManager, timer::Timer};
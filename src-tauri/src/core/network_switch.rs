@@ -0,0 +1,625 @@
+use std::{
+    collections::VecDeque,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use network_interface::{Addr, NetworkInterface, NetworkInterfaceConfig, V4IfAddr};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use serde_yaml_ng::Value;
+use tokio::{
+    net::{TcpSocket, TcpStream},
+    process::Command,
+    time::timeout,
+};
+
+use crate::{
+    config::{Config, INetworkSwitchRule},
+    core::handle,
+    feat, logging,
+    process::AsyncHandler,
+    singleton_lazy,
+    utils::{
+        cidr::IpCidr,
+        logging::Type,
+        notification::{NotificationEvent, notify_event},
+    },
+};
+
+/// 保留的切换记录数量
+const HISTORY_CAPACITY: usize = 200;
+const DEFAULT_INTERVAL_SECS: u64 = 10;
+const MIN_INTERVAL_SECS: u64 = 3;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+/// mihomo 默认的 TUN 地址与 fake-ip 网段
+const DEFAULT_TUN_NETWORK: &str = "198.18.0.0/15";
+
+/// 当前网络环境
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NetworkSnapshot {
+    /// 已分配非回环地址的网卡
+    pub interfaces: Vec<String>,
+    pub gateway: Option<String>,
+    pub ssid: Option<String>,
+}
+
+/// 默认网关所在的物理网卡，探测时绑定到该网卡以绕过 TUN
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PhysicalInterface {
+    name: String,
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    index: u32,
+    addr: Ipv4Addr,
+}
+
+/// 内核创建的 TUN 网卡，不参与网卡匹配与探测
+#[derive(Debug, Clone, Default)]
+struct TunFilter {
+    device: Option<String>,
+    networks: Vec<IpCidr>,
+}
+
+impl TunFilter {
+    async fn current() -> Self {
+        let tun = {
+            let runtime = Config::runtime().await;
+            runtime
+                .latest_ref()
+                .config
+                .as_ref()
+                .and_then(|config| config.get("tun"))
+                .cloned()
+        };
+        let device = tun
+            .as_ref()
+            .and_then(|tun| tun.get("device"))
+            .and_then(Value::as_str)
+            .map(String::from);
+        let networks = tun
+            .as_ref()
+            .and_then(|tun| tun.get("inet4-address"))
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .chain([DEFAULT_TUN_NETWORK])
+            .filter_map(|network| network.parse().ok())
+            .collect();
+        Self { device, networks }
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    fn is_tun(&self, interface: &NetworkInterface) -> bool {
+        self.device.as_deref() == Some(interface.name.as_str())
+            || interface.addr.iter().any(|addr| self.contains(addr.ip()))
+    }
+}
+
+/// 一次自动切换的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkSwitchRecord {
+    /// 毫秒时间戳
+    pub time: i64,
+    pub rule: String,
+    pub network: NetworkSnapshot,
+    /// 已执行的动作
+    pub actions: Vec<String>,
+    /// 执行失败的动作
+    pub errors: Vec<String>,
+}
+
+/// 网络环境监测与自动切换
+pub struct NetworkSwitcher {
+    history: RwLock<VecDeque<NetworkSwitchRecord>>,
+    /// 最近一次生效的规则下标，规则不变时不重复应用，避免覆盖用户的手动调整
+    applied_rule: Mutex<Option<usize>>,
+    started: AtomicBool,
+}
+
+impl Default for NetworkSwitcher {
+    fn default() -> Self {
+        Self {
+            history: RwLock::new(VecDeque::with_capacity(HISTORY_CAPACITY)),
+            applied_rule: Mutex::new(None),
+            started: AtomicBool::new(false),
+        }
+    }
+}
+
+impl NetworkSwitcher {
+    /// 启动后台监测任务，重复调用无副作用
+    pub fn start(&'static self) {
+        if self
+            .started
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return;
+        }
+
+        AsyncHandler::spawn(move || async move {
+            logging!(info, Type::Network, "网络环境自动切换已启动");
+            loop {
+                let (enabled, interval, rules) = {
+                    let verge = Config::verge().await;
+                    let settings = verge
+                        .latest_ref()
+                        .network_switch
+                        .clone()
+                        .unwrap_or_default();
+                    (
+                        settings.enabled.unwrap_or(false),
+                        settings
+                            .interval
+                            .unwrap_or(DEFAULT_INTERVAL_SECS)
+                            .max(MIN_INTERVAL_SECS),
+                        settings.rules.unwrap_or_default(),
+                    )
+                };
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+
+                if handle::Handle::global().is_exiting() {
+                    break;
+                }
+                if !enabled || rules.is_empty() {
+                    *self.applied_rule.lock() = None;
+                    continue;
+                }
+
+                self.evaluate(&rules).await;
+            }
+            logging!(info, Type::Network, "网络环境自动切换已停止");
+        });
+    }
+
+    async fn evaluate(&self, rules: &[INetworkSwitchRule]) {
+        let tun = TunFilter::current().await;
+        let (snapshot, physical) = detect(&tun).await;
+
+        let mut matched = None;
+        for (index, rule) in rules.iter().enumerate() {
+            if rule.enabled.unwrap_or(true)
+                && rule_matches(rule, &snapshot, &tun, physical.as_ref()).await
+            {
+                matched = Some(index);
+                break;
+            }
+        }
+
+        let Some(index) = matched else {
+            *self.applied_rule.lock() = None;
+            return;
+        };
+        if self.applied_rule.lock().replace(index) == Some(index) {
+            return;
+        }
+
+        let Some(rule) = rules.get(index) else {
+            return;
+        };
+        let record = apply_rule(rule, snapshot).await;
+        logging!(
+            info,
+            Type::Network,
+            "网络环境变化，应用规则 [{}]: {:?}, 失败: {:?}",
+            record.rule,
+            record.actions,
+            record.errors
+        );
+        if let Some(app_handle) = handle::Handle::try_app_handle() {
+            notify_event(
+                app_handle.clone(),
+                NotificationEvent::NetworkSwitched { rule: &record.rule },
+            )
+            .await;
+        }
+        self.push(record);
+    }
+
+    fn push(&self, record: NetworkSwitchRecord) {
+        let mut history = self.history.write();
+        if history.len() >= HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(record);
+    }
+
+    /// 获取最近的切换记录，按时间从旧到新排列
+    pub fn history(&self, limit: Option<usize>) -> Vec<NetworkSwitchRecord> {
+        let history = self.history.read();
+        let skip = limit.map_or(0, |limit| history.len().saturating_sub(limit));
+        history.iter().skip(skip).cloned().collect()
+    }
+}
+
+singleton_lazy!(NetworkSwitcher, NETWORK_SWITCHER, NetworkSwitcher::default);
+
+fn rule_name(rule: &INetworkSwitchRule) -> String {
+    rule.name.clone().unwrap_or_else(|| "unnamed".into())
+}
+
+/// 静态条件（网卡、网关、SSID）是否满足，空条件视为满足
+fn static_conditions_match(rule: &INetworkSwitchRule, snapshot: &NetworkSnapshot) -> bool {
+    let interfaces_match = rule.interfaces.as_ref().is_none_or(|names| {
+        names.is_empty() || names.iter().any(|name| snapshot.interfaces.contains(name))
+    });
+    let gateway_match = rule
+        .gateway
+        .as_ref()
+        .is_none_or(|gateway| snapshot.gateway.as_deref() == Some(gateway.trim()));
+    let ssid_match = rule
+        .ssid
+        .as_ref()
+        .is_none_or(|ssid| snapshot.ssid.as_deref() == Some(ssid.as_str()));
+    interfaces_match && gateway_match && ssid_match
+}
+
+async fn rule_matches(
+    rule: &INetworkSwitchRule,
+    snapshot: &NetworkSnapshot,
+    tun: &TunFilter,
+    physical: Option<&PhysicalInterface>,
+) -> bool {
+    if !static_conditions_match(rule, snapshot) {
+        return false;
+    }
+    match rule.probe_host.as_deref().map(str::trim) {
+        Some(host) if !host.is_empty() => probe(host, tun, physical).await,
+        _ => true,
+    }
+}
+
+/// 探测目标是否可达，有物理网卡时绑定到该网卡，避免连接被 TUN 接管而总是成功
+async fn probe(host: &str, tun: &TunFilter, physical: Option<&PhysicalInterface>) -> bool {
+    let addr = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{host}:80")
+    };
+    let connect = async {
+        let Some(physical) = physical else {
+            return TcpStream::connect(addr).await.map(drop);
+        };
+        // TUN 接管 DNS 时解析结果可能是 fake-ip，这类地址只能经由 TUN 访问
+        let target = tokio::net::lookup_host(&addr)
+            .await?
+            .find(|target| target.is_ipv4() && !tun.contains(target.ip()))
+            .ok_or_else(|| std::io::Error::other(format!("no routable address for {addr}")))?;
+        connect_bound(target, physical).await.map(drop)
+    };
+    matches!(timeout(PROBE_TIMEOUT, connect).await, Ok(Ok(())))
+}
+
+async fn connect_bound(
+    target: SocketAddr,
+    physical: &PhysicalInterface,
+) -> std::io::Result<TcpStream> {
+    let socket = TcpSocket::new_v4()?;
+    #[cfg(target_os = "linux")]
+    if let Err(e) = socket.bind_device(Some(physical.name.as_bytes())) {
+        logging!(
+            debug,
+            Type::Network,
+            "绑定网卡 {} 失败，仅绑定源地址: {}",
+            physical.name,
+            e
+        );
+    }
+    #[cfg(target_os = "macos")]
+    bind_interface_index(&socket, physical.index)?;
+    socket.bind(SocketAddr::new(IpAddr::V4(physical.addr), 0))?;
+    socket.connect(target).await
+}
+
+/// 通过 `IP_BOUND_IF` 将连接限定在指定网卡上
+#[cfg(target_os = "macos")]
+fn bind_interface_index(socket: &TcpSocket, index: u32) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IP,
+            libc::IP_BOUND_IF,
+            std::ptr::from_ref(&index).cast(),
+            std::mem::size_of::<u32>() as libc::socklen_t,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+async fn apply_rule(rule: &INetworkSwitchRule, network: NetworkSnapshot) -> NetworkSwitchRecord {
+    let mut actions = Vec::new();
+    let mut errors = Vec::new();
+
+    if let Some(uid) = rule.profile.clone() {
+        let current = Config::profiles().await.latest_ref().get_current();
+        if current.as_ref() != Some(&uid) {
+            feat::toggle_proxy_profile(uid.clone()).await;
+            let current = Config::profiles().await.latest_ref().get_current();
+            if current.as_ref() == Some(&uid) {
+                actions.push(format!("profile: {uid}"));
+            } else {
+                errors.push(format!("profile: {uid}"));
+            }
+        }
+    }
+    if let Some(mode) = rule.mode.clone() {
+        match feat::set_clash_mode(mode.clone()).await {
+            Ok(()) => actions.push(format!("mode: {mode}")),
+            Err(err) => errors.push(format!("mode: {err}")),
+        }
+    }
+    if let Some(enable) = rule.system_proxy {
+        match feat::set_system_proxy(enable).await {
+            Ok(()) => actions.push(format!("system_proxy: {enable}")),
+            Err(err) => errors.push(format!("system_proxy: {err}")),
+        }
+    }
+    if let Some(enable) = rule.tun {
+        match feat::set_tun_mode(enable).await {
+            Ok(()) => actions.push(format!("tun: {enable}")),
+            Err(err) => errors.push(format!("tun: {err}")),
+        }
+    }
+
+    NetworkSwitchRecord {
+        time: chrono::Local::now().timestamp_millis(),
+        rule: rule_name(rule),
+        network,
+        actions,
+        errors,
+    }
+}
+
+/// 采集当前网络环境
+pub async fn detect_network() -> NetworkSnapshot {
+    detect(&TunFilter::current().await).await.0
+}
+
+async fn detect(tun: &TunFilter) -> (NetworkSnapshot, Option<PhysicalInterface>) {
+    let tun_filter = tun.clone();
+    let interfaces = AsyncHandler::spawn_blocking(move || active_interfaces(&tun_filter))
+        .await
+        .unwrap_or_default();
+    let gateway = default_gateway().await;
+    let physical = physical_interface(&interfaces, gateway.as_deref());
+
+    let mut names: Vec<String> = interfaces
+        .into_iter()
+        .map(|interface| interface.name)
+        .collect();
+    names.sort();
+    names.dedup();
+    let snapshot = NetworkSnapshot {
+        interfaces: names,
+        gateway,
+        ssid: current_ssid().await,
+    };
+    (snapshot, physical)
+}
+
+/// 已分配非回环地址且不属于 TUN 的网卡
+fn active_interfaces(tun: &TunFilter) -> Vec<NetworkInterface> {
+    NetworkInterface::show()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|interface| interface.addr.iter().any(|addr| !addr.ip().is_loopback()))
+        .filter(|interface| !tun.is_tun(interface))
+        .collect()
+}
+
+/// 优先选择与默认网关同网段的网卡，否则取第一个带 IPv4 地址的网卡
+fn physical_interface(
+    interfaces: &[NetworkInterface],
+    gateway: Option<&str>,
+) -> Option<PhysicalInterface> {
+    let gateway = gateway.and_then(|gateway| gateway.parse::<Ipv4Addr>().ok());
+    let candidates: Vec<(&NetworkInterface, &V4IfAddr)> = interfaces
+        .iter()
+        .flat_map(|interface| {
+            interface.addr.iter().filter_map(move |addr| match addr {
+                Addr::V4(v4) if !v4.ip.is_loopback() => Some((interface, v4)),
+                _ => None,
+            })
+        })
+        .collect();
+    let same_subnet = |(_, v4): &&(&NetworkInterface, &V4IfAddr)| {
+        gateway.zip(v4.netmask).is_some_and(|(gateway, netmask)| {
+            u32::from(gateway) & u32::from(netmask) == u32::from(v4.ip) & u32::from(netmask)
+        })
+    };
+    let (interface, v4) = candidates
+        .iter()
+        .find(same_subnet)
+        .or_else(|| candidates.first())?;
+    Some(PhysicalInterface {
+        name: interface.name.clone(),
+        index: interface.index,
+        addr: v4.ip,
+    })
+}
+
+async fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    {
+        #[allow(unused_imports)] // Required for .creation_flags() method
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW - 隐藏窗口
+    }
+    let output = timeout(COMMAND_TIMEOUT, command.output())
+        .await
+        .ok()?
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+async fn default_gateway() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let content = tokio::fs::read_to_string("/proc/net/route").await.ok()?;
+        parse_proc_route_gateway(&content)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = command_output("route", &["-n", "get", "default"]).await?;
+        parse_labeled_value(&output, "gateway:")
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let output = command_output("route", &["print", "-4", "0.0.0.0"]).await?;
+        parse_windows_route_gateway(&output)
+    }
+}
+
+async fn current_ssid() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let output = command_output("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"]).await?;
+        parse_nmcli_ssid(&output)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = command_output("networksetup", &["-getairportnetwork", "en0"]).await?;
+        parse_labeled_value(&output, "Current Wi-Fi Network:")
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let output = command_output("netsh", &["wlan", "show", "interfaces"]).await?;
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "SSID")
+                .then(|| value.trim().to_string())
+                .filter(|ssid| !ssid.is_empty())
+        })
+    }
+}
+
+/// 解析 `/proc/net/route`，取目标为 0.0.0.0 的网关（小端十六进制）
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_route_gateway(content: &str) -> Option<String> {
+    content.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (destination, gateway) = (fields.get(1)?, fields.get(2)?);
+        if *destination != "00000000" || *gateway == "00000000" {
+            return None;
+        }
+        let raw = u32::from_str_radix(gateway, 16).ok()?;
+        Some(std::net::Ipv4Addr::from(raw.to_le_bytes()).to_string())
+    })
+}
+
+/// 解析 `nmcli -t -f active,ssid dev wifi` 输出
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_nmcli_ssid(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        line.strip_prefix("yes:")
+            .map(|ssid| ssid.replace("\\:", ":"))
+            .filter(|ssid| !ssid.is_empty())
+    })
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_labeled_value(output: &str, label: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix(label)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    })
+}
+
+/// 解析 `route print -4 0.0.0.0` 输出中的默认路由网关
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_windows_route_gateway(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["0.0.0.0", "0.0.0.0", gateway, ..]
+                if gateway.parse::<std::net::Ipv4Addr>().is_ok() =>
+            {
+                Some((*gateway).to_string())
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_route_gateway() {
+        let content = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+            wlp2s0\t0000A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\n\
+            wlp2s0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\n";
+        assert_eq!(
+            parse_proc_route_gateway(content).as_deref(),
+            Some("192.168.1.1")
+        );
+    }
+
+    #[test]
+    fn test_parse_ssid_and_gateway_outputs() {
+        assert_eq!(
+            parse_nmcli_ssid("no:Neighbor\nyes:Office\\:5G\n").as_deref(),
+            Some("Office:5G")
+        );
+        assert_eq!(
+            parse_labeled_value("   route to: default\n    gateway: 10.0.0.1\n", "gateway:")
+                .as_deref(),
+            Some("10.0.0.1")
+        );
+        let windows = "Network Destination        Netmask          Gateway       Interface  Metric\n\
+            \x20         0.0.0.0          0.0.0.0      172.16.0.1    172.16.0.23     25\n";
+        assert_eq!(
+            parse_windows_route_gateway(windows).as_deref(),
+            Some("172.16.0.1")
+        );
+    }
+
+    #[test]
+    fn test_static_conditions_match() {
+        let snapshot = NetworkSnapshot {
+            interfaces: vec!["eth0".into(), "wlan0".into()],
+            gateway: Some("10.0.0.1".into()),
+            ssid: Some("Office".into()),
+        };
+        let office = INetworkSwitchRule {
+            gateway: Some("10.0.0.1".into()),
+            ssid: Some("Office".into()),
+            ..Default::default()
+        };
+        let home = INetworkSwitchRule {
+            ssid: Some("Home".into()),
+            ..Default::default()
+        };
+        let vpn = INetworkSwitchRule {
+            interfaces: Some(vec!["tun0".into()]),
+            ..Default::default()
+        };
+        assert!(static_conditions_match(&office, &snapshot));
+        assert!(!static_conditions_match(&home, &snapshot));
+        assert!(!static_conditions_match(&vpn, &snapshot));
+        assert!(static_conditions_match(
+            &INetworkSwitchRule::default(),
+            &snapshot
+        ));
+    }
+}
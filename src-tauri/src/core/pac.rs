@@ -0,0 +1,246 @@
+//! `/commands/pac` 提供的 PAC 脚本
+//!
+//! 默认使用 `pac_file_content`，开启 `pac_from_rules` 后根据运行时配置的规则生成，
+//! 让不支持系统代理的浏览器也按规则分流。脚本中的 `%mixed-port%` 在输出时替换。
+
+use std::net::Ipv4Addr;
+
+use parking_lot::RwLock;
+use serde_yaml_ng::Value;
+
+use crate::{
+    config::{Config, DEFAULT_PAC},
+    logging, singleton_lazy,
+    utils::logging::Type,
+};
+
+const PROXY_RESULT: &str = "PROXY 127.0.0.1:%mixed-port%; SOCKS5 127.0.0.1:%mixed-port%; DIRECT";
+/// 拒绝的请求指向本机 discard 端口，且不回退到直连，使其无法建立连接
+const REJECT_RESULT: &str = "\"PROXY 127.0.0.1:9\"";
+
+/// 单条规则转换后的判断条件
+fn rule_condition(kind: &str, payload: &str, no_resolve: bool) -> Option<String> {
+    let payload = payload.trim().to_ascii_lowercase();
+    if payload.is_empty() {
+        return None;
+    }
+    let literal = serde_json::to_string(&payload).ok()?;
+    match kind {
+        "DOMAIN" => Some(format!("host === {literal}")),
+        "DOMAIN-SUFFIX" => {
+            let suffix = serde_json::to_string(&format!(".{payload}")).ok()?;
+            Some(format!("host === {literal} || dnsDomainIs(host, {suffix})"))
+        }
+        "DOMAIN-KEYWORD" => Some(format!("host.indexOf({literal}) !== -1")),
+        "IP-CIDR" => {
+            let (net, mask) = parse_ipv4_cidr(&payload)?;
+            Some(format!("inNet(\"{net}\", \"{mask}\", {no_resolve})"))
+        }
+        _ => None,
+    }
+}
+
+/// `10.0.0.0/8` -> (`10.0.0.0`, `255.0.0.0`)
+fn parse_ipv4_cidr(cidr: &str) -> Option<(Ipv4Addr, Ipv4Addr)> {
+    let (addr, prefix) = cidr.split_once('/')?;
+    let addr: Ipv4Addr = addr.trim().parse().ok()?;
+    let prefix: u32 = prefix.trim().parse().ok()?;
+    if prefix > 32 {
+        return None;
+    }
+    let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+    Some((Ipv4Addr::from(u32::from(addr) & mask), Ipv4Addr::from(mask)))
+}
+
+fn policy_result(policy: &str) -> &'static str {
+    match policy.trim().to_ascii_uppercase().as_str() {
+        "DIRECT" => "\"DIRECT\"",
+        "REJECT" | "REJECT-DROP" => REJECT_RESULT,
+        _ => "proxy",
+    }
+}
+
+/// 根据规则生成 PAC 脚本，无法在浏览器端判断的规则会被跳过
+pub fn generate_pac(rules: &[String]) -> String {
+    let mut branches = Vec::new();
+    let mut skipped = 0usize;
+    let mut fallback = "proxy";
+
+    for rule in rules {
+        let parts: Vec<&str> = rule.split(',').map(str::trim).collect();
+        let kind = parts
+            .first()
+            .copied()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        if kind == "MATCH" {
+            fallback = policy_result(parts.get(1).copied().unwrap_or_default());
+            break;
+        }
+        let branch = match (parts.get(1), parts.get(2)) {
+            (Some(payload), Some(policy)) => {
+                let no_resolve = parts
+                    .iter()
+                    .skip(3)
+                    .any(|option| option.eq_ignore_ascii_case("no-resolve"));
+                rule_condition(&kind, payload, no_resolve).map(|condition| {
+                    format!("  if ({condition}) return {};", policy_result(policy))
+                })
+            }
+            _ => None,
+        };
+        match branch {
+            Some(branch) => branches.push(branch),
+            None => skipped += 1,
+        }
+    }
+
+    let mut script = String::new();
+    script.push_str(&format!(
+        "// generated from {} rules, {skipped} unsupported rules skipped\n",
+        rules.len()
+    ));
+    script.push_str("function FindProxyForURL(url, host) {\n");
+    script.push_str(&format!("  var proxy = \"{PROXY_RESULT}\";\n"));
+    script.push_str("  host = host.toLowerCase();\n");
+    script.push_str("  var isIp = /^\\d+\\.\\d+\\.\\d+\\.\\d+$/.test(host);\n");
+    script.push_str("  var ip = isIp ? host : null;\n");
+    script.push_str("  function inNet(net, mask, noResolve) {\n");
+    script.push_str("    if (noResolve && !isIp) return false;\n");
+    script.push_str("    if (ip === null) ip = dnsResolve(host) || \"\";\n");
+    script.push_str("    return ip !== \"\" && isInNet(ip, net, mask);\n");
+    script.push_str("  }\n");
+    for branch in branches {
+        script.push_str(&branch);
+        script.push('\n');
+    }
+    script.push_str(&format!("  return {fallback};\n}}\n"));
+    script
+}
+
+/// 从运行时配置中读取规则
+fn runtime_rules(config: Option<&serde_yaml_ng::Mapping>) -> Vec<String> {
+    config
+        .and_then(|config| config.get("rules"))
+        .and_then(Value::as_sequence)
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 当前对外提供的 PAC 脚本
+pub struct PacManager {
+    content: RwLock<String>,
+}
+
+impl Default for PacManager {
+    fn default() -> Self {
+        Self {
+            content: RwLock::new(DEFAULT_PAC.into()),
+        }
+    }
+}
+
+singleton_lazy!(PacManager, PAC_MANAGER, PacManager::default);
+
+impl PacManager {
+    /// 当前 PAC 脚本，`%mixed-port%` 尚未替换
+    pub fn content(&self) -> String {
+        self.content.read().clone()
+    }
+
+    /// 按设置重新生成，运行时配置更新或 PAC 设置变化后调用
+    pub async fn refresh(&self) {
+        let (from_rules, custom) = {
+            let verge = Config::verge().await;
+            let verge = verge.latest_ref();
+            (
+                verge.pac_from_rules.unwrap_or(false),
+                verge.pac_file_content.clone(),
+            )
+        };
+
+        let content = if from_rules {
+            let rules = {
+                let runtime = Config::runtime().await;
+                runtime_rules(runtime.latest_ref().config.as_ref())
+            };
+            logging!(
+                info,
+                Type::Config,
+                "根据 {} 条运行时规则生成 PAC",
+                rules.len()
+            );
+            generate_pac(&rules)
+        } else {
+            custom.unwrap_or_else(|| DEFAULT_PAC.into())
+        };
+        *self.content.write() = content;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(rules: &[&str]) -> Vec<String> {
+        rules.iter().map(|rule| rule.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_ipv4_cidr() {
+        assert_eq!(
+            parse_ipv4_cidr("192.168.1.7/16"),
+            Some((Ipv4Addr::new(192, 168, 0, 0), Ipv4Addr::new(255, 255, 0, 0)))
+        );
+        assert_eq!(
+            parse_ipv4_cidr("0.0.0.0/0"),
+            Some((Ipv4Addr::new(0, 0, 0, 0), Ipv4Addr::new(0, 0, 0, 0)))
+        );
+        assert_eq!(parse_ipv4_cidr("10.0.0.0/33"), None);
+        assert_eq!(parse_ipv4_cidr("fd00::/8"), None);
+    }
+
+    #[test]
+    fn test_generate_pac() {
+        let script = generate_pac(&rules(&[
+            "DOMAIN,ads.example.com,REJECT",
+            "DOMAIN-SUFFIX,Example.com,DIRECT",
+            "DOMAIN-KEYWORD,google,Proxy",
+            "IP-CIDR,10.0.0.0/8,DIRECT,no-resolve",
+            "GEOIP,CN,DIRECT",
+            "RULE-SET,reject,REJECT",
+            "MATCH,DIRECT",
+            "DOMAIN,after-match.com,Proxy",
+        ]));
+
+        assert!(script.starts_with("// generated from 8 rules, 2 unsupported rules skipped\n"));
+        assert!(script.contains("if (host === \"ads.example.com\") return \"PROXY 127.0.0.1:9\";"));
+        assert!(script.contains(
+            "if (host === \"example.com\" || dnsDomainIs(host, \".example.com\")) return \"DIRECT\";"
+        ));
+        assert!(script.contains("if (host.indexOf(\"google\") !== -1) return proxy;"));
+        assert!(
+            script.contains("if (inNet(\"10.0.0.0\", \"255.0.0.0\", true)) return \"DIRECT\";")
+        );
+        assert!(script.trim_end().ends_with("return \"DIRECT\";\n}"));
+        assert!(!script.contains("after-match.com"));
+
+        // 规则顺序保持不变
+        let ads = script.find("ads.example.com").unwrap_or_default();
+        let suffix = script.find("dnsDomainIs").unwrap_or_default();
+        assert!(ads < suffix);
+    }
+
+    #[test]
+    fn test_generate_pac_without_match() {
+        let script = generate_pac(&[]);
+        assert!(script.contains("return proxy;\n}"));
+        assert!(script.contains("%mixed-port%"));
+    }
+}
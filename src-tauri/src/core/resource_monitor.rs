@@ -0,0 +1,295 @@
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::{
+    config::{Config, IVerge},
+    core::{CoreManager, RunningMode, handle},
+    logging, logging_error,
+    process::AsyncHandler,
+    singleton_lazy,
+    utils::{
+        logging::Type,
+        notification::{NotificationEvent, notify_event},
+    },
+};
+
+/// 保留的采样点数量
+const HISTORY_CAPACITY: usize = 1440;
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+const MIN_INTERVAL_SECS: u64 = 1;
+const DEFAULT_SUSTAINED_SECS: u64 = 300;
+
+/// 单次采样结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CoreResourceSample {
+    /// 毫秒时间戳
+    pub time: i64,
+    pub pid: u32,
+    /// CPU 占用百分比（多核可超过 100）
+    pub cpu_usage: f32,
+    /// 常驻内存（字节）
+    pub memory: u64,
+    /// 打开的文件描述符数量，平台不支持时为空
+    pub open_files: Option<usize>,
+    /// 线程数量，平台不支持时为空
+    pub threads: Option<usize>,
+}
+
+/// 超限时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThresholdAction {
+    Warn,
+    Restart,
+}
+
+/// 从 verge 配置解析出的监控参数
+struct MonitorSettings {
+    enabled: bool,
+    interval: Duration,
+    memory_limit: Option<u64>,
+    sustained: Duration,
+    action: ThresholdAction,
+}
+
+impl MonitorSettings {
+    fn from_verge(verge: &IVerge) -> Self {
+        let monitor = verge.core_resource_monitor.clone().unwrap_or_default();
+        let action = match monitor.action.as_deref() {
+            Some("restart") => ThresholdAction::Restart,
+            _ => ThresholdAction::Warn,
+        };
+        Self {
+            enabled: monitor.enabled.unwrap_or(true),
+            interval: Duration::from_secs(
+                monitor
+                    .interval
+                    .unwrap_or(DEFAULT_INTERVAL_SECS)
+                    .max(MIN_INTERVAL_SECS),
+            ),
+            memory_limit: monitor
+                .memory_limit
+                .filter(|&mb| mb > 0)
+                .map(|mb| mb * 1024 * 1024),
+            sustained: Duration::from_secs(
+                monitor.sustained_secs.unwrap_or(DEFAULT_SUSTAINED_SECS),
+            ),
+            action,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SamplerState {
+    system: System,
+    /// 当前跟踪的内核进程
+    pid: Option<Pid>,
+    /// 内存首次超过阈值的时间
+    over_limit_since: Option<Instant>,
+}
+
+/// 内核进程资源监控
+pub struct CoreResourceMonitor {
+    history: RwLock<VecDeque<CoreResourceSample>>,
+    state: Mutex<SamplerState>,
+    started: AtomicBool,
+}
+
+impl Default for CoreResourceMonitor {
+    fn default() -> Self {
+        Self {
+            history: RwLock::new(VecDeque::with_capacity(HISTORY_CAPACITY)),
+            state: Mutex::new(SamplerState::default()),
+            started: AtomicBool::new(false),
+        }
+    }
+}
+
+impl CoreResourceMonitor {
+    /// 启动后台采样任务，重复调用无副作用
+    pub fn start(&'static self) {
+        if self
+            .started
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return;
+        }
+
+        AsyncHandler::spawn(move || async move {
+            logging!(info, Type::Core, "内核资源监控已启动");
+            loop {
+                let settings = {
+                    let verge = Config::verge().await;
+                    MonitorSettings::from_verge(&verge.latest_ref())
+                };
+                tokio::time::sleep(settings.interval).await;
+
+                if handle::Handle::global().is_exiting() {
+                    break;
+                }
+                if !settings.enabled {
+                    continue;
+                }
+
+                let Some(sample) = self.sample() else {
+                    continue;
+                };
+                self.check_threshold(&settings, &sample).await;
+                self.push(sample);
+            }
+            logging!(info, Type::Core, "内核资源监控已停止");
+        });
+    }
+
+    fn push(&self, sample: CoreResourceSample) {
+        let mut history = self.history.write();
+        if history.len() >= HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(sample);
+    }
+
+    /// 定位内核进程：优先使用 sidecar 子进程 PID，否则按进程名查找
+    fn locate_core(state: &mut SamplerState) -> Option<Pid> {
+        if let Some(pid) = CoreManager::global().get_core_pid() {
+            return Some(Pid::from_u32(pid));
+        }
+        if CoreManager::global().get_running_mode() == RunningMode::NotRunning {
+            return None;
+        }
+        if let Some(pid) = state.pid
+            && state.system.process(pid).is_some()
+        {
+            return Some(pid);
+        }
+
+        state.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        IVerge::VALID_CLASH_CORES.iter().find_map(|name| {
+            let name = if cfg!(windows) {
+                format!("{name}.exe")
+            } else {
+                (*name).to_string()
+            };
+            state
+                .system
+                .processes_by_exact_name(name.as_ref())
+                .next()
+                .map(|process| process.pid())
+        })
+    }
+
+    fn sample(&self) -> Option<CoreResourceSample> {
+        let mut state = self.state.lock();
+        let pid = Self::locate_core(&mut state)?;
+        if state.pid != Some(pid) {
+            // 进程变化后重新累计超限时间
+            state.pid = Some(pid);
+            state.over_limit_since = None;
+        }
+
+        state.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[pid]),
+            true,
+            ProcessRefreshKind::nothing()
+                .with_cpu()
+                .with_memory()
+                .with_tasks(),
+        );
+        let process = state.system.process(pid)?;
+
+        Some(CoreResourceSample {
+            time: chrono::Local::now().timestamp_millis(),
+            pid: pid.as_u32(),
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            open_files: process.open_files(),
+            threads: process.tasks().map(|tasks| tasks.len()),
+        })
+    }
+
+    /// 记录超限时间，持续超限达到设定时长时返回 true
+    fn exceeded_for_sustained(
+        &self,
+        settings: &MonitorSettings,
+        sample: &CoreResourceSample,
+    ) -> bool {
+        let mut state = self.state.lock();
+        let over_limit = settings
+            .memory_limit
+            .is_some_and(|limit| sample.memory > limit);
+        if !over_limit {
+            state.over_limit_since = None;
+            return false;
+        }
+
+        let since = *state.over_limit_since.get_or_insert_with(Instant::now);
+        if since.elapsed() < settings.sustained {
+            return false;
+        }
+        // 触发后重新计时，避免每次采样重复处理
+        state.over_limit_since = None;
+        true
+    }
+
+    async fn check_threshold(&self, settings: &MonitorSettings, sample: &CoreResourceSample) {
+        if !self.exceeded_for_sustained(settings, sample) {
+            return;
+        }
+
+        let memory = (sample.memory / 1024 / 1024).to_string();
+        let limit = (settings.memory_limit.unwrap_or_default() / 1024 / 1024).to_string();
+        let seconds = settings.sustained.as_secs().to_string();
+        logging!(
+            warn,
+            Type::Core,
+            "内核内存持续超限: {} MB > {} MB, {}s",
+            memory,
+            limit,
+            seconds
+        );
+
+        let restarted = matches!(settings.action, ThresholdAction::Restart);
+        if restarted {
+            logging!(info, Type::Core, "内存超限，正在重启内核");
+            logging_error!(Type::Core, CoreManager::global().restart_core().await);
+            handle::Handle::refresh_clash();
+        }
+        // 无界面守护模式没有 Tauri 应用，无法发送系统通知
+        if let Some(app_handle) = handle::Handle::try_app_handle() {
+            notify_event(
+                app_handle.clone(),
+                NotificationEvent::CoreMemoryExceeded {
+                    memory: &memory,
+                    limit: &limit,
+                    seconds: &seconds,
+                    restarted,
+                },
+            )
+            .await;
+        }
+    }
+
+    /// 获取最近的采样记录，按时间从旧到新排列
+    pub fn history(&self, limit: Option<usize>) -> Vec<CoreResourceSample> {
+        let history = self.history.read();
+        let skip = limit.map_or(0, |limit| history.len().saturating_sub(limit));
+        history.iter().skip(skip).cloned().collect()
+    }
+}
+
+singleton_lazy!(
+    CoreResourceMonitor,
+    CORE_RESOURCE_MONITOR,
+    CoreResourceMonitor::default
+);
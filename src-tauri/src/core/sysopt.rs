@@ -1,9 +1,15 @@
+#[cfg(target_os = "linux")]
+use crate::core::linux_sysproxy::LinuxProxy;
 #[cfg(target_os = "windows")]
 use crate::utils::autostart as startup_shortcut;
 use crate::{
-    config::{Config, IVerge},
-    core::{EventDrivenProxyManager, handle::Handle},
-    logging, logging_error, singleton_lazy,
+    config::{
+        BypassPlatform, Config, IVerge, derive_bypass_from_rules, parse_bypass, render_bypass,
+    },
+    core::{EventDrivenProxyManager, handle::Handle, tool_proxy::ToolProxy},
+    logging, logging_error,
+    process::AsyncHandler,
+    singleton_lazy,
     utils::logging::Type,
 };
 use anyhow::Result;
@@ -26,29 +32,132 @@ static DEFAULT_BYPASS: &str =
 #[cfg(target_os = "macos")]
 static DEFAULT_BYPASS: &str = "127.0.0.1,192.168.0.0/16,10.0.0.0/8,172.16.0.0/12,172.29.0.0/16,localhost,*.local,*.crashlytics.com,<local>";
 
-async fn get_bypass() -> String {
-    let use_default = Config::verge()
+/// 系统代理读写，Linux 下按桌面环境分发到对应的存储
+#[cfg(not(target_os = "windows"))]
+pub trait ProxyStore: Sized {
+    fn load() -> Result<Self>;
+    fn store(&self) -> Result<()>;
+}
+
+#[cfg(not(target_os = "windows"))]
+impl ProxyStore for Sysproxy {
+    fn load() -> Result<Self> {
+        #[cfg(target_os = "linux")]
+        {
+            LinuxProxy::global().get_system_proxy()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Ok(Sysproxy::get_system_proxy()?)
+        }
+    }
+
+    fn store(&self) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            LinuxProxy::global().set_system_proxy(self)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Ok(self.set_system_proxy()?)
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+impl ProxyStore for Autoproxy {
+    fn load() -> Result<Self> {
+        #[cfg(target_os = "linux")]
+        {
+            LinuxProxy::global().get_auto_proxy()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Ok(Autoproxy::get_auto_proxy()?)
+        }
+    }
+
+    fn store(&self) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            LinuxProxy::global().set_auto_proxy(self)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Ok(self.set_auto_proxy()?)
+        }
+    }
+}
+
+/// 将最新的 Linux 代理后端设置同步给 [`LinuxProxy`]
+#[cfg(target_os = "linux")]
+async fn configure_linux_proxy() {
+    let settings = Config::verge()
         .await
         .latest_ref()
-        .use_default_bypass
-        .unwrap_or(true);
-    let res = {
+        .linux_proxy
+        .clone()
+        .unwrap_or_default();
+    LinuxProxy::global().configure(settings);
+}
+
+/// 系统代理绕过字符串，配置了条目列表时按当前平台生成，否则沿用旧的字符串设置
+pub(crate) async fn get_bypass() -> String {
+    let (use_default, custom_bypass, entries, from_rules) = {
         let verge = Config::verge().await;
         let verge = verge.latest_ref();
-        verge.system_proxy_bypass.clone()
-    };
-    let custom_bypass = match res {
-        Some(bypass) => bypass,
-        None => "".into(),
+        (
+            verge.use_default_bypass.unwrap_or(true),
+            verge.system_proxy_bypass.clone().unwrap_or_default(),
+            verge.bypass_entries.clone(),
+            verge.bypass_from_rules.unwrap_or(false),
+        )
     };
 
-    if custom_bypass.is_empty() {
-        DEFAULT_BYPASS.into()
-    } else if use_default {
-        format!("{DEFAULT_BYPASS},{custom_bypass}")
-    } else {
-        custom_bypass
+    let mut entries = match entries {
+        Some(entries) => entries,
+        None => {
+            let bypass = if custom_bypass.is_empty() {
+                DEFAULT_BYPASS.into()
+            } else if use_default {
+                format!("{DEFAULT_BYPASS},{custom_bypass}")
+            } else {
+                custom_bypass
+            };
+            if !from_rules {
+                return bypass;
+            }
+            parse_bypass(&bypass)
+        }
+    };
+    if from_rules {
+        let runtime = Config::runtime().await;
+        entries.extend(derive_bypass_from_rules(
+            runtime.latest_ref().config.as_ref(),
+        ));
     }
+    if entries.is_empty() || use_default {
+        entries.splice(0..0, parse_bypass(DEFAULT_BYPASS));
+    }
+    render_bypass(&entries, BypassPlatform::current())
+}
+
+/// 同步开发工具代理，`proxy` 为 None 时还原，文件读写与提权在阻塞线程中执行
+///
+/// 退出过程中不提权，避免提权窗口阻塞退出
+async fn sync_tool_proxy(proxy: Option<String>) {
+    let settings = Config::verge()
+        .await
+        .latest_ref()
+        .tool_proxy
+        .clone()
+        .unwrap_or_default();
+    let elevate = !Handle::global().is_exiting();
+    let result = AsyncHandler::spawn_blocking(move || {
+        ToolProxy::global().sync(&settings, proxy.as_deref(), elevate);
+    })
+    .await;
+    logging_error!(Type::System, result);
 }
 
 // Uses tokio Command with CREATE_NO_WINDOW flag to avoid DLL initialization issues during shutdown
@@ -128,6 +237,11 @@ impl Sysopt {
             )
         };
 
+        #[cfg(target_os = "linux")]
+        configure_linux_proxy().await;
+
+        sync_tool_proxy(sys_enable.then(|| format!("http://{proxy_host}:{port}"))).await;
+
         #[cfg(not(target_os = "windows"))]
         {
             let mut sys = Sysproxy {
@@ -142,8 +256,8 @@ impl Sysopt {
             };
 
             if !sys_enable {
-                sys.set_system_proxy()?;
-                auto.set_auto_proxy()?;
+                sys.store()?;
+                auto.store()?;
                 let proxy_manager = EventDrivenProxyManager::global();
                 proxy_manager.notify_config_changed();
                 return Ok(());
@@ -152,8 +266,8 @@ impl Sysopt {
             if pac_enable {
                 sys.enable = false;
                 auto.enable = true;
-                sys.set_system_proxy()?;
-                auto.set_auto_proxy()?;
+                sys.store()?;
+                auto.store()?;
                 let proxy_manager = EventDrivenProxyManager::global();
                 proxy_manager.notify_config_changed();
                 return Ok(());
@@ -162,8 +276,8 @@ impl Sysopt {
             if sys_enable {
                 auto.enable = false;
                 sys.enable = true;
-                auto.set_auto_proxy()?;
-                sys.set_system_proxy()?;
+                auto.store()?;
+                sys.store()?;
                 let proxy_manager = EventDrivenProxyManager::global();
                 proxy_manager.notify_config_changed();
                 return Ok(());
@@ -199,10 +313,15 @@ impl Sysopt {
     pub async fn reset_sysproxy(&self) -> Result<()> {
         let _lock = self.reset_sysproxy.lock().await;
         //直接关闭所有代理
+        #[cfg(target_os = "linux")]
+        configure_linux_proxy().await;
+
+        sync_tool_proxy(None).await;
+
         #[cfg(not(target_os = "windows"))]
         {
-            let mut sysproxy: Sysproxy = Sysproxy::get_system_proxy()?;
-            let mut autoproxy = match Autoproxy::get_auto_proxy() {
+            let mut sysproxy = Sysproxy::load()?;
+            let mut autoproxy = match Autoproxy::load() {
                 Ok(ap) => ap,
                 Err(e) => {
                     log::warn!(target: "app", "重置代理时获取自动代理配置失败: {e}, 使用默认配置");
@@ -214,8 +333,8 @@ impl Sysopt {
             };
             sysproxy.enable = false;
             autoproxy.enable = false;
-            autoproxy.set_auto_proxy()?;
-            sysproxy.set_system_proxy()?;
+            autoproxy.store()?;
+            sysproxy.store()?;
         }
 
         #[cfg(target_os = "windows")]
@@ -267,7 +386,10 @@ impl Sysopt {
 
     /// 尝试使用原来的自启动方法
     fn try_original_autostart_method(&self, is_enable: bool) {
-        let app_handle = Handle::app_handle();
+        let Some(app_handle) = Handle::try_app_handle() else {
+            logging!(warn, Type::System, "无界面守护模式不支持设置自启动");
+            return;
+        };
         let autostart_manager = app_handle.autolaunch();
 
         if is_enable {
@@ -294,7 +416,9 @@ impl Sysopt {
         }
 
         // 回退到原来的方法
-        let app_handle = Handle::app_handle();
+        let Some(app_handle) = Handle::try_app_handle() else {
+            anyhow::bail!("auto launch status is unavailable in daemon mode");
+        };
         let autostart_manager = app_handle.autolaunch();
 
         match autostart_manager.is_enabled() {
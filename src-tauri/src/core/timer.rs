@@ -1,7 +1,7 @@
 use crate::{config::Config, feat, logging, logging_error, singleton, utils::logging::Type};
 use anyhow::{Context, Result};
 use delay_timer::prelude::{DelayTimer, DelayTimerBuilder, TaskBuilder};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::{
     collections::HashMap,
     pin::Pin,
@@ -33,6 +33,12 @@ pub struct Timer {
 
     /// Flag to mark if timer is initialized - atomic for better performance
     pub initialized: AtomicBool,
+
+    /// 解锁检测定时复检任务
+    unlock_recheck_task: Mutex<Option<TimerTask>>,
+
+    /// 后台节点延迟探测任务
+    delay_probe_task: Mutex<Option<TimerTask>>,
 }
 
 // Use singleton macro
@@ -45,6 +51,8 @@ impl Timer {
             timer_map: Arc::new(RwLock::new(HashMap::new())),
             timer_count: AtomicU64::new(1),
             initialized: AtomicBool::new(false),
+            unlock_recheck_task: Mutex::new(None),
+            delay_probe_task: Mutex::new(None),
         }
     }
 
@@ -135,6 +143,9 @@ impl Timer {
             }
         }
 
+        logging_error!(Type::Timer, self.refresh_unlock_recheck_task().await);
+        logging_error!(Type::Timer, self.refresh_delay_probe_task().await);
+
         logging!(info, Type::Timer, "Timer initialization completed");
         Ok(())
     }
@@ -160,6 +171,113 @@ impl Timer {
         Ok(())
     }
 
+    /// 按设置的间隔注册、更新或移除解锁检测定时复检任务
+    pub async fn refresh_unlock_recheck_task(&self) -> Result<()> {
+        let minutes = Config::verge()
+            .await
+            .latest_ref()
+            .unlock_recheck_interval
+            .unwrap_or(0);
+        self.refresh_repeated_task(
+            &self.unlock_recheck_task,
+            minutes,
+            "unlock recheck",
+            "解锁检测定时复检",
+            || {
+                Box::pin(async {
+                    logging!(info, Type::Timer, "Running unlock recheck task");
+                    crate::cmd::media_unlock_checker::recheck_unlock().await;
+                })
+            },
+        )
+    }
+
+    /// 按自动延迟检测开关与间隔注册、更新或移除后台节点延迟探测任务
+    pub async fn refresh_delay_probe_task(&self) -> Result<()> {
+        let minutes = {
+            let verge = Config::verge().await;
+            let verge = verge.latest_ref();
+            if verge.enable_auto_delay_detection.unwrap_or(false) {
+                verge
+                    .delay_probe_interval
+                    .filter(|minutes| *minutes > 0)
+                    .unwrap_or(feat::DEFAULT_DELAY_PROBE_INTERVAL)
+            } else {
+                0
+            }
+        };
+        self.refresh_repeated_task(
+            &self.delay_probe_task,
+            minutes,
+            "delay probe",
+            "后台节点延迟探测",
+            || {
+                Box::pin(async {
+                    logging!(debug, Type::Timer, "Running delay probe task");
+                    logging_error!(Type::Timer, feat::DelayProber::global().probe_all().await);
+                })
+            },
+        )
+    }
+
+    /// 按间隔注册、更新或移除 `slot` 中的周期任务，间隔为 0 时移除，间隔未变化时保持不变
+    fn refresh_repeated_task(
+        &self,
+        slot: &Mutex<Option<TimerTask>>,
+        minutes: u64,
+        name: &str,
+        label: &str,
+        routine: fn() -> Pin<Box<dyn std::future::Future<Output = ()> + Send>>,
+    ) -> Result<()> {
+        let mut current = slot.lock();
+        if current.as_ref().map_or(0, |task| task.interval_minutes) == minutes {
+            return Ok(());
+        }
+
+        let delay_timer = self.delay_timer.write();
+        if let Some(task) = current.take()
+            && let Err(e) = delay_timer.remove_task(task.task_id)
+        {
+            logging!(
+                warn,
+                Type::Timer,
+                "Failed to remove {} task {}: {}",
+                name,
+                task.task_id,
+                e
+            );
+        }
+        if minutes == 0 {
+            logging!(info, Type::Timer, "{}已关闭", label);
+            return Ok(());
+        }
+
+        let tid = self.timer_count.fetch_add(1, Ordering::SeqCst);
+        let task = TaskBuilder::default()
+            .set_task_id(tid)
+            .set_maximum_parallel_runnable_num(1)
+            .set_frequency_repeated_by_minutes(minutes)
+            .spawn_async_routine(routine)
+            .with_context(|| format!("failed to create {name} timer task"))?;
+        delay_timer
+            .add_task(task)
+            .with_context(|| format!("failed to add {name} timer task"))?;
+        *current = Some(TimerTask {
+            task_id: tid,
+            interval_minutes: minutes,
+            last_run: chrono::Local::now().timestamp(),
+        });
+        logging!(
+            info,
+            Type::Timer,
+            "{}已启用: interval={}min, task_id={}",
+            label,
+            minutes,
+            tid
+        );
+        Ok(())
+    }
+
     /// Refresh timer tasks with better error handling
     pub async fn refresh(&self) -> Result<()> {
         // Generate diff outside of lock to minimize lock contention
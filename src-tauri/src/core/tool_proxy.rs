@@ -0,0 +1,660 @@
+//! 开发工具代理
+//!
+//! 随系统代理开关为 git、npm、pnpm、pip、cargo、docker、apt 写入代理设置。
+//! 首次写入前记录被改动的键在原文件中的值，关闭系统代理或退出时只还原这些键，
+//! 期间用户对其他内容的修改会被保留。
+//!
+//! docker 的 `daemon.json` 只在 dockerd 启动时读取，写入或还原后需要手动执行
+//! `systemctl restart docker`（或重启 Docker Desktop）才会生效。
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    config::IToolProxy,
+    logging, singleton_lazy,
+    utils::{dirs, logging::Type},
+};
+
+/// 未配置时默认写入的工具，docker 与 apt 需要管理员权限，需手动开启
+const DEFAULT_TOOLS: &[Tool] = &[Tool::Git, Tool::Npm, Tool::Pnpm, Tool::Pip, Tool::Cargo];
+const NO_PROXY: &str = "localhost,127.0.0.1,::1";
+const NPM_KEYS: &[&str] = &["proxy", "https-proxy", "noproxy"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Git,
+    Npm,
+    Pnpm,
+    Pip,
+    Cargo,
+    Docker,
+    Apt,
+}
+
+/// 写入的键在原文件中的值，还原时只处理这些键
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Backup {
+    /// 原文件不存在，还原后没有剩余内容时删除文件
+    created: bool,
+    /// 键 -> 原值，原本不存在的键为 None
+    values: BTreeMap<String, Option<String>>,
+}
+
+impl Tool {
+    pub const ALL: &[Self] = &[
+        Self::Git,
+        Self::Npm,
+        Self::Pnpm,
+        Self::Pip,
+        Self::Cargo,
+        Self::Docker,
+        Self::Apt,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Git => "git",
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Pip => "pip",
+            Self::Cargo => "cargo",
+            Self::Docker => "docker",
+            Self::Apt => "apt",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|tool| tool.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// 工具的配置文件，当前平台不支持时返回 None
+    fn path(self) -> Option<PathBuf> {
+        let home = ::dirs::home_dir();
+        match self {
+            Self::Git => home.map(|home| home.join(".gitconfig")),
+            Self::Npm => home.map(|home| home.join(".npmrc")),
+            #[cfg(target_os = "windows")]
+            Self::Pnpm => ::dirs::data_local_dir().map(|dir| dir.join("pnpm/config/rc")),
+            #[cfg(target_os = "macos")]
+            Self::Pnpm => ::dirs::preference_dir().map(|dir| dir.join("pnpm/rc")),
+            #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+            Self::Pnpm => ::dirs::config_dir().map(|dir| dir.join("pnpm/rc")),
+            #[cfg(target_os = "windows")]
+            Self::Pip => ::dirs::config_dir().map(|dir| dir.join("pip/pip.ini")),
+            #[cfg(not(target_os = "windows"))]
+            Self::Pip => ::dirs::config_dir().map(|dir| dir.join("pip/pip.conf")),
+            Self::Cargo => std::env::var_os("CARGO_HOME")
+                .map(PathBuf::from)
+                .or_else(|| home.map(|home| home.join(".cargo")))
+                .map(|dir| dir.join("config.toml")),
+            #[cfg(target_os = "linux")]
+            Self::Docker => Some(PathBuf::from("/etc/docker/daemon.json")),
+            #[cfg(not(target_os = "linux"))]
+            Self::Docker => home.map(|home| home.join(".docker/daemon.json")),
+            #[cfg(target_os = "linux")]
+            Self::Apt => Some(PathBuf::from("/etc/apt/apt.conf.d/95needyclash-proxy")),
+            #[cfg(not(target_os = "linux"))]
+            Self::Apt => None,
+        }
+    }
+
+    /// INI/TOML 风格配置中代理设置所在的分组与缩进
+    const fn ini_section(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::Git => Some(("http", "\t")),
+            Self::Pip => Some(("global", "")),
+            Self::Cargo => Some(("http", "")),
+            _ => None,
+        }
+    }
+
+    /// 写入的键，apt 的配置文件完全由本程序生成，不记录键
+    const fn keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Git | Self::Pip | Self::Cargo => &["proxy"],
+            Self::Npm | Self::Pnpm => NPM_KEYS,
+            Self::Docker => &["proxies"],
+            Self::Apt => &[],
+        }
+    }
+
+    /// 在原内容基础上写入代理设置
+    fn render(self, content: &str, proxy: &str) -> Result<String> {
+        let rendered = match self {
+            Self::Git | Self::Pip => {
+                let (section, indent) = self.ini_section().unwrap_or_default();
+                set_ini_values(content, section, &[("proxy", proxy.into())], indent)
+            }
+            Self::Cargo => {
+                let proxy = proxy.trim_start_matches("http://");
+                set_ini_values(content, "http", &[("proxy", format!("\"{proxy}\""))], "")
+            }
+            Self::Npm | Self::Pnpm => set_flat_values(
+                content,
+                &[
+                    ("proxy", proxy.into()),
+                    ("https-proxy", proxy.into()),
+                    ("noproxy", NO_PROXY.into()),
+                ],
+            ),
+            Self::Docker => set_docker_value(
+                content,
+                "proxies",
+                Some(json!({
+                    "http-proxy": proxy,
+                    "https-proxy": proxy,
+                    "no-proxy": NO_PROXY,
+                })),
+            )?,
+            Self::Apt => {
+                format!("Acquire::http::Proxy \"{proxy}\";\nAcquire::https::Proxy \"{proxy}\";\n")
+            }
+        };
+        Ok(rendered)
+    }
+
+    /// 记录写入前各键的原值
+    fn capture(self, content: Option<&str>) -> Result<Backup> {
+        let Some(content) = content else {
+            return Ok(Backup {
+                created: true,
+                values: self
+                    .keys()
+                    .iter()
+                    .map(|key| ((*key).into(), None))
+                    .collect(),
+            });
+        };
+        let mut values = BTreeMap::new();
+        for key in self.keys() {
+            let value = match self {
+                Self::Docker => get_docker_value(content, key)?,
+                _ => match self.ini_section() {
+                    Some((section, _)) => get_ini_value(content, section, key),
+                    None => get_flat_value(content, key),
+                },
+            };
+            values.insert((*key).into(), value);
+        }
+        Ok(Backup {
+            created: false,
+            values,
+        })
+    }
+
+    /// 将记录的键还原为原值，原本不存在的键被删除
+    fn restore_values(self, content: &str, backup: &Backup) -> Result<String> {
+        let mut content = content.to_string();
+        for (key, value) in &backup.values {
+            content = match (self, self.ini_section()) {
+                (Self::Docker, _) => {
+                    let value = value.as_deref().map(serde_json::from_str).transpose()?;
+                    set_docker_value(&content, key, value)?
+                }
+                (_, Some((section, indent))) => match value {
+                    Some(value) => {
+                        set_ini_values(&content, section, &[(key, value.clone())], indent)
+                    }
+                    None => remove_ini_value(&content, section, key),
+                },
+                _ => match value {
+                    Some(value) => set_flat_values(&content, &[(key, value.clone())]),
+                    None => remove_flat_value(&content, key),
+                },
+            };
+        }
+        if self == Self::Apt {
+            content.clear();
+        }
+        Ok(content)
+    }
+}
+
+fn ini_key(line: &str) -> Option<&str> {
+    line.split_once('=').map(|(key, _)| key.trim())
+}
+
+fn ini_section(line: &str) -> Option<&str> {
+    let line = line.trim();
+    line.strip_prefix('[')
+        .and_then(|line| line.strip_suffix(']'))
+        .map(str::trim)
+}
+
+/// 在 INI/TOML 风格内容的指定分组中设置键值，缺少分组时追加到末尾
+fn set_ini_values(content: &str, section: &str, values: &[(&str, String)], indent: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let render = |key: &str, value: &str| format!("{indent}{key} = {value}");
+
+    let Some(start) = lines
+        .iter()
+        .position(|line| ini_section(line) == Some(section))
+    else {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.push(format!("[{section}]"));
+        lines.extend(values.iter().map(|(key, value)| render(key, value)));
+        return lines.join("\n") + "\n";
+    };
+    let end = lines
+        .iter()
+        .skip(start + 1)
+        .position(|line| ini_section(line).is_some())
+        .map_or(lines.len(), |offset| start + 1 + offset);
+
+    let mut insert_at = start + 1;
+    for (key, value) in values {
+        match lines[start + 1..end]
+            .iter()
+            .position(|line| ini_key(line) == Some(*key))
+        {
+            Some(offset) => lines[start + 1 + offset] = render(key, value),
+            None => {
+                lines.insert(insert_at, render(key, value));
+                insert_at += 1;
+            }
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// 读取 INI/TOML 风格内容中指定分组的键值
+fn get_ini_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        if let Some(name) = ini_section(line) {
+            in_section = name == section;
+        } else if in_section && ini_key(line) == Some(key) {
+            return line
+                .split_once('=')
+                .map(|(_, value)| value.trim().to_string());
+        }
+    }
+    None
+}
+
+/// 删除指定分组中的键，分组因此变空时一并删除分组
+fn remove_ini_value(content: &str, section: &str, key: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let Some(start) = lines
+        .iter()
+        .position(|line| ini_section(line) == Some(section))
+    else {
+        return content.to_string();
+    };
+    let end = lines
+        .iter()
+        .skip(start + 1)
+        .position(|line| ini_section(line).is_some())
+        .map_or(lines.len(), |offset| start + 1 + offset);
+    let Some(offset) = lines[start + 1..end]
+        .iter()
+        .position(|line| ini_key(line) == Some(key))
+    else {
+        return content.to_string();
+    };
+    lines.remove(start + 1 + offset);
+
+    if lines[start + 1..end - 1]
+        .iter()
+        .all(|line| line.trim().is_empty())
+    {
+        lines.drain(start..end - 1);
+        // 去掉追加分组时插入的空行
+        if start > 0
+            && lines
+                .get(start - 1)
+                .is_some_and(|line| line.trim().is_empty())
+        {
+            lines.remove(start - 1);
+        }
+    }
+    join_lines(&lines)
+}
+
+fn join_lines(lines: &[String]) -> String {
+    if lines.is_empty() {
+        String::new()
+    } else {
+        lines.join("\n") + "\n"
+    }
+}
+
+fn get_flat_value(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (name, value) = line.split_once('=')?;
+        (name.trim() == key).then(|| value.trim().to_string())
+    })
+}
+
+/// 设置 `.npmrc` 这类无分组的 `key=value` 内容
+fn set_flat_values(content: &str, values: &[(&str, String)]) -> String {
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    for (key, value) in values {
+        let line = format!("{key}={value}");
+        match lines.iter().position(|item| ini_key(item) == Some(*key)) {
+            Some(index) => lines[index] = line,
+            None => lines.push(line),
+        }
+    }
+    join_lines(&lines)
+}
+
+fn remove_flat_value(content: &str, key: &str) -> String {
+    let lines: Vec<String> = content
+        .lines()
+        .filter(|line| ini_key(line) != Some(key))
+        .map(String::from)
+        .collect();
+    join_lines(&lines)
+}
+
+fn parse_docker(content: &str) -> Result<serde_json::Map<String, serde_json::Value>> {
+    if content.trim().is_empty() {
+        return Ok(serde_json::Map::new());
+    }
+    match serde_json::from_str(content).context("invalid docker daemon.json")? {
+        serde_json::Value::Object(object) => Ok(object),
+        _ => Err(anyhow!("docker daemon.json is not an object")),
+    }
+}
+
+/// 原值以 JSON 文本记录
+fn get_docker_value(content: &str, key: &str) -> Result<Option<String>> {
+    parse_docker(content)?
+        .get(key)
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(Into::into)
+}
+
+/// 设置或删除 docker daemon 配置的顶层键，原文件无法解析时拒绝写入
+fn set_docker_value(content: &str, key: &str, value: Option<serde_json::Value>) -> Result<String> {
+    let mut object = parse_docker(content)?;
+    match value {
+        Some(value) => object.insert(key.into(), value),
+        None => object.remove(key),
+    };
+    if object.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(serde_json::to_string_pretty(&object)? + "\n")
+}
+
+/// 写入目标文件，Linux 下系统文件无权限且允许提权时提权写入
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn write_target(path: &Path, content: &str, elevate: bool) -> Result<()> {
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(path, content));
+    match result {
+        #[cfg(target_os = "linux")]
+        Err(e) if elevate && e.kind() == ErrorKind::PermissionDenied => {
+            crate::utils::help::write_file_elevated(path, content)
+        }
+        result => result.map_err(Into::into),
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn remove_target(path: &Path, elevate: bool) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        #[cfg(target_os = "linux")]
+        Err(e) if elevate && e.kind() == ErrorKind::PermissionDenied => {
+            let status = std::process::Command::new(crate::utils::help::linux_elevator())
+                .args(["rm", "-f"])
+                .arg(path)
+                .status()?;
+            if !status.success() {
+                return Err(anyhow!("failed to remove {}", path.display()));
+            }
+            Ok(())
+        }
+        result => result.map_err(Into::into),
+    }
+}
+
+/// 开发工具代理管理，备份保存在应用目录下，重启后仍可还原
+#[derive(Default)]
+pub struct ToolProxy {
+    lock: Mutex<()>,
+}
+
+singleton_lazy!(ToolProxy, TOOL_PROXY, ToolProxy::default);
+
+impl ToolProxy {
+    fn backup_dir() -> Result<PathBuf> {
+        Ok(dirs::app_home_dir()?.join("tool_proxy"))
+    }
+
+    fn backup_file(tool: Tool) -> Result<PathBuf> {
+        Ok(Self::backup_dir()?.join(format!("{}.json", tool.name())))
+    }
+
+    fn is_applied(tool: Tool) -> bool {
+        Self::backup_file(tool).is_ok_and(|path| path.exists())
+    }
+
+    /// 已写入代理设置的工具
+    pub fn applied(&self) -> Vec<&'static str> {
+        Tool::ALL
+            .iter()
+            .filter(|tool| Self::is_applied(**tool))
+            .map(|tool| tool.name())
+            .collect()
+    }
+
+    fn read_target(path: &Path) -> Result<Option<String>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn apply(tool: Tool, proxy: &str) -> Result<()> {
+        let path = tool
+            .path()
+            .ok_or_else(|| anyhow!("{} is not supported on this platform", tool.name()))?;
+        let original = Self::read_target(&path)?;
+
+        // 只在首次写入时记录原值，避免把自己写入的内容当作原值
+        let first_apply = !Self::is_applied(tool);
+        if first_apply {
+            let backup = tool.capture(original.as_deref())?;
+            fs::create_dir_all(Self::backup_dir()?)?;
+            fs::write(Self::backup_file(tool)?, serde_json::to_string(&backup)?)?;
+        }
+
+        let content = original.unwrap_or_default();
+        let result = tool.render(&content, proxy).and_then(|rendered| {
+            if rendered == content {
+                return Ok(());
+            }
+            write_target(&path, &rendered, true)?;
+            if tool == Tool::Docker {
+                logging!(
+                    info,
+                    Type::System,
+                    "已写入 docker 代理设置，需重启 dockerd 后生效"
+                );
+            }
+            Ok(())
+        });
+        if result.is_err() && first_apply {
+            Self::discard_backup(tool);
+        }
+        result
+    }
+
+    fn discard_backup(tool: Tool) {
+        if let Ok(path) = Self::backup_file(tool) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn restore(tool: Tool, elevate: bool) -> Result<()> {
+        let Some(path) = tool.path() else {
+            Self::discard_backup(tool);
+            return Ok(());
+        };
+        let backup: Backup = serde_json::from_str(&fs::read_to_string(Self::backup_file(tool)?)?)
+            .with_context(|| format!("invalid {} proxy backup", tool.name()))?;
+
+        if let Some(content) = Self::read_target(&path)? {
+            let restored = tool.restore_values(&content, &backup)?;
+            if backup.created && restored.trim().is_empty() {
+                remove_target(&path, elevate)?;
+            } else if restored != content {
+                write_target(&path, &restored, elevate)?;
+            }
+        }
+        Self::discard_backup(tool);
+        Ok(())
+    }
+
+    /// 按设置同步，`proxy` 为 None 时还原全部工具
+    ///
+    /// `elevate` 为 false 时不弹出提权窗口，需要提权的工具保留记录，下次同步时再还原
+    pub fn sync(&self, settings: &IToolProxy, proxy: Option<&str>, elevate: bool) {
+        let _guard = self.lock.lock();
+        let selected: Vec<Tool> = match &settings.tools {
+            _ if !settings.enabled.unwrap_or(false) || proxy.is_none() => Vec::new(),
+            Some(tools) => tools
+                .iter()
+                .filter_map(|name| Tool::from_name(name))
+                .collect(),
+            None => DEFAULT_TOOLS.to_vec(),
+        };
+
+        for tool in Tool::ALL {
+            let apply = selected.contains(tool);
+            let result = match proxy {
+                Some(proxy) if apply => Self::apply(*tool, proxy),
+                _ if Self::is_applied(*tool) => Self::restore(*tool, elevate),
+                _ => continue,
+            };
+            if let Err(e) = result {
+                logging!(
+                    warn,
+                    Type::System,
+                    "{} 代理设置同步失败: {}",
+                    tool.name(),
+                    e
+                );
+            } else if !apply {
+                logging!(info, Type::System, "已还原 {} 代理设置", tool.name());
+            }
+        }
+    }
+
+    /// 退出时还原所有已写入的工具，不提权以免提权窗口阻塞退出
+    pub fn restore_all(&self) {
+        self.sync(&IToolProxy::default(), None, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXY: &str = "http://127.0.0.1:7897";
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_set_ini_values() {
+        let gitconfig =
+            "[user]\n\tname = dev\n[http]\n\tsslVerify = true\n\tproxy = http://old:1\n";
+        let rendered = Tool::Git.render(gitconfig, PROXY).unwrap();
+        assert_eq!(
+            rendered,
+            "[user]\n\tname = dev\n[http]\n\tsslVerify = true\n\tproxy = http://127.0.0.1:7897\n"
+        );
+
+        let cargo = Tool::Cargo.render("", PROXY).unwrap();
+        assert_eq!(cargo, "[http]\nproxy = \"127.0.0.1:7897\"\n");
+
+        let pip = Tool::Pip
+            .render("[global]\ntimeout = 60\n[install]\nuser = true\n", PROXY)
+            .unwrap();
+        assert_eq!(
+            pip,
+            "[global]\nproxy = http://127.0.0.1:7897\ntimeout = 60\n[install]\nuser = true\n"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_set_flat_values() {
+        let rendered = Tool::Npm
+            .render(
+                "registry=https://registry.npmjs.org/\nproxy=http://old:1\n",
+                PROXY,
+            )
+            .unwrap();
+        assert_eq!(
+            rendered,
+            "registry=https://registry.npmjs.org/\nproxy=http://127.0.0.1:7897\nhttps-proxy=http://127.0.0.1:7897\nnoproxy=localhost,127.0.0.1,::1\n"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_restore_values() {
+        // 写入后用户新增的设置在还原时保留
+        let original = "[user]\n\tname = dev\n";
+        let backup = Tool::Git.capture(Some(original)).unwrap();
+        let rendered = Tool::Git.render(original, PROXY).unwrap();
+        let edited = format!("{rendered}[core]\n\teditor = vim\n");
+        assert_eq!(
+            Tool::Git.restore_values(&edited, &backup).unwrap(),
+            "[user]\n\tname = dev\n[core]\n\teditor = vim\n"
+        );
+
+        let npmrc = "registry=https://registry.npmjs.org/\nproxy=http://old:1\n";
+        let backup = Tool::Npm.capture(Some(npmrc)).unwrap();
+        let rendered = Tool::Npm.render(npmrc, PROXY).unwrap();
+        assert_eq!(Tool::Npm.restore_values(&rendered, &backup).unwrap(), npmrc);
+
+        let backup = Tool::Cargo.capture(None).unwrap();
+        assert!(backup.created);
+        let rendered = Tool::Cargo.render("", PROXY).unwrap();
+        assert_eq!(Tool::Cargo.restore_values(&rendered, &backup).unwrap(), "");
+
+        let daemon = "{\"log-level\":\"warn\"}";
+        let backup = Tool::Docker.capture(Some(daemon)).unwrap();
+        let rendered = Tool::Docker.render(daemon, PROXY).unwrap();
+        let restored = Tool::Docker.restore_values(&rendered, &backup).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&restored).unwrap();
+        assert_eq!(value, json!({"log-level": "warn"}));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_render_docker() {
+        let rendered = Tool::Docker
+            .render("{\"log-level\": \"warn\"}", PROXY)
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(value["log-level"], "warn");
+        assert_eq!(value["proxies"]["https-proxy"], PROXY);
+        assert!(Tool::Docker.render("not json", PROXY).is_err());
+        assert!(Tool::Docker.render("[]", PROXY).is_err());
+    }
+}
@@ -1,8 +1,8 @@
 use once_cell::sync::OnceCell;
 use tauri::Emitter;
 use tauri::tray::TrayIconBuilder;
-#[cfg(target_os = "macos")]
 pub mod speed_rate;
+mod subscription;
 use crate::module::lightweight;
 use crate::process::AsyncHandler;
 use crate::utils::window_manager::WindowManager;
@@ -12,10 +12,15 @@ use crate::{
     feat, logging,
     module::lightweight::is_in_lightweight_mode,
     singleton_lazy,
-    utils::{dirs::find_target_icons, i18n::t},
+    utils::{
+        dirs::find_target_icons,
+        i18n::t,
+        notification::{NotificationEvent, notify_event},
+    },
 };
 
-use super::handle;
+use self::subscription::SubscriptionSummary;
+use super::{handle, hotkey::Hotkey};
 use anyhow::Result;
 use futures::future::join_all;
 use parking_lot::Mutex;
@@ -59,16 +64,21 @@ fn should_handle_tray_click() -> bool {
     }
 }
 
+/// 代理组名 -> (节点名, 菜单项)，用于原地更新延迟显示
+type ProxyMenuItems = HashMap<String, Vec<(String, CheckMenuItem<Wry>)>>;
+
 #[cfg(target_os = "macos")]
 pub struct Tray {
     last_menu_update: Mutex<Option<Instant>>,
     menu_updating: AtomicBool,
+    proxy_items: Mutex<ProxyMenuItems>,
 }
 
 #[cfg(not(target_os = "macos"))]
 pub struct Tray {
     last_menu_update: Mutex<Option<Instant>>,
     menu_updating: AtomicBool,
+    proxy_items: Mutex<ProxyMenuItems>,
 }
 
 impl TrayState {
@@ -179,6 +189,7 @@ impl Default for Tray {
         Tray {
             last_menu_update: Mutex::new(None),
             menu_updating: AtomicBool::new(false),
+            proxy_items: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -193,7 +204,9 @@ impl Tray {
             return Ok(());
         }
 
-        let app_handle = handle::Handle::app_handle();
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return Ok(());
+        };
 
         match self.create_tray_from_handle(app_handle).await {
             Ok(_) => {
@@ -216,7 +229,9 @@ impl Tray {
             return Ok(());
         }
 
-        let app_handle = handle::Handle::app_handle();
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return Ok(());
+        };
         let tray_event = { Config::verge().await.latest_ref().tray_event.clone() };
         let tray_event: String = tray_event.unwrap_or("main_window".into());
         let tray = app_handle
@@ -260,7 +275,9 @@ impl Tray {
             return Ok(());
         }
 
-        let app_handle = handle::Handle::app_handle();
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return Ok(());
+        };
 
         // 设置更新状态
         self.menu_updating.store(true, Ordering::Release);
@@ -328,7 +345,9 @@ impl Tray {
             return Ok(());
         }
 
-        let app_handle = handle::Handle::app_handle();
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return Ok(());
+        };
 
         let tray = match app_handle.tray_by_id("main") {
             Some(tray) => tray,
@@ -349,6 +368,13 @@ impl Tray {
             (false, false) => TrayState::get_common_tray_icon().await,
         };
 
+        // 开启托盘网速时使用拼接速率后的图标
+        if let Some(icon) = speed_rate::SpeedRate::global().decorate_icon(&icon_bytes) {
+            let _ = tray.set_icon(Some(icon));
+            let _ = tray.set_icon_as_template(true);
+            return Ok(());
+        }
+
         let colorful = verge.tray_icon.clone().unwrap_or("monochrome".into());
         let is_colorful = colorful == "colorful";
 
@@ -364,7 +390,9 @@ impl Tray {
             return Ok(());
         }
 
-        let app_handle = handle::Handle::app_handle();
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return Ok(());
+        };
 
         let tray = match app_handle.tray_by_id("main") {
             Some(tray) => tray,
@@ -396,7 +424,9 @@ impl Tray {
             return Ok(());
         }
 
-        let app_handle = handle::Handle::app_handle();
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return Ok(());
+        };
         let _tray = app_handle
             .tray_by_id("main")
             .ok_or_else(|| anyhow::anyhow!("Failed to get main tray"))?;
@@ -414,7 +444,9 @@ impl Tray {
             return Ok(());
         }
 
-        let app_handle = handle::Handle::app_handle();
+        let Some(app_handle) = handle::Handle::try_app_handle() else {
+            return Ok(());
+        };
 
         let verge = Config::verge().await.latest_ref().clone();
         let system_proxy = verge.enable_system_proxy.as_ref().unwrap_or(&false);
@@ -451,7 +483,7 @@ impl Tray {
             format!("{main}+{}", rest.split('.').next().unwrap_or(""))
         });
 
-        let tooltip = format!(
+        let mut tooltip = format!(
             "NeedyClash {}\n{}: {}\n{}: {}\n{}: {}",
             reassembled_version,
             sys_proxy_text,
@@ -461,6 +493,18 @@ impl Tray {
             profile_text,
             current_profile_name
         );
+        if let Some(summary) = SubscriptionSummary::current().await {
+            for (label, value) in [
+                ("Used / Total", summary.usage),
+                ("Expire Time", summary.expire),
+                ("Update Time", summary.updated),
+            ] {
+                if let Some(value) = value {
+                    tooltip.push_str(&format!("\n{}: {}", t(label).await, value));
+                }
+            }
+        }
+        let tooltip = speed_rate::SpeedRate::global().decorate_tooltip(tooltip);
 
         if let Some(tray) = app_handle.tray_by_id("main") {
             let _ = tray.set_tooltip(Some(&tooltip));
@@ -481,6 +525,8 @@ impl Tray {
         self.update_tray_display().await?;
         self.update_icon().await?;
         self.update_tooltip().await?;
+        // 轻量模式切换时暂停或恢复托盘网速
+        speed_rate::SpeedRate::global().sync().await;
         Ok(())
     }
 
@@ -565,6 +611,37 @@ impl Tray {
         Ok(())
     }
 
+    /// 延迟测试完成后原地更新代理组节点的延迟显示，无需重建菜单
+    pub async fn update_proxy_delays(&self, group_name: &str) -> Result<()> {
+        let items = self
+            .proxy_items
+            .lock()
+            .get(group_name)
+            .cloned()
+            .unwrap_or_default();
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let proxy_nodes_data = handle::Handle::mihomo().await.get_proxies().await?;
+        let timeout_text = t("timeout").await;
+        let scores = stability_scores().await;
+        for (proxy_name, item) in items {
+            let delay = proxy_nodes_data
+                .proxies
+                .get(&proxy_name)
+                .and_then(|h| h.history.last())
+                .map(|h| h.delay);
+            let _ = item.set_text(proxy_item_text(
+                &proxy_name,
+                delay,
+                scores.get(&proxy_name).copied(),
+                &timeout_text,
+            ));
+        }
+        Ok(())
+    }
+
     // 托盘状态更新函数
     pub async fn update_all_states(&self) -> Result<()> {
         if handle::Handle::global().is_exiting() {
@@ -582,6 +659,35 @@ impl Tray {
     }
 }
 
+/// 后台延迟探测得到的各节点稳定性评分
+async fn stability_scores() -> HashMap<String, u8> {
+    feat::DelayProber::global()
+        .stabilities()
+        .await
+        .into_iter()
+        .map(|stability| (stability.node, stability.score))
+        .collect()
+}
+
+/// 代理节点菜单项的文字，有后台探测数据时附带稳定性评分
+fn proxy_item_text(
+    proxy_name: &str,
+    delay: Option<u32>,
+    score: Option<u8>,
+    timeout_text: &str,
+) -> String {
+    let delay_text = match delay {
+        None => "-ms".into(),
+        Some(0) => timeout_text.into(),
+        Some(delay) if delay >= 10000 => timeout_text.into(),
+        Some(delay) => format!("{delay}ms"),
+    };
+    match score {
+        Some(score) => format!("{proxy_name}   | {delay_text}  ·  {score}"),
+        None => format!("{proxy_name}   | {delay_text}"),
+    }
+}
+
 async fn create_tray_menu(
     app_handle: &AppHandle,
     mode: Option<&str>,
@@ -650,11 +756,7 @@ async fn create_tray_menu(
         .map(|h| {
             h.iter()
                 .filter_map(|item| {
-                    let mut parts = item.split(',');
-                    match (parts.next(), parts.next()) {
-                        (Some(func), Some(key)) => Some((func.into(), key.into())),
-                        _ => None,
-                    }
+                    Hotkey::split_binding(item).map(|(func, key)| (func.into(), key.into()))
                 })
                 .collect::<std::collections::HashMap<String, String>>()
         })
@@ -667,6 +769,9 @@ async fn create_tray_menu(
                 let app_handle = app_handle.clone();
                 let profile_uid = profile_uid.clone();
                 let profile_name = profile_name.clone();
+                let accelerator = hotkeys
+                    .get(&format!("switch_profile:{profile_uid}"))
+                    .cloned();
                 async move {
                     let is_current_profile = Config::profiles()
                         .await
@@ -678,7 +783,7 @@ async fn create_tray_menu(
                         t(&profile_name).await,
                         true,
                         is_current_profile,
-                        None::<&str>,
+                        accelerator.as_deref(),
                     )
                 }
             });
@@ -687,6 +792,10 @@ async fn create_tray_menu(
     };
 
     // 代理组子菜单
+    let delay_check_text = t("Delay check").await;
+    let timeout_text = t("timeout").await;
+    let scores = stability_scores().await;
+    let mut proxy_items = ProxyMenuItems::new();
     let proxy_submenus: Vec<Submenu<Wry>> = {
         let mut submenus: Vec<(String, usize, Submenu<Wry>)> = Vec::new();
 
@@ -712,25 +821,24 @@ async fn create_tray_menu(
                 let now_proxy = group_data.now.as_deref().unwrap_or_default();
 
                 // Create proxy items
-                let group_items: Vec<CheckMenuItem<Wry>> = all_proxies
+                let group_items: Vec<(String, CheckMenuItem<Wry>)> = all_proxies
                     .iter()
                     .filter_map(|proxy_str| {
                         let is_selected = *proxy_str == now_proxy;
                         let item_id = format!("proxy_{}_{}", group_name, proxy_str);
 
                         // Get delay for display
-                        let delay_text = proxy_nodes_data
+                        let delay = proxy_nodes_data
                             .proxies
                             .get(proxy_str)
                             .and_then(|h| h.history.last())
-                            .map(|h| match h.delay {
-                                0 => "-ms".into(),
-                                delay if delay >= 10000 => "-ms".into(),
-                                _ => format!("{}ms", h.delay),
-                            })
-                            .unwrap_or_else(|| "-ms".into());
-
-                        let display_text = format!("{}   | {}", proxy_str, delay_text);
+                            .map(|h| h.delay);
+                        let display_text = proxy_item_text(
+                            proxy_str,
+                            delay,
+                            scores.get(proxy_str).copied(),
+                            &timeout_text,
+                        );
 
                         CheckMenuItem::with_id(
                             app_handle,
@@ -742,6 +850,7 @@ async fn create_tray_menu(
                         )
                         .map_err(|e| log::warn!(target: "app", "创建代理菜单项失败: {}", e))
                         .ok()
+                        .map(|item| (proxy_str.clone(), item))
                     })
                     .collect();
 
@@ -767,10 +876,25 @@ async fn create_tray_menu(
                     group_name.to_string()
                 };
 
-                let group_items_refs: Vec<&dyn IsMenuItem<Wry>> = group_items
-                    .iter()
-                    .map(|item| item as &dyn IsMenuItem<Wry>)
-                    .collect();
+                // 组内测速入口，结果通过 update_proxy_delays 原地刷新
+                let test_delay_item = MenuItem::with_id(
+                    app_handle,
+                    format!("test_delay_{}", group_name),
+                    &delay_check_text,
+                    true,
+                    None::<&str>,
+                )?;
+                let group_separator = PredefinedMenuItem::separator(app_handle)?;
+
+                let group_items_refs: Vec<&dyn IsMenuItem<Wry>> =
+                    [&test_delay_item as &dyn IsMenuItem<Wry>, &group_separator]
+                        .into_iter()
+                        .chain(
+                            group_items
+                                .iter()
+                                .map(|(_, item)| item as &dyn IsMenuItem<Wry>),
+                        )
+                        .collect();
 
                 if let Ok(submenu) = Submenu::with_id_and_items(
                     app_handle,
@@ -781,6 +905,7 @@ async fn create_tray_menu(
                 ) {
                     let insertion_index = submenus.len();
                     submenus.push((group_name.to_string(), insertion_index, submenu));
+                    proxy_items.insert(group_name.to_string(), group_items);
                 } else {
                     log::warn!(target: "app", "创建代理组子菜单失败: {}", group_name);
                 }
@@ -806,6 +931,7 @@ async fn create_tray_menu(
             .map(|(_, _, submenu)| submenu)
             .collect()
     };
+    *Tray::global().proxy_items.lock() = proxy_items;
 
     // Pre-fetch all localized strings
     let dashboard_text = t("Dashboard").await;
@@ -827,6 +953,14 @@ async fn create_tray_menu(
     let verge_version_text = t("Verge Version").await;
     let more_text = t("More").await;
     let exit_text = t("Exit").await;
+    let subscription_text = t("Subscriptions").await;
+    let update_profile_text = t("Update").await;
+    let update_all_profiles_text = t("Update All Profiles").await;
+    let subscription_labels = [
+        t("Used / Total").await,
+        t("Expire Time").await,
+        t("Update Time").await,
+    ];
 
     // Convert to references only when needed
     let profile_menu_items_refs: Vec<&dyn IsMenuItem<Wry>> = profile_menu_items
@@ -877,6 +1011,61 @@ async fn create_tray_menu(
         &profile_menu_items_refs,
     )?;
 
+    // 当前订阅的用量与到期信息，信息项仅用于展示
+    let subscription_submenu = match SubscriptionSummary::current().await {
+        Some(summary) => {
+            let mut info_items = Vec::new();
+            for (label, value) in
+                subscription_labels
+                    .iter()
+                    .zip([summary.usage, summary.expire, summary.updated])
+            {
+                if let Some(value) = value {
+                    info_items.push(MenuItem::new(
+                        app_handle,
+                        format!("{label}: {value}"),
+                        false,
+                        None::<&str>,
+                    )?);
+                }
+            }
+            // 菜单项携带构建时的订阅 uid，避免点击前切换订阅后更新到别的订阅
+            let update_profile = MenuItem::with_id(
+                app_handle,
+                format!("update_profile_{}", summary.uid),
+                update_profile_text,
+                summary.is_remote,
+                None::<&str>,
+            )?;
+            let update_all_profiles = MenuItem::with_id(
+                app_handle,
+                "update_all_profiles",
+                update_all_profiles_text,
+                true,
+                None::<&str>,
+            )?;
+            let info_separator = PredefinedMenuItem::separator(app_handle)?;
+
+            let mut items: Vec<&dyn IsMenuItem<Wry>> = info_items
+                .iter()
+                .map(|item| item as &dyn IsMenuItem<Wry>)
+                .collect();
+            if !items.is_empty() {
+                items.push(&info_separator);
+            }
+            items.push(&update_profile);
+            items.push(&update_all_profiles);
+            Some(Submenu::with_id_and_items(
+                app_handle,
+                "subscription",
+                subscription_text,
+                true,
+                &items,
+            )?)
+        }
+        None => None,
+    };
+
     // 创建代理主菜单
     let (proxies_submenu, inline_proxy_items): (Option<Submenu<Wry>>, Vec<&dyn IsMenuItem<Wry>>) =
         if show_proxy_groups_inline {
@@ -1014,6 +1203,9 @@ async fn create_tray_menu(
         separator,
         profiles,
     ];
+    if let Some(ref subscription_menu) = subscription_submenu {
+        menu_items.push(subscription_menu);
+    }
 
     // 如果有代理节点，添加代理节点菜单
     if show_proxy_groups_inline {
@@ -1043,6 +1235,48 @@ async fn create_tray_menu(
     Ok(menu)
 }
 
+/// 从托盘更新订阅，逐个记录失败的订阅并通知用户
+async fn update_profiles_from_tray(uid: Option<String>) {
+    let failures = match feat::update_profiles(uid.clone()).await {
+        Ok(results) => results
+            .into_iter()
+            .filter_map(|result| Some((result.uid, result.error?)))
+            .collect::<Vec<_>>(),
+        Err(e) => vec![(uid.unwrap_or_default(), e.to_string())],
+    };
+    let Some((_, error)) = failures.first() else {
+        return;
+    };
+
+    let names = {
+        let profiles = Config::profiles().await;
+        let profiles = profiles.latest_ref();
+        failures
+            .iter()
+            .map(|(uid, error)| {
+                let name = profiles
+                    .get_item(uid)
+                    .ok()
+                    .and_then(|item| item.name.clone())
+                    .unwrap_or_else(|| uid.clone());
+                log::warn!(target: "app", "托盘更新订阅 {name} 失败: {error}");
+                name
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if let Some(app_handle) = handle::Handle::try_app_handle() {
+        notify_event(
+            app_handle.clone(),
+            NotificationEvent::ProfileUpdateFailed {
+                profiles: &names,
+                error,
+            },
+        )
+        .await;
+    }
+}
+
 fn on_menu_event(_: &AppHandle, event: MenuEvent) {
     AsyncHandler::spawn(|| async move {
         match event.id.as_ref() {
@@ -1096,6 +1330,26 @@ fn on_menu_event(_: &AppHandle, event: MenuEvent) {
                 let profile_index = &id["profiles_".len()..];
                 feat::toggle_proxy_profile(profile_index.into()).await;
             }
+            id if id.starts_with("update_profile_") => {
+                let uid = &id["update_profile_".len()..];
+                update_profiles_from_tray(Some(uid.into())).await;
+            }
+            "update_all_profiles" => {
+                update_profiles_from_tray(None).await;
+            }
+            id if id.starts_with("test_delay_") => {
+                let group_name = &id["test_delay_".len()..];
+                match feat::test_group_delay(group_name).await {
+                    Ok(()) => {
+                        if let Err(e) = Tray::global().update_proxy_delays(group_name).await {
+                            log::warn!(target: "app", "更新托盘延迟显示失败: {e}");
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!(target: "app", "托盘测速失败: {} {:?}", group_name, e);
+                    }
+                }
+            }
             id if id.starts_with("proxy_") => {
                 // proxy_{group_name}_{proxy_name}
                 let parts: Vec<&str> = id.splitn(3, '_').collect();
@@ -1 +1,408 @@
+//! 托盘网速显示
+//!
+//! 订阅 mihomo 的 `/traffic` 流并对速率做平滑，按固定间隔刷新托盘：
+//! macOS 将速率绘制到托盘图标右侧，其他平台写入提示（Linux 同时写入标题）。
+//! 轻量模式下暂停订阅。
 
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use tauri::async_runtime::JoinHandle;
+#[cfg(target_os = "macos")]
+use tauri::image::Image;
+use tauri_plugin_mihomo::models::WebSocketMessage;
+use tokio::sync::mpsc;
+
+use crate::{
+    config::Config, core::handle, logging, module::lightweight::is_in_lightweight_mode,
+    process::AsyncHandler, singleton_lazy, utils::logging::Type,
+};
+
+/// 托盘刷新间隔
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+/// 断线重连间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+/// 指数平滑系数，越大越贴近瞬时速率
+const SMOOTHING: f64 = 0.4;
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+struct Traffic {
+    up: u64,
+    down: u64,
+}
+
+/// 平滑后的速率，单位字节每秒
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub up: f64,
+    pub down: f64,
+}
+
+/// 指数移动平均，首个样本直接采用
+#[derive(Debug, Default)]
+struct SpeedSmoother {
+    rate: Option<Rate>,
+}
+
+impl SpeedSmoother {
+    fn push(&mut self, up: u64, down: u64) -> Rate {
+        let sample = Rate {
+            up: up as f64,
+            down: down as f64,
+        };
+        let rate = match self.rate {
+            Some(prev) => Rate {
+                up: prev.up + SMOOTHING * (sample.up - prev.up),
+                down: prev.down + SMOOTHING * (sample.down - prev.down),
+            },
+            None => sample,
+        };
+        self.rate = Some(rate);
+        rate
+    }
+
+    fn reset(&mut self) {
+        self.rate = None;
+    }
+}
+
+/// 格式化为紧凑的速率文本，如 `512B/s`、`1.5K/s`、`23M/s`
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "K", "M", "G"];
+    let mut value = bytes_per_sec.max(0.0);
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit > 0 && value < 10.0 {
+        format!("{value:.1}{}/s", UNITS[unit])
+    } else {
+        format!("{value:.0}{}/s", UNITS[unit])
+    }
+}
+
+fn speed_line(rate: Rate) -> String {
+    format!("↑{} ↓{}", format_rate(rate.up), format_rate(rate.down))
+}
+
+/// 5x7 点阵字形，每行低 5 位从左到右
+#[cfg(any(target_os = "macos", test))]
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '/' => [0x01, 0x01, 0x02, 0x04, 0x08, 0x10, 0x10],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        's' => [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E],
+        '↑' => [0x04, 0x0E, 0x15, 0x04, 0x04, 0x04, 0x04],
+        '↓' => [0x04, 0x04, 0x04, 0x04, 0x15, 0x0E, 0x04],
+        _ => [0; 7],
+    }
+}
+
+/// 每行最多绘制的字符数（箭头 + `1023K/s`），固定宽度避免图标抖动
+#[cfg(any(target_os = "macos", test))]
+const TEXT_CHARS: u32 = 8;
+
+#[cfg(any(target_os = "macos", test))]
+fn draw_text(rgba: &mut [u8], width: u32, x: u32, y: u32, scale: u32, text: &str) {
+    for (index, c) in text.chars().take(TEXT_CHARS as usize).enumerate() {
+        let origin_x = x + index as u32 * 6 * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..5u32 {
+                if bits & (0x10 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let px = origin_x + col * scale + dx;
+                        let py = y + row as u32 * scale + dy;
+                        let offset = ((py * width + px) * 4) as usize;
+                        if let Some(pixel) = rgba.get_mut(offset..offset + 4) {
+                            pixel.copy_from_slice(&[0, 0, 0, 255]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 在图标右侧拼接上下两行速率，返回 RGBA 数据与尺寸
+#[cfg(any(target_os = "macos", test))]
+fn compose_rgba(icon: &[u8], width: u32, height: u32, rate: Rate) -> (Vec<u8>, u32, u32) {
+    let line_height = height / 2;
+    let scale = (line_height / 8).max(1);
+    let gap = scale * 2;
+    let total_width = width + gap + TEXT_CHARS * 6 * scale;
+
+    let mut rgba = vec![0u8; (total_width * height * 4) as usize];
+    for (row, line) in icon.chunks_exact((width * 4) as usize).enumerate() {
+        let offset = row * (total_width * 4) as usize;
+        if let Some(target) = rgba.get_mut(offset..offset + line.len()) {
+            target.copy_from_slice(line);
+        }
+    }
+
+    let padding = line_height.saturating_sub(7 * scale) / 2;
+    let x = width + gap;
+    let up = format!("↑{}", format_rate(rate.up));
+    let down = format!("↓{}", format_rate(rate.down));
+    draw_text(&mut rgba, total_width, x, padding, scale, &up);
+    draw_text(
+        &mut rgba,
+        total_width,
+        x,
+        line_height + padding,
+        scale,
+        &down,
+    );
+    (rgba, total_width, height)
+}
+
+/// 托盘网速订阅与渲染
+#[derive(Default)]
+pub struct SpeedRate {
+    worker: Mutex<Option<JoinHandle<()>>>,
+    connection: Mutex<Option<u32>>,
+    smoother: Mutex<SpeedSmoother>,
+    last_render: Mutex<Option<Instant>>,
+    rate: Mutex<Option<Rate>>,
+    /// 当前状态对应的原始图标，由 `Tray::update_icon` 写入
+    #[cfg(target_os = "macos")]
+    base_icon: Mutex<Option<Vec<u8>>>,
+    /// 不含速率的托盘提示，由 `Tray::update_tooltip` 写入
+    base_tooltip: Mutex<String>,
+}
+
+singleton_lazy!(SpeedRate, SPEED_RATE, SpeedRate::default);
+
+impl SpeedRate {
+    pub fn is_running(&self) -> bool {
+        self.worker.lock().is_some()
+    }
+
+    /// 按设置与轻量模式状态启停订阅
+    pub async fn sync(&self) {
+        // 无界面守护模式没有托盘
+        if handle::Handle::try_app_handle().is_none() {
+            return;
+        }
+        let enabled = Config::verge()
+            .await
+            .latest_ref()
+            .enable_tray_speed
+            .unwrap_or(false);
+        if enabled && !is_in_lightweight_mode() {
+            self.start();
+        } else {
+            self.stop().await;
+        }
+    }
+
+    fn start(&self) {
+        let mut worker = self.worker.lock();
+        if worker.is_some() {
+            return;
+        }
+        logging!(info, Type::Tray, "启动托盘网速订阅");
+        *worker = Some(AsyncHandler::spawn(|| async {
+            loop {
+                if let Err(e) = Self::global().subscribe().await {
+                    logging!(warn, Type::Tray, "托盘网速订阅中断: {}", e);
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }));
+    }
+
+    async fn stop(&self) {
+        let Some(worker) = self.worker.lock().take() else {
+            return;
+        };
+        logging!(info, Type::Tray, "停止托盘网速订阅");
+        worker.abort();
+        self.disconnect().await;
+        self.smoother.lock().reset();
+        *self.rate.lock() = None;
+        *self.last_render.lock() = None;
+
+        // 恢复不含速率的图标与提示
+        let tray = super::Tray::global();
+        if let Err(e) = tray.update_icon().await {
+            logging!(warn, Type::Tray, "恢复托盘图标失败: {}", e);
+        }
+        if let Err(e) = tray.update_tooltip().await {
+            logging!(warn, Type::Tray, "恢复托盘提示失败: {}", e);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(tray) = handle::Handle::app_handle().tray_by_id("main") {
+            let _ = tray.set_title(None::<&str>);
+        }
+    }
+
+    async fn disconnect(&self) {
+        let connection = self.connection.lock().take();
+        if let Some(id) = connection {
+            let _ = handle::Handle::mihomo().await.disconnect(id, None).await;
+        }
+    }
+
+    async fn subscribe(&self) -> Result<()> {
+        self.disconnect().await;
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let id = handle::Handle::mihomo()
+            .await
+            .ws_traffic(move |message| {
+                if let WebSocketMessage::Text(text) = message {
+                    let _ = tx.send(text.to_string());
+                }
+            })
+            .await?;
+        *self.connection.lock() = Some(id);
+
+        while let Some(text) = rx.recv().await {
+            if text.starts_with("Websocket error") {
+                bail!("{text}");
+            }
+            if let Ok(traffic) = serde_json::from_str::<Traffic>(&text) {
+                self.on_traffic(traffic);
+            }
+        }
+        Ok(())
+    }
+
+    fn on_traffic(&self, traffic: Traffic) {
+        let rate = self.smoother.lock().push(traffic.up, traffic.down);
+        *self.rate.lock() = Some(rate);
+
+        // 节流，避免频繁重绘
+        {
+            let mut last_render = self.last_render.lock();
+            if last_render.is_some_and(|last| last.elapsed() < REFRESH_INTERVAL) {
+                return;
+            }
+            *last_render = Some(Instant::now());
+        }
+        self.render(rate);
+    }
+
+    fn render(&self, rate: Rate) {
+        let Some(tray) = handle::Handle::app_handle().tray_by_id("main") else {
+            return;
+        };
+
+        #[cfg(target_os = "macos")]
+        {
+            let base_icon = self.base_icon.lock().clone();
+            if let Some(image) = base_icon.and_then(|icon| Self::compose(&icon, rate)) {
+                let _ = tray.set_icon(Some(image));
+                let _ = tray.set_icon_as_template(true);
+            }
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            let tooltip = format!("{}\n{}", self.base_tooltip.lock(), speed_line(rate));
+            let _ = tray.set_tooltip(Some(&tooltip));
+        }
+
+        #[cfg(target_os = "linux")]
+        let _ = tray.set_title(Some(&speed_line(rate)));
+    }
+
+    #[cfg(target_os = "macos")]
+    fn compose(icon: &[u8], rate: Rate) -> Option<Image<'static>> {
+        let image = Image::from_bytes(icon).ok()?;
+        let (rgba, width, height) = compose_rgba(image.rgba(), image.width(), image.height(), rate);
+        Some(Image::new_owned(rgba, width, height))
+    }
+
+    /// 记录原始图标，订阅中时返回拼接速率后的图标
+    #[cfg(target_os = "macos")]
+    pub fn decorate_icon(&self, icon: &[u8]) -> Option<Image<'static>> {
+        *self.base_icon.lock() = Some(icon.to_vec());
+        if !self.is_running() {
+            return None;
+        }
+        let rate = (*self.rate.lock())?;
+        Self::compose(icon, rate)
+    }
+
+    /// 记录原始提示，订阅中时追加速率行
+    pub fn decorate_tooltip(&self, tooltip: String) -> String {
+        tooltip.clone_into(&mut self.base_tooltip.lock());
+        match *self.rate.lock() {
+            Some(rate) if self.is_running() => format!("{tooltip}\n{}", speed_line(rate)),
+            _ => tooltip,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(0.0), "0B/s");
+        assert_eq!(format_rate(512.4), "512B/s");
+        assert_eq!(format_rate(1536.0), "1.5K/s");
+        assert_eq!(format_rate(23.0 * 1024.0 * 1024.0), "23M/s");
+        assert_eq!(format_rate(-1.0), "0B/s");
+    }
+
+    #[test]
+    fn test_smoother() {
+        let mut smoother = SpeedSmoother::default();
+        assert_eq!(smoother.push(1000, 0).up, 1000.0);
+        let rate = smoother.push(0, 1000);
+        assert!(rate.up > 0.0 && rate.up < 1000.0);
+        assert!(rate.down > 0.0 && rate.down < 1000.0);
+        smoother.reset();
+        assert_eq!(
+            smoother.push(10, 20),
+            Rate {
+                up: 10.0,
+                down: 20.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_compose_rgba() {
+        let icon = vec![255u8; 32 * 32 * 4];
+        let rate = Rate {
+            up: 2048.0,
+            down: 1.0,
+        };
+        let (rgba, width, height) = compose_rgba(&icon, 32, 32, rate);
+        assert_eq!(height, 32);
+        assert!(width > 32);
+        assert_eq!(rgba.len(), (width * height * 4) as usize);
+        // 原图标保持不变
+        assert_eq!(&rgba[..32 * 4], &icon[..32 * 4]);
+        // 文字区域有像素
+        let text_pixels = rgba
+            .chunks_exact(4)
+            .enumerate()
+            .filter(|(index, pixel)| *index as u32 % width >= 32 && pixel[3] == 255)
+            .count();
+        assert!(text_pixels > 0);
+    }
+}
@@ -0,0 +1,119 @@
+//! 托盘中当前订阅的用量、到期与更新时间
+
+use chrono::{DateTime, Local};
+
+use crate::config::{Config, PrfExtra, PrfItem};
+
+/// 当前订阅的展示信息
+#[derive(Debug, Default, Clone)]
+pub struct SubscriptionSummary {
+    pub uid: String,
+    pub is_remote: bool,
+    /// 已用 / 总量（百分比）
+    pub usage: Option<String>,
+    /// 到期日期
+    pub expire: Option<String>,
+    /// 最近一次成功更新的时间
+    pub updated: Option<String>,
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.2} {}", UNITS[unit])
+    }
+}
+
+fn format_timestamp(timestamp: u64, format: &str) -> Option<String> {
+    let timestamp = i64::try_from(timestamp).ok().filter(|ts| *ts > 0)?;
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.with_timezone(&Local).format(format).to_string())
+}
+
+/// 订阅未提供总量时返回 None
+fn usage_text(extra: &PrfExtra) -> Option<String> {
+    if extra.total == 0 {
+        return None;
+    }
+    let used = extra.upload.saturating_add(extra.download);
+    let percent = used.saturating_mul(100) / extra.total;
+    Some(format!(
+        "{} / {} ({percent}%)",
+        format_bytes(used),
+        format_bytes(extra.total)
+    ))
+}
+
+impl SubscriptionSummary {
+    fn from_item(uid: String, item: &PrfItem) -> Self {
+        let extra = item.extra.as_ref();
+        Self {
+            uid,
+            is_remote: item.itype.as_deref() == Some("remote"),
+            usage: extra.and_then(usage_text),
+            expire: extra.and_then(|extra| format_timestamp(extra.expire, "%Y-%m-%d")),
+            updated: item
+                .updated
+                .and_then(|updated| format_timestamp(updated as u64, "%Y-%m-%d %H:%M")),
+        }
+    }
+
+    /// 读取当前订阅，未选择订阅时返回 None
+    pub async fn current() -> Option<Self> {
+        let profiles = Config::profiles().await;
+        let profiles = profiles.latest_ref();
+        let uid = profiles.get_current()?;
+        let item = profiles.get_item(&uid).ok()?;
+        Some(Self::from_item(uid, item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_text() {
+        let extra = PrfExtra {
+            upload: 1024 * 1024 * 1024,
+            download: 3 * 1024 * 1024 * 1024,
+            total: 16 * 1024 * 1024 * 1024,
+            expire: 0,
+        };
+        assert_eq!(
+            usage_text(&extra).as_deref(),
+            Some("4.00 GB / 16.00 GB (25%)")
+        );
+        assert_eq!(usage_text(&PrfExtra::default()), None);
+        assert_eq!(format_bytes(512), "512 B");
+    }
+
+    #[test]
+    fn test_summary_from_item() {
+        let item = PrfItem {
+            itype: Some("remote".into()),
+            extra: Some(PrfExtra {
+                expire: 1_900_000_000,
+                ..PrfExtra::default()
+            }),
+            ..PrfItem::default()
+        };
+        let summary = SubscriptionSummary::from_item("uid".into(), &item);
+        assert!(summary.is_remote);
+        assert!(summary.usage.is_none());
+        assert!(
+            summary
+                .expire
+                .is_some_and(|expire| expire.starts_with("2030-"))
+        );
+        assert!(summary.updated.is_none());
+    }
+}
@@ -50,6 +50,25 @@ impl Default for TunManager {
     }
 }
 
+/// 启用失败时记录未通过的启用前检查，便于排查
+#[cfg(target_os = "linux")]
+async fn log_preflight_problems() {
+    use crate::core::tun_preflight::{CheckStatus, run_preflight};
+
+    for check in run_preflight().await {
+        if check.status != CheckStatus::Pass {
+            logging!(
+                warn,
+                Type::System,
+                "TUN 启用前检查 {} 未通过: {}, 建议: {}",
+                check.id,
+                check.message,
+                check.fix.unwrap_or_default()
+            );
+        }
+    }
+}
+
 impl TunManager {
     /// 获取当前 TUN 状态
     pub async fn get_status(&self) -> TunStatus {
@@ -63,6 +82,18 @@ impl TunManager {
 
     /// 检查是否可以启用 TUN 模式
     pub async fn can_enable_tun() -> Result<()> {
+        // 能力模式下以内核二进制上的能力位为准，二进制被替换后能力会丢失
+        #[cfg(target_os = "linux")]
+        if crate::core::CoreManager::global().get_running_mode()
+            == crate::core::RunningMode::Capability
+        {
+            if crate::utils::capability::core_has_tun_capabilities().await {
+                return Ok(());
+            }
+            anyhow::bail!(
+                "The core binary lacks cap_net_admin,cap_net_bind_service, grant them again"
+            );
+        }
         check_elevated_privileges()
     }
 
@@ -80,6 +111,8 @@ impl TunManager {
         if let Err(err) = self.apply_tun_config(true).await {
             let error_msg = format!("启用 TUN 模式失败: {}", err);
             logging!(error, Type::System, "{}", error_msg);
+            #[cfg(target_os = "linux")]
+            log_preflight_problems().await;
             self.set_status(TunStatus::Error(error_msg.clone())).await;
             return Err(anyhow::anyhow!(error_msg));
         }
@@ -124,6 +157,14 @@ impl TunManager {
             }
         });
 
+        // 无界面守护模式没有控制器客户端，重新生成并下发完整配置
+        if Handle::try_app_handle().is_none() {
+            return crate::core::CoreManager::global()
+                .update_config()
+                .await
+                .map(|_| ());
+        }
+
         // 通过 Handle 应用配置（带超时保护）
         timeout(
             Duration::from_secs(CONFIG_TIMEOUT_SECS),
@@ -0,0 +1,447 @@
+//! Linux TUN 启用前检查
+//!
+//! 逐项检查 `/dev/net/tun`、内核权限、已有 tun 设备与冲突路由、DNS 接管以及
+//! `ip`/`nft` 工具，每项给出 pass/warn/fail 结果与修复建议。
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    net::Ipv4Addr,
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Serialize;
+use serde_yaml_ng::{Mapping, Value};
+
+use crate::{
+    config::Config,
+    core::{CoreManager, RunningMode, service},
+    logging,
+    process::AsyncHandler,
+    utils::{capability, cidr::IpCidr, logging::Type, permission::check_admin_privileges},
+};
+
+const TUN_DEVICE: &str = "/dev/net/tun";
+/// mihomo 默认的 TUN 地址与 fake-ip 网段
+const DEFAULT_TUN_NETWORK: &str = "198.18.0.0/15";
+const DEFAULT_TUN_NAME: &str = "Meta";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// 单项检查结果，`message` 与 `fix` 为前端翻译键，按 `params` 插值
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub message: &'static str,
+    /// 修复建议，通过时为空
+    pub fix: Option<&'static str>,
+    pub params: BTreeMap<&'static str, String>,
+}
+
+impl PreflightCheck {
+    fn new(
+        id: &'static str,
+        status: CheckStatus,
+        message: &'static str,
+        fix: Option<&'static str>,
+    ) -> Self {
+        Self {
+            id,
+            status,
+            message,
+            fix,
+            params: BTreeMap::new(),
+        }
+    }
+
+    fn pass(id: &'static str, message: &'static str) -> Self {
+        Self::new(id, CheckStatus::Pass, message, None)
+    }
+
+    fn warn(id: &'static str, message: &'static str, fix: &'static str) -> Self {
+        Self::new(id, CheckStatus::Warn, message, Some(fix))
+    }
+
+    fn fail(id: &'static str, message: &'static str, fix: &'static str) -> Self {
+        Self::new(id, CheckStatus::Fail, message, Some(fix))
+    }
+
+    fn param(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.params.insert(name, value.to_string());
+        self
+    }
+}
+
+fn check_tun_device() -> PreflightCheck {
+    const ID: &str = "tun_device";
+    let Ok(metadata) = fs::metadata(TUN_DEVICE) else {
+        return PreflightCheck::fail(
+            ID,
+            "Preflight TUN Device Missing",
+            "Preflight Load TUN Module",
+        )
+        .param("device", TUN_DEVICE);
+    };
+    if !metadata.file_type().is_char_device() {
+        return PreflightCheck::fail(
+            ID,
+            "Preflight TUN Device Invalid",
+            "Preflight Recreate TUN Device",
+        )
+        .param("device", TUN_DEVICE);
+    }
+    match fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(TUN_DEVICE)
+    {
+        Ok(_) => {
+            PreflightCheck::pass(ID, "Preflight TUN Device Available").param("device", TUN_DEVICE)
+        }
+        Err(e) => PreflightCheck::warn(
+            ID,
+            "Preflight TUN Device Inaccessible",
+            "Preflight Allow TUN Device Access",
+        )
+        .param("device", TUN_DEVICE)
+        .param("error", e),
+    }
+}
+
+async fn check_core_privilege() -> PreflightCheck {
+    const ID: &str = "core_privilege";
+    match CoreManager::global().get_running_mode() {
+        RunningMode::Service => match service::is_service_available().await {
+            Ok(()) => PreflightCheck::pass(ID, "Preflight Core Runs Through Service"),
+            Err(e) => PreflightCheck::fail(
+                ID,
+                "Preflight Service Unreachable",
+                "Preflight Restart Service",
+            )
+            .param("error", e),
+        },
+        RunningMode::Capability => {
+            if capability::core_has_tun_capabilities().await {
+                PreflightCheck::pass(ID, "Preflight Core Has Capabilities")
+            } else {
+                PreflightCheck::fail(
+                    ID,
+                    "Preflight Core Lost Capabilities",
+                    "Preflight Grant Capabilities Again",
+                )
+            }
+        }
+        RunningMode::Sidecar | RunningMode::NotRunning => {
+            if check_admin_privileges().unwrap_or(false) {
+                PreflightCheck::pass(ID, "Preflight App Runs As Root")
+            } else if capability::core_has_tun_capabilities().await {
+                PreflightCheck::warn(
+                    ID,
+                    "Preflight Capabilities Not Applied",
+                    "Preflight Restart Core",
+                )
+            } else {
+                PreflightCheck::fail(
+                    ID,
+                    "Preflight Core Unprivileged",
+                    "Preflight Grant Privilege",
+                )
+            }
+        }
+    }
+}
+
+/// `/sys/class/net` 下的 tun/tap 与 WireGuard 设备
+fn tun_devices(net_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(net_dir) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            let path = entry.path();
+            path.join("tun_flags").exists()
+                || fs::read_to_string(path.join("uevent"))
+                    .is_ok_and(|uevent| uevent.contains("DEVTYPE=wireguard"))
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    devices.sort();
+    devices
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Route {
+    iface: String,
+    destination: IpCidr,
+}
+
+/// 解析 `/proc/net/route`，地址以本机字节序的十六进制表示
+fn parse_proc_routes(content: &str) -> Vec<Route> {
+    let addr = |hex: &str| {
+        u32::from_str_radix(hex, 16)
+            .ok()
+            .map(|value| Ipv4Addr::from(value.to_ne_bytes()))
+    };
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (iface, destination, mask) = (fields.first()?, fields.get(1)?, fields.get(7)?);
+            let prefix = addr(mask)?.to_bits().count_ones();
+            let destination = format!("{}/{prefix}", addr(destination)?).parse().ok()?;
+            Some(Route {
+                iface: iface.to_string(),
+                destination,
+            })
+        })
+        .collect()
+}
+
+fn overlaps(a: &IpCidr, b: &IpCidr) -> bool {
+    a.contains(b.addr()) || b.contains(a.addr())
+}
+
+fn check_conflicts(
+    devices: &[String],
+    routes: &[Route],
+    tun_name: &str,
+    tun_network: &IpCidr,
+) -> PreflightCheck {
+    const ID: &str = "conflicts";
+    let others: Vec<&String> = devices.iter().filter(|name| *name != tun_name).collect();
+    let default_via_tun: Vec<&str> = routes
+        .iter()
+        .filter(|route| route.destination.prefix() == 0 && others.contains(&&route.iface))
+        .map(|route| route.iface.as_str())
+        .collect();
+    let overlapping: Vec<String> = routes
+        .iter()
+        .filter(|route| {
+            route.iface != tun_name
+                && route.destination.prefix() > 0
+                && overlaps(&route.destination, tun_network)
+        })
+        .map(|route| format!("{} dev {}", route.destination, route.iface))
+        .collect();
+
+    if !default_via_tun.is_empty() {
+        return PreflightCheck::fail(
+            ID,
+            "Preflight Default Route Via Tunnel",
+            "Preflight Disconnect VPN",
+        )
+        .param("devices", default_via_tun.join(", "));
+    }
+    if !overlapping.is_empty() {
+        return PreflightCheck::warn(
+            ID,
+            "Preflight Routes Overlap",
+            "Preflight Change TUN Network",
+        )
+        .param("network", tun_network)
+        .param("routes", overlapping.join(", "));
+    }
+    if !others.is_empty() {
+        let names: Vec<&str> = others.iter().map(|name| name.as_str()).collect();
+        return PreflightCheck::warn(
+            ID,
+            "Preflight Other Tunnels",
+            "Preflight Exclude Tunnel Routes",
+        )
+        .param("devices", names.join(", "));
+    }
+    PreflightCheck::pass(ID, "Preflight No Conflicts")
+}
+
+fn check_dns(resolv_conf: &str, link_target: Option<&str>) -> PreflightCheck {
+    const ID: &str = "dns";
+    let stub = link_target.is_some_and(|target| target.contains("stub-resolv.conf"))
+        || resolv_conf
+            .lines()
+            .any(|line| line.split_whitespace().eq(["nameserver", "127.0.0.53"]));
+    if stub {
+        return PreflightCheck::warn(ID, "Preflight Resolved Stub", "Preflight Keep DNS Hijack");
+    }
+    if resolv_conf.contains("Generated by NetworkManager") {
+        return PreflightCheck::warn(
+            ID,
+            "Preflight NetworkManager DNS",
+            "Preflight Disable NetworkManager DNS",
+        );
+    }
+    PreflightCheck::pass(ID, "Preflight No DNS Interference")
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .chain(["/sbin", "/usr/sbin"].map(PathBuf::from))
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn check_tooling(auto_redirect: bool) -> PreflightCheck {
+    const ID: &str = "tooling";
+    let ip_rule = find_in_path("ip").is_some_and(|ip| {
+        Command::new(ip)
+            .args(["rule", "show"])
+            .output()
+            .is_ok_and(|output| output.status.success())
+    });
+    let firewall = find_in_path("nft").or_else(|| find_in_path("iptables"));
+
+    match (ip_rule, firewall) {
+        (_, None) if auto_redirect => PreflightCheck::fail(
+            ID,
+            "Preflight Firewall Missing",
+            "Preflight Install Firewall",
+        ),
+        (false, _) => PreflightCheck::warn(
+            ID,
+            "Preflight IP Rule Unavailable",
+            "Preflight Install Iproute2",
+        ),
+        (true, None) => PreflightCheck::warn(
+            ID,
+            "Preflight Firewall Tooling Missing",
+            "Preflight Install Nftables",
+        ),
+        (true, Some(_)) => PreflightCheck::pass(ID, "Preflight Tooling Available"),
+    }
+}
+
+/// 运行时配置中的 tun 设置
+async fn runtime_tun() -> Mapping {
+    let runtime = Config::runtime().await;
+    runtime
+        .latest_ref()
+        .config
+        .as_ref()
+        .and_then(|config| config.get("tun"))
+        .and_then(Value::as_mapping)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// 执行全部检查
+pub async fn run_preflight() -> Vec<PreflightCheck> {
+    let tun = runtime_tun().await;
+    let tun_name = tun
+        .get("device")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_TUN_NAME)
+        .to_string();
+    let tun_network = tun
+        .get("inet4-address")
+        .and_then(Value::as_sequence)
+        .and_then(|addresses| addresses.first())
+        .and_then(Value::as_str)
+        .and_then(|address| address.parse::<IpCidr>().ok())
+        .or_else(|| DEFAULT_TUN_NETWORK.parse().ok());
+    let auto_redirect = tun
+        .get("auto-redirect")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let core_privilege = check_core_privilege().await;
+
+    // 文件读取与外部命令都是阻塞操作，放到阻塞线程中执行
+    let result = AsyncHandler::spawn_blocking(move || {
+        let devices = tun_devices(Path::new("/sys/class/net"));
+        let routes = parse_proc_routes(&fs::read_to_string("/proc/net/route").unwrap_or_default());
+        let resolv_conf = fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
+        let link_target = fs::read_link("/etc/resolv.conf")
+            .ok()
+            .map(|target| target.to_string_lossy().into_owned());
+
+        let mut checks = vec![check_tun_device()];
+        if let Some(tun_network) = tun_network {
+            checks.push(check_conflicts(&devices, &routes, &tun_name, &tun_network));
+        }
+        checks.push(check_dns(&resolv_conf, link_target.as_deref()));
+        checks.push(check_tooling(auto_redirect));
+        checks
+    })
+    .await;
+
+    let mut checks = match result {
+        Ok(checks) => checks,
+        Err(e) => {
+            logging!(error, Type::Core, "TUN 预检执行失败: {}", e);
+            Vec::new()
+        }
+    };
+    checks.insert(checks.len().min(1), core_privilege);
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROC_ROUTE: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t00000000\t0100A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t0000A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+docker0\t000012C6\t00000000\t0001\t0\t0\t0\t0000FFFF\t0\t0\t0
+";
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_parse_proc_routes() {
+        let routes = parse_proc_routes(PROC_ROUTE);
+        assert_eq!(routes.len(), 3);
+        assert_eq!(routes[0].destination.to_string(), "0.0.0.0/0");
+        assert_eq!(routes[1].destination.to_string(), "192.168.0.0/24");
+        assert_eq!(routes[2].destination.to_string(), "198.18.0.0/16");
+
+        let network: IpCidr = DEFAULT_TUN_NETWORK.parse().unwrap();
+        let check = check_conflicts(&[], &routes, "Meta", &network);
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert_eq!(check.params["routes"], "198.18.0.0/16 dev docker0");
+
+        let mut routes = routes;
+        routes[0].iface = "wg0".into();
+        let devices = vec!["Meta".to_string(), "wg0".to_string()];
+        let check = check_conflicts(&devices, &routes, "Meta", &network);
+        assert_eq!(check.status, CheckStatus::Fail);
+
+        let check = check_conflicts(&["Meta".into()], &routes[1..2], "Meta", &network);
+        assert_eq!(check.status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_check_dns() {
+        assert_eq!(
+            check_dns("nameserver 127.0.0.53\noptions edns0\n", None).status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            check_dns("", Some("../run/systemd/resolve/stub-resolv.conf")).status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            check_dns(
+                "# Generated by NetworkManager\nnameserver 192.168.1.1\n",
+                None
+            )
+            .status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            check_dns("nameserver 1.1.1.1\n", None).status,
+            CheckStatus::Pass
+        );
+    }
+}
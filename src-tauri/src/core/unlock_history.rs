@@ -0,0 +1,353 @@
+//! 解锁检测历史
+//!
+//! 按订阅与节点记录每次检测结果，解锁状态由可用变为不可用或地区变化时发送通知。
+//! 生成 `-OK` 代理组时从这里读取各服务已解锁的节点。
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::{
+    config::Config,
+    core::{CoreManager, handle},
+    logging, singleton_lazy,
+    utils::{
+        dirs, help,
+        logging::Type,
+        notification::{NotificationEvent, notify_event},
+    },
+};
+
+const HISTORY_FILE: &str = "unlock_history.yaml";
+/// 保留的检测记录数量
+const HISTORY_CAPACITY: usize = 5000;
+
+/// 单个服务的检测结果
+#[derive(Debug, Clone, Copy)]
+pub struct UnlockResult<'a> {
+    pub service: &'a str,
+    pub status: &'a str,
+    pub region: Option<&'a str>,
+}
+
+/// 一次检测的单个服务结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockRecord {
+    /// 毫秒时间戳
+    pub time: i64,
+    pub profile: Option<String>,
+    /// 出口节点，按当前代理设置检测时出口由分流规则决定，记为空
+    pub node: Option<String>,
+    pub service: String,
+    pub status: String,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnlockChange {
+    Blocked,
+    RegionChanged { region: String },
+}
+
+impl UnlockRecord {
+    fn new(profile: Option<&str>, node: Option<&str>, result: &UnlockResult<'_>) -> Self {
+        Self {
+            time: chrono::Local::now().timestamp_millis(),
+            profile: profile.map(str::to_string),
+            node: node.map(str::to_string),
+            service: result.service.to_string(),
+            status: result.status.to_string(),
+            region: result.region.map(str::to_string),
+        }
+    }
+
+    /// 检测失败或未完成的结果不参与状态对比
+    fn is_conclusive(&self) -> bool {
+        !matches!(self.status.as_str(), "Failed" | "Pending")
+    }
+
+    fn same_target(&self, other: &Self) -> bool {
+        self.service == other.service && self.node == other.node && self.profile == other.profile
+    }
+
+    /// 与同一目标的上一次有效结果对比，出口未知时两次结果不可比
+    fn change_from(&self, previous: &Self) -> Option<UnlockChange> {
+        if self.node.is_none() || !self.is_conclusive() || previous.status != "Yes" {
+            return None;
+        }
+        if self.status != "Yes" {
+            return Some(UnlockChange::Blocked);
+        }
+        match (&previous.region, &self.region) {
+            (Some(before), Some(after)) if before != after => Some(UnlockChange::RegionChanged {
+                region: after.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UnlockHistory {
+    /// 首次访问时从文件加载
+    records: Mutex<Option<VecDeque<UnlockRecord>>>,
+}
+
+singleton_lazy!(UnlockHistory, UNLOCK_HISTORY, UnlockHistory::default);
+
+impl UnlockHistory {
+    async fn load() -> VecDeque<UnlockRecord> {
+        let Ok(path) = dirs::app_home_dir().map(|dir| dir.join(HISTORY_FILE)) else {
+            return VecDeque::new();
+        };
+        help::read_yaml::<VecDeque<UnlockRecord>>(&path)
+            .await
+            .unwrap_or_default()
+    }
+
+    async fn save(records: &VecDeque<UnlockRecord>) {
+        let result = match dirs::app_home_dir() {
+            Ok(dir) => {
+                help::save_yaml(
+                    &dir.join(HISTORY_FILE),
+                    records,
+                    Some("# Media unlock history"),
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            logging!(warn, Type::Network, "保存解锁检测历史失败: {}", e);
+        }
+    }
+
+    /// 追加记录并返回状态变化
+    fn append(
+        records: &mut VecDeque<UnlockRecord>,
+        new_records: Vec<UnlockRecord>,
+    ) -> Vec<(UnlockRecord, UnlockChange)> {
+        let mut changes = Vec::new();
+        for record in new_records {
+            let change = records
+                .iter()
+                .rev()
+                .find(|previous| previous.same_target(&record) && previous.is_conclusive())
+                .and_then(|previous| record.change_from(previous));
+            if let Some(change) = change {
+                changes.push((record.clone(), change));
+            }
+            if records.len() >= HISTORY_CAPACITY {
+                records.pop_front();
+            }
+            records.push_back(record);
+        }
+        changes
+    }
+
+    /// 记录一次检测结果，状态变化时发送通知
+    pub async fn record(&self, node: Option<&str>, results: &[UnlockResult<'_>]) {
+        let profile = Config::profiles().await.latest_ref().get_current();
+        let new_records = results
+            .iter()
+            .map(|result| UnlockRecord::new(profile.as_deref(), node, result))
+            .collect();
+
+        let changes = {
+            let mut records = self.records.lock().await;
+            if records.is_none() {
+                *records = Some(Self::load().await);
+            }
+            let records = records.get_or_insert_default();
+            let changes = Self::append(records, new_records);
+            Self::save(records).await;
+            changes
+        };
+
+        for (record, change) in changes {
+            notify_change(&record, &change).await;
+        }
+    }
+
+    /// 各服务最近一次有效检测为解锁的节点，按首次出现的顺序排列
+    fn latest_unlocked(
+        records: &VecDeque<UnlockRecord>,
+        profile: Option<&str>,
+        services: &[String],
+    ) -> HashMap<String, Vec<String>> {
+        let mut latest: Vec<&UnlockRecord> = Vec::new();
+        for record in records {
+            if record.node.is_none()
+                || record.profile.as_deref() != profile
+                || !record.is_conclusive()
+                || !services.contains(&record.service)
+            {
+                continue;
+            }
+            match latest.iter_mut().find(|prev| prev.same_target(record)) {
+                Some(prev) => *prev = record,
+                None => latest.push(record),
+            }
+        }
+
+        let mut unlocked: HashMap<String, Vec<String>> = HashMap::new();
+        for record in latest.into_iter().filter(|record| record.status == "Yes") {
+            if let Some(node) = &record.node {
+                unlocked
+                    .entry(record.service.clone())
+                    .or_default()
+                    .push(node.clone());
+            }
+        }
+        unlocked
+    }
+
+    /// 当前订阅下各服务已解锁的节点
+    pub async fn unlocked_nodes(&self, services: &[String]) -> HashMap<String, Vec<String>> {
+        let profile = Config::profiles().await.latest_ref().get_current();
+        let mut records = self.records.lock().await;
+        if records.is_none() {
+            *records = Some(Self::load().await);
+        }
+        Self::latest_unlocked(
+            records.get_or_insert_default(),
+            profile.as_deref(),
+            services,
+        )
+    }
+
+    /// 查询某服务的历史记录，按时间升序
+    pub async fn query(
+        &self,
+        service: &str,
+        node: Option<&str>,
+        limit: Option<usize>,
+    ) -> Vec<UnlockRecord> {
+        let mut records = self.records.lock().await;
+        if records.is_none() {
+            *records = Some(Self::load().await);
+        }
+        let matched = records
+            .get_or_insert_default()
+            .iter()
+            .filter(|record| record.service == service)
+            .filter(|record| node.is_none_or(|node| record.node.as_deref() == Some(node)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let skip = limit.map_or(0, |limit| matched.len().saturating_sub(limit));
+        matched.into_iter().skip(skip).collect()
+    }
+}
+
+async fn notify_change(record: &UnlockRecord, change: &UnlockChange) {
+    let node = record.node.as_deref().unwrap_or_default();
+    logging!(
+        info,
+        Type::Network,
+        "服务 {} 在节点 {} 的解锁状态变化: {:?}",
+        record.service,
+        node,
+        change
+    );
+    let Some(app_handle) = handle::Handle::try_app_handle() else {
+        return;
+    };
+    let event = match change {
+        UnlockChange::Blocked => NotificationEvent::UnlockBlocked {
+            service: &record.service,
+            node,
+        },
+        UnlockChange::RegionChanged { region } => NotificationEvent::UnlockRegionChanged {
+            service: &record.service,
+            node,
+            region,
+        },
+    };
+    notify_event(app_handle.clone(), event).await;
+}
+
+/// 重新生成运行时配置，使按解锁结果生成的代理组包含最新的已解锁节点
+pub async fn refresh_unlock_groups() {
+    let enabled = Config::verge()
+        .await
+        .latest_ref()
+        .unlock_groups
+        .as_ref()
+        .and_then(|groups| groups.enabled)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    match CoreManager::global().update_config().await {
+        Ok(_) => handle::Handle::refresh_clash(),
+        Err(e) => {
+            logging!(warn, Type::Network, "按解锁结果更新代理组失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(node: &str, status: &str, region: Option<&str>) -> UnlockRecord {
+        UnlockRecord {
+            time: 0,
+            profile: Some("profile".into()),
+            node: Some(node.into()),
+            service: "Netflix".into(),
+            status: status.into(),
+            region: region.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_append_detects_changes() {
+        let mut records = VecDeque::new();
+        let changes = UnlockHistory::append(
+            &mut records,
+            vec![
+                record("HK", "Yes", Some("HK")),
+                record("US", "Yes", Some("US")),
+            ],
+        );
+        assert!(changes.is_empty());
+
+        // 检测失败不视为被封锁，也不影响后续对比
+        let changes = UnlockHistory::append(&mut records, vec![record("HK", "Failed", None)]);
+        assert!(changes.is_empty());
+
+        let changes = UnlockHistory::append(
+            &mut records,
+            vec![record("HK", "No", None), record("US", "Yes", Some("JP"))],
+        );
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].1, UnlockChange::Blocked);
+        assert_eq!(
+            changes[1].1,
+            UnlockChange::RegionChanged {
+                region: "JP".into()
+            }
+        );
+
+        // 已经是不可用状态时不重复提醒
+        let changes = UnlockHistory::append(&mut records, vec![record("HK", "No", None)]);
+        assert!(changes.is_empty());
+        assert_eq!(records.len(), 6);
+
+        let unlocked =
+            UnlockHistory::latest_unlocked(&records, Some("profile"), &["Netflix".to_string()]);
+        assert_eq!(unlocked.get("Netflix").cloned().unwrap_or_default(), ["US"]);
+        assert!(UnlockHistory::latest_unlocked(&records, None, &["Netflix".into()]).is_empty());
+
+        // 出口未知的结果不参与对比
+        let unknown = |status: &str| UnlockRecord {
+            node: None,
+            ..record("", status, None)
+        };
+        UnlockHistory::append(&mut records, vec![unknown("Yes")]);
+        let changes = UnlockHistory::append(&mut records, vec![unknown("No")]);
+        assert!(changes.is_empty());
+    }
+}
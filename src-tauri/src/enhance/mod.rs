@@ -4,9 +4,15 @@ mod merge;
 mod script;
 pub mod seq;
 mod tun;
+mod unlock_groups;
 
+pub use self::tun::validate_tun_filter;
+pub use self::unlock_groups::{
+    UNLOCK_CHECK_GROUP, set_unlock_check_active, unlock_check_proxy, use_unlock_check_listener,
+    use_unlock_groups,
+};
 use self::{chain::*, field::*, merge::*, script::*, seq::*, tun::*};
-use crate::{config::Config, utils::tmpl};
+use crate::{config::Config, core::unlock_history::UnlockHistory, utils::tmpl};
 use serde_yaml_ng::Mapping;
 use std::collections::{HashMap, HashSet};
 
@@ -30,6 +36,7 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             verge.enable_dns_settings.unwrap_or(false),
         )
     };
+    let tun_filter = { Config::verge().await.latest_ref().tun_filter.clone() };
     #[cfg(not(target_os = "windows"))]
     let redir_enabled = {
         let verge = Config::verge().await;
@@ -373,7 +380,25 @@ pub async fn enhance() -> (Mapping, Vec<String>, HashMap<String, ResultLog>) {
             });
     }
 
-    config = use_tun(config, enable_tun);
+    // 按解锁检测结果生成的代理组
+    let (unlock_groups, test_url) = {
+        let verge = Config::verge().await;
+        let verge = verge.latest_ref();
+        (
+            verge.unlock_groups.clone().unwrap_or_default(),
+            verge.default_latency_test.clone().unwrap_or_default(),
+        )
+    };
+    let unlocked = if unlock_groups.enabled.unwrap_or(false) {
+        let services = unlock_groups.services.clone().unwrap_or_default();
+        UnlockHistory::global().unlocked_nodes(&services).await
+    } else {
+        HashMap::new()
+    };
+    config = use_unlock_groups(config, &unlock_groups, &unlocked, &test_url);
+    config = use_unlock_check_listener(config, unlock_groups::unlock_check_active());
+
+    config = use_tun(config, enable_tun, tun_filter.as_ref());
     config = use_sort(config);
 
     // 应用独立的DNS配置（如果启用）
@@ -1,7 +1,9 @@
+use anyhow::{Result, anyhow, bail};
 use serde_yaml_ng::{Mapping, Value};
 
 #[cfg(target_os = "macos")]
 use crate::process::AsyncHandler;
+use crate::{config::ITunFilter, utils::cidr::IpCidr};
 
 macro_rules! revise {
     ($map: expr, $key: expr, $val: expr) => {
@@ -21,7 +23,155 @@ macro_rules! append {
     };
 }
 
-pub fn use_tun(mut config: Mapping, enable: bool) -> Mapping {
+/// `1000` 或 `1000-1999`（也接受 `1000:1999`）形式的 uid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UidRule {
+    Single(u32),
+    Range(u32, u32),
+}
+
+fn parse_uid(value: &str) -> Result<UidRule> {
+    let value = value.trim();
+    let parse = |uid: &str| {
+        uid.trim()
+            .parse::<u32>()
+            .map_err(|_| anyhow!("invalid uid: {value:?}"))
+    };
+    match value.split_once(['-', ':']) {
+        Some((start, end)) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                bail!("invalid uid range: {value:?}");
+            }
+            Ok(UidRule::Range(start, end))
+        }
+        None => parse(value).map(UidRule::Single),
+    }
+}
+
+/// Android 包名，如 `com.example.app`
+fn is_package_name(value: &str) -> bool {
+    let mut segments = value.split('.');
+    let valid = |segment: &str| {
+        segment
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic())
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    value.contains('.') && segments.all(valid)
+}
+
+fn is_interface_name(value: &str) -> bool {
+    !value.trim().is_empty() && !value.chars().any(|c| c.is_control() || c == ',')
+}
+
+/// 校验 TUN 包含/排除设置
+pub fn validate_tun_filter(filter: &ITunFilter) -> Result<()> {
+    for uid in [&filter.include_uid, &filter.exclude_uid]
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        parse_uid(uid)?;
+    }
+    for package in [&filter.include_package, &filter.exclude_package]
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        if !is_package_name(package.trim()) {
+            bail!("invalid package name: {package:?}");
+        }
+    }
+    for interface in [&filter.include_interface, &filter.exclude_interface]
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        if !is_interface_name(interface) {
+            bail!("invalid interface name: {interface:?}");
+        }
+    }
+    let non_empty = |list: &Option<Vec<String>>| list.as_ref().is_some_and(|list| !list.is_empty());
+    if non_empty(&filter.include_interface) && non_empty(&filter.exclude_interface) {
+        bail!("include_interface and exclude_interface cannot be used together");
+    }
+    for cidr in filter.route_exclude_address.iter().flatten() {
+        cidr.parse::<IpCidr>()?;
+    }
+    Ok(())
+}
+
+fn string_seq(items: impl IntoIterator<Item = String>) -> Value {
+    Value::Sequence(items.into_iter().map(Value::from).collect())
+}
+
+/// 设置列表字段，未设置时保留原值，空列表时移除
+fn revise_list(tun: &mut Mapping, key: &str, list: Option<Value>) {
+    match list {
+        Some(Value::Sequence(seq)) if seq.is_empty() => {
+            tun.remove(key);
+        }
+        Some(value) => {
+            revise!(tun, key, value);
+        }
+        None => {}
+    }
+}
+
+fn revise_uid(tun: &mut Mapping, key: &str, uids: Option<&Vec<String>>) {
+    let Some(uids) = uids else {
+        return;
+    };
+    // 已校验过，这里忽略无法解析的项
+    let rules: Vec<UidRule> = uids.iter().filter_map(|uid| parse_uid(uid).ok()).collect();
+    let singles = rules.iter().filter_map(|rule| match rule {
+        UidRule::Single(uid) => Some(Value::from(*uid)),
+        UidRule::Range(..) => None,
+    });
+    let ranges = rules.iter().filter_map(|rule| match rule {
+        UidRule::Range(start, end) => Some(format!("{start}:{end}")),
+        UidRule::Single(_) => None,
+    });
+    revise_list(tun, key, Some(Value::Sequence(singles.collect())));
+    revise_list(tun, &format!("{key}-range"), Some(string_seq(ranges)));
+}
+
+/// 将包含/排除设置合并到 tun 配置
+fn apply_tun_filter(tun: &mut Mapping, filter: &ITunFilter) {
+    let names = |list: &Option<Vec<String>>| {
+        list.as_ref().map(|list| {
+            string_seq(
+                list.iter()
+                    .map(|item| item.trim().to_string())
+                    .filter(|item| !item.is_empty()),
+            )
+        })
+    };
+
+    revise_uid(tun, "include-uid", filter.include_uid.as_ref());
+    revise_uid(tun, "exclude-uid", filter.exclude_uid.as_ref());
+    revise_list(tun, "include-package", names(&filter.include_package));
+    revise_list(tun, "exclude-package", names(&filter.exclude_package));
+    revise_list(tun, "include-interface", names(&filter.include_interface));
+    revise_list(tun, "exclude-interface", names(&filter.exclude_interface));
+    revise_list(
+        tun,
+        "route-exclude-address",
+        filter.route_exclude_address.as_ref().map(|list| {
+            string_seq(
+                list.iter()
+                    .filter_map(|cidr| cidr.parse::<IpCidr>().ok())
+                    .map(|cidr| cidr.to_string()),
+            )
+        }),
+    );
+}
+
+pub fn use_tun(mut config: Mapping, enable: bool, filter: Option<&ITunFilter>) -> Mapping {
     let tun_key = Value::from("tun");
     let tun_val = config.get(&tun_key);
     let mut tun_val = tun_val.map_or(Mapping::new(), |val| {
@@ -99,9 +249,95 @@ pub fn use_tun(mut config: Mapping, enable: bool) -> Mapping {
         });
     }
 
+    if let Some(filter) = filter {
+        apply_tun_filter(&mut tun_val, filter);
+    }
+
     // 更新TUN配置
     revise!(tun_val, "enable", enable);
     revise!(config, "tun", tun_val);
 
     config
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(items: &[&str]) -> Option<Vec<String>> {
+        Some(items.iter().map(|item| item.to_string()).collect())
+    }
+
+    #[test]
+    fn test_validate_tun_filter() {
+        let filter = ITunFilter {
+            include_uid: list(&["1000", "2000-2999", "3000:3001"]),
+            include_package: list(&["com.example.app"]),
+            exclude_interface: list(&["docker0", "Ethernet 2"]),
+            route_exclude_address: list(&["192.168.0.0/16", "fd00::/8"]),
+            ..ITunFilter::default()
+        };
+        assert!(validate_tun_filter(&filter).is_ok());
+
+        let invalid = [
+            ITunFilter {
+                exclude_uid: list(&["2000-1000"]),
+                ..ITunFilter::default()
+            },
+            ITunFilter {
+                include_uid: list(&["-1"]),
+                ..ITunFilter::default()
+            },
+            ITunFilter {
+                include_package: list(&["example"]),
+                ..ITunFilter::default()
+            },
+            ITunFilter {
+                include_interface: list(&["eth0"]),
+                exclude_interface: list(&["wlan0"]),
+                ..ITunFilter::default()
+            },
+            ITunFilter {
+                route_exclude_address: list(&["10.0.0.0/33"]),
+                ..ITunFilter::default()
+            },
+        ];
+        assert!(
+            invalid
+                .iter()
+                .all(|filter| validate_tun_filter(filter).is_err())
+        );
+    }
+
+    #[test]
+    fn test_use_tun_filter() {
+        let config: Mapping = serde_yaml_ng::from_str(
+            "tun:\n  stack: system\n  include-package: [com.profile.app]\n  exclude-interface: [lo]\n",
+        )
+        .unwrap_or_default();
+        let filter = ITunFilter {
+            include_uid: list(&["1000", "2000-2999"]),
+            exclude_interface: Some(Vec::new()),
+            route_exclude_address: list(&["10.0.0.1"]),
+            ..ITunFilter::default()
+        };
+        let config = use_tun(config, false, Some(&filter));
+        let tun = config
+            .get("tun")
+            .and_then(Value::as_mapping)
+            .cloned()
+            .unwrap_or_default();
+
+        let yaml = |value: &str| serde_yaml_ng::from_str::<Value>(value).unwrap_or_default();
+        assert_eq!(tun.get("include-uid"), Some(&yaml("[1000]")));
+        assert_eq!(tun.get("include-uid-range"), Some(&yaml("['2000:2999']")));
+        assert_eq!(
+            tun.get("route-exclude-address"),
+            Some(&yaml("['10.0.0.1/32']"))
+        );
+        // 未设置的字段保留订阅中的值，空列表移除
+        assert_eq!(tun.get("include-package"), Some(&yaml("[com.profile.app]")));
+        assert!(!tun.contains_key("exclude-interface"));
+        assert!(!tun.contains_key("exclude-uid"));
+    }
+}
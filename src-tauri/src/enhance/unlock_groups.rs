@@ -0,0 +1,315 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::{Ipv4Addr, TcpListener},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use once_cell::sync::Lazy;
+use serde_yaml_ng::{Mapping, Sequence, Value};
+
+use crate::config::IUnlockGroups;
+
+/// 生成的代理组带有此标记，重新生成时据此整体替换，不应手动编辑
+const GENERATED_KEY: &str = "x-generated";
+const GROUP_SUFFIX: &str = "-OK";
+const DEFAULT_TEST_URL: &str = "https://cp.cloudflare.com/generate_204";
+const URL_TEST_INTERVAL: u64 = 300;
+/// 没有可用节点时的占位，避免空分组导致配置无法加载
+const FALLBACK_PROXY: &str = "DIRECT";
+
+/// 逐个节点检测解锁时使用的隐藏分组与监听入站，检测时只切换该分组，不影响用户的选择
+pub const UNLOCK_CHECK_GROUP: &str = "x-unlock-check";
+const UNLOCK_CHECK_LISTENER: &str = "x-unlock-check";
+/// 无法分配空闲端口时使用的检测端口
+const FALLBACK_CHECK_PORT: u16 = 7899;
+
+/// 本次运行中检测入站使用的端口，启动时分配一次，重新生成配置时保持不变
+static UNLOCK_CHECK_PORT: Lazy<u16> = Lazy::new(|| {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|listener| listener.local_addr())
+        .map_or(FALLBACK_CHECK_PORT, |addr| addr.port())
+});
+
+/// 仅在逐节点检测期间为真，其余时间运行时配置中不包含检测分组与入站
+static UNLOCK_CHECK_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+pub fn unlock_check_active() -> bool {
+    UNLOCK_CHECK_ACTIVE.load(Ordering::Acquire)
+}
+
+/// 开始或结束逐节点检测，之后需要重新生成运行时配置才会生效
+pub fn set_unlock_check_active(active: bool) {
+    UNLOCK_CHECK_ACTIVE.store(active, Ordering::Release);
+}
+
+fn unlock_item_name(service: &str) -> String {
+    format!("{service}{GROUP_SUFFIX}")
+}
+
+fn is_generated(group: &Value) -> bool {
+    group
+        .get(GENERATED_KEY)
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn item_name(item: &Value) -> Option<&str> {
+    item.get("name").and_then(Value::as_str)
+}
+
+/// 移除旧的生成分组，再为每个服务追加只包含已解锁节点的分组
+/// `unlocked` 为服务名到节点名的映射。配置 `proxies` 中的节点直接列出，
+/// 其余节点可能来自 proxy-provider，通过 `use` 全部 provider 并用 `filter` 按名称精确匹配，
+/// 没有 provider 时这些节点会被忽略
+pub fn use_unlock_groups(
+    mut config: Mapping,
+    options: &IUnlockGroups,
+    unlocked: &HashMap<String, Vec<String>>,
+    test_url: &str,
+) -> Mapping {
+    let mut groups = config
+        .get("proxy-groups")
+        .and_then(Value::as_sequence)
+        .cloned()
+        .unwrap_or_default();
+    let before = groups.len();
+    groups.retain(|group| !is_generated(group));
+    if before == groups.len() && !options.enabled.unwrap_or(false) {
+        return config;
+    }
+
+    if options.enabled.unwrap_or(false) {
+        let proxy_names: HashSet<&str> = config
+            .get("proxies")
+            .and_then(Value::as_sequence)
+            .map(|proxies| proxies.iter().filter_map(item_name).collect())
+            .unwrap_or_default();
+        let providers = config
+            .get("proxy-providers")
+            .and_then(Value::as_mapping)
+            .map(|providers| providers.keys().cloned().collect::<Sequence>())
+            .unwrap_or_default();
+        let test_url = if test_url.is_empty() {
+            DEFAULT_TEST_URL
+        } else {
+            test_url
+        };
+        let group_type = match options.group_type.as_deref() {
+            Some("url-test") => "url-test",
+            _ => "select",
+        };
+
+        let mut generated = Sequence::new();
+        for service in options.services.iter().flatten() {
+            let name = unlock_item_name(service);
+            // 已有同名分组时保留用户的定义
+            if groups.iter().any(|group| item_name(group) == Some(&name)) {
+                continue;
+            }
+            let (listed, others): (Vec<&String>, Vec<&String>) = unlocked
+                .get(service)
+                .into_iter()
+                .flatten()
+                .partition(|node| proxy_names.contains(node.as_str()));
+            let mut nodes = listed
+                .into_iter()
+                .map(|node| Value::from(node.as_str()))
+                .collect::<Sequence>();
+            let use_providers = !others.is_empty() && !providers.is_empty();
+            if nodes.is_empty() && !use_providers {
+                nodes.push(FALLBACK_PROXY.into());
+            }
+
+            let mut group = Mapping::new();
+            group.insert("name".into(), name.into());
+            group.insert("type".into(), group_type.into());
+            group.insert("proxies".into(), nodes.into());
+            if use_providers {
+                let names = others
+                    .iter()
+                    .map(|node| regex::escape(node))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                group.insert("use".into(), providers.clone().into());
+                group.insert("filter".into(), format!("^(?:{names})$").into());
+            }
+            if group_type == "url-test" {
+                group.insert("url".into(), test_url.into());
+                group.insert("interval".into(), URL_TEST_INTERVAL.into());
+            }
+            group.insert(GENERATED_KEY.into(), true.into());
+            generated.push(group.into());
+        }
+        groups.extend(generated);
+    }
+
+    config.insert("proxy-groups".into(), groups.into());
+    config
+}
+
+/// 检测期间添加检测分组与只转发到该分组的本地入站，`enabled` 为假时只移除同名项
+///
+/// 分组通过 `include-all` 包含全部节点（含 proxy-provider 中的节点），并列出所有代理组。
+pub fn use_unlock_check_listener(mut config: Mapping, enabled: bool) -> Mapping {
+    let mut groups = config
+        .get("proxy-groups")
+        .and_then(Value::as_sequence)
+        .cloned()
+        .unwrap_or_default();
+    groups.retain(|group| item_name(group) != Some(UNLOCK_CHECK_GROUP));
+    let mut listeners = config
+        .get("listeners")
+        .and_then(Value::as_sequence)
+        .cloned()
+        .unwrap_or_default();
+    listeners.retain(|listener| item_name(listener) != Some(UNLOCK_CHECK_LISTENER));
+    if !enabled {
+        config.insert("proxy-groups".into(), groups.into());
+        if listeners.is_empty() {
+            config.remove("listeners");
+        } else {
+            config.insert("listeners".into(), listeners.into());
+        }
+        return config;
+    }
+
+    let mut members = vec![Value::from(FALLBACK_PROXY)];
+    members.extend(groups.iter().filter_map(item_name).map(Value::from));
+
+    let mut group = Mapping::new();
+    group.insert("name".into(), UNLOCK_CHECK_GROUP.into());
+    group.insert("type".into(), "select".into());
+    group.insert("include-all".into(), true.into());
+    group.insert("proxies".into(), members.into());
+    group.insert("hidden".into(), true.into());
+    groups.push(group.into());
+    config.insert("proxy-groups".into(), groups.into());
+
+    let mut listener = Mapping::new();
+    listener.insert("name".into(), UNLOCK_CHECK_LISTENER.into());
+    listener.insert("type".into(), "mixed".into());
+    listener.insert("listen".into(), Ipv4Addr::LOCALHOST.to_string().into());
+    listener.insert("port".into(), (*UNLOCK_CHECK_PORT).into());
+    listener.insert("proxy".into(), UNLOCK_CHECK_GROUP.into());
+    listeners.push(listener.into());
+    config.insert("listeners".into(), listeners.into());
+    config
+}
+
+/// 运行时配置中检测入站的代理地址
+pub fn unlock_check_proxy(config: &Mapping) -> Option<String> {
+    let port = config
+        .get("listeners")?
+        .as_sequence()?
+        .iter()
+        .find(|listener| item_name(listener) == Some(UNLOCK_CHECK_LISTENER))?
+        .get("port")?
+        .as_u64()?;
+    Some(format!("http://{}:{port}", Ipv4Addr::LOCALHOST))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "
+proxies:
+  - { name: HK, type: ss }
+  - { name: US, type: ss }
+proxy-groups:
+  - { name: Proxy, type: select, proxies: [HK, US] }
+  - { name: Old-OK, type: select, proxies: [HK], x-generated: true }
+  - { name: Claude-OK, type: select, proxies: [US] }
+";
+
+    fn group_nodes(config: &Mapping, name: &str) -> Option<Vec<String>> {
+        let groups = config.get("proxy-groups")?.as_sequence()?;
+        let group = groups.iter().find(|group| item_name(group) == Some(name))?;
+        Some(
+            group
+                .get("proxies")?
+                .as_sequence()?
+                .iter()
+                .filter_map(|node| node.as_str().map(str::to_string))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_use_unlock_groups() {
+        let config: Mapping = serde_yaml_ng::from_str(CONFIG).unwrap();
+        let options = IUnlockGroups {
+            enabled: Some(true),
+            services: Some(vec!["Netflix".into(), "Disney+".into(), "Claude".into()]),
+            group_type: Some("url-test".into()),
+        };
+        let unlocked = HashMap::from([
+            (
+                "Netflix".to_string(),
+                vec!["US".to_string(), "Gone".to_string()],
+            ),
+            ("Claude".to_string(), vec!["HK".to_string()]),
+        ]);
+        let result = use_unlock_groups(config.clone(), &options, &unlocked, "http://test");
+
+        assert_eq!(group_nodes(&result, "Netflix-OK").unwrap(), ["US"]);
+        assert_eq!(group_nodes(&result, "Disney+-OK").unwrap(), ["DIRECT"]);
+        // 用户自己的同名分组不被覆盖，旧的生成分组被移除
+        assert_eq!(group_nodes(&result, "Claude-OK").unwrap(), ["US"]);
+        assert!(group_nodes(&result, "Old-OK").is_none());
+
+        // 重复应用结果不变
+        let again = use_unlock_groups(result.clone(), &options, &unlocked, "http://test");
+        assert_eq!(again, result);
+
+        let disabled = use_unlock_groups(config.clone(), &IUnlockGroups::default(), &unlocked, "");
+        assert!(group_nodes(&disabled, "Old-OK").is_none());
+        assert!(group_nodes(&disabled, "Netflix-OK").is_none());
+
+        // 不在 proxies 中的节点通过 provider 与 filter 引用
+        let mut with_provider = config;
+        with_provider.insert(
+            "proxy-providers".into(),
+            serde_yaml_ng::from_str("{ sub: { type: http } }").unwrap(),
+        );
+        let result = use_unlock_groups(with_provider, &options, &unlocked, "");
+        let groups = result.get("proxy-groups").unwrap().as_sequence().unwrap();
+        let netflix = groups
+            .iter()
+            .find(|group| item_name(group) == Some("Netflix-OK"))
+            .unwrap();
+        assert_eq!(group_nodes(&result, "Netflix-OK").unwrap(), ["US"]);
+        assert_eq!(netflix.get("filter").unwrap().as_str(), Some("^(?:Gone)$"));
+        assert_eq!(
+            netflix.get("use").unwrap().as_sequence().unwrap(),
+            &vec![Value::from("sub")]
+        );
+    }
+
+    #[test]
+    fn test_use_unlock_check_listener() {
+        let config: Mapping = serde_yaml_ng::from_str(CONFIG).unwrap();
+        let result = use_unlock_check_listener(config.clone(), true);
+        assert_eq!(
+            group_nodes(&result, UNLOCK_CHECK_GROUP).unwrap(),
+            ["DIRECT", "Proxy", "Old-OK", "Claude-OK"]
+        );
+        let proxy = unlock_check_proxy(&result).unwrap();
+        assert!(proxy.starts_with("http://127.0.0.1:"));
+
+        // 重复应用不会产生重复的分组与入站
+        let again = use_unlock_check_listener(result.clone(), true);
+        assert_eq!(again, result);
+        assert_eq!(
+            again.get("listeners").unwrap().as_sequence().unwrap().len(),
+            1
+        );
+
+        // 检测结束后移除检测分组与入站
+        let removed = use_unlock_check_listener(result, false);
+        assert!(group_nodes(&removed, UNLOCK_CHECK_GROUP).is_none());
+        assert!(unlock_check_proxy(&removed).is_none());
+        assert_eq!(removed, use_unlock_check_listener(config, false));
+    }
+}
@@ -0,0 +1,546 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow, bail};
+use futures::io::{AsyncRead, AsyncReadExt};
+use isahc::{AsyncBody, prelude::*};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::{
+    core::handle,
+    logging,
+    utils::{
+        logging::Type,
+        network::{NetworkManager, ProxyType},
+    },
+};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_DURATION_SECS: u64 = 15;
+const DEFAULT_MAX_BYTES: u64 = 100 * 1024 * 1024;
+const DEFAULT_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 带宽测试参数
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BandwidthOptions {
+    /// 下载测试地址
+    pub download_url: String,
+    /// 上传测试地址，为空时不测上传
+    pub upload_url: Option<String>,
+    /// 上传的数据量（字节）
+    pub upload_bytes: Option<u64>,
+    /// 单项测试的最大传输量（字节），达到后提前结束
+    pub max_bytes: Option<u64>,
+    /// 单项测试的最长时间（秒）
+    pub duration_secs: Option<u64>,
+}
+
+/// 一个采样区间内的速率
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BandwidthSample {
+    /// 距开始的毫秒数
+    pub elapsed_ms: u64,
+    /// 累计传输字节数
+    pub bytes: u64,
+    /// 区间速率（Mbps）
+    pub mbps: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BandwidthDirection {
+    Download,
+    Upload,
+}
+
+/// 测试过程中推送给前端的进度
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BandwidthProgress {
+    pub direction: BandwidthDirection,
+    #[serde(flatten)]
+    pub sample: BandwidthSample,
+}
+
+/// 单项（下载或上传）的测试结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransferReport {
+    pub bytes: u64,
+    pub elapsed_ms: u64,
+    /// 平均速率（Mbps）
+    pub mbps: f64,
+    pub peak_mbps: f64,
+    pub samples: Vec<BandwidthSample>,
+    /// 是否因达到字节上限或时间上限提前结束
+    pub capped: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BandwidthReport {
+    pub download: Option<TransferReport>,
+    pub upload: Option<TransferReport>,
+    pub cancelled: bool,
+}
+
+fn mbps(bytes: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0.0;
+    }
+    (bytes as f64 * 8.0 / 1_000_000.0 / secs * 100.0).round() / 100.0
+}
+
+/// 按固定间隔记录传输速率
+struct RateMeter {
+    start: Instant,
+    last_at: Instant,
+    last_bytes: u64,
+    report: TransferReport,
+}
+
+impl RateMeter {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last_at: now,
+            last_bytes: 0,
+            report: TransferReport::default(),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// 距上次采样超过间隔时记录一次，返回新的采样
+    fn update(&mut self, bytes: u64, force: bool) -> Option<BandwidthSample> {
+        let now = Instant::now();
+        let interval = now.duration_since(self.last_at);
+        if !force && interval < SAMPLE_INTERVAL {
+            return None;
+        }
+        if interval.is_zero() {
+            return None;
+        }
+        let sample = BandwidthSample {
+            elapsed_ms: now.duration_since(self.start).as_millis() as u64,
+            bytes,
+            mbps: mbps(bytes.saturating_sub(self.last_bytes), interval),
+        };
+        self.last_at = now;
+        self.last_bytes = bytes;
+        self.report.peak_mbps = self.report.peak_mbps.max(sample.mbps);
+        self.report.samples.push(sample);
+        Some(sample)
+    }
+
+    fn finish(mut self, bytes: u64, capped: bool) -> TransferReport {
+        self.update(bytes, true);
+        let elapsed = self.elapsed();
+        self.report.bytes = bytes;
+        self.report.elapsed_ms = elapsed.as_millis() as u64;
+        self.report.mbps = mbps(bytes, elapsed);
+        self.report.capped = capped;
+        self.report
+    }
+}
+
+/// 读取数据直到结束、达到上限或被取消
+async fn measure_read<R, F>(
+    reader: &mut R,
+    max_bytes: u64,
+    max_duration: Duration,
+    cancelled: &AtomicBool,
+    on_sample: &mut F,
+) -> Result<TransferReport>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(BandwidthSample),
+{
+    let mut meter = RateMeter::new();
+    let mut buf = vec![0u8; READ_BUFFER_SIZE];
+    let mut total = 0u64;
+    let mut capped = false;
+    loop {
+        if cancelled.load(Ordering::Acquire) {
+            bail!("bandwidth test cancelled");
+        }
+        let remaining = max_duration.saturating_sub(meter.elapsed());
+        if remaining.is_zero() {
+            capped = true;
+            break;
+        }
+        // 数据停滞时也按间隔醒来，以便记录采样和响应取消
+        let wait = remaining.min(SAMPLE_INTERVAL);
+        let Ok(read) = tokio::time::timeout(wait, reader.read(&mut buf)).await else {
+            if let Some(sample) = meter.update(total, false) {
+                on_sample(sample);
+            }
+            continue;
+        };
+        let read = read?;
+        if read == 0 {
+            break;
+        }
+        total += read as u64;
+        if let Some(sample) = meter.update(total, false) {
+            on_sample(sample);
+        }
+        if total >= max_bytes {
+            capped = true;
+            break;
+        }
+    }
+    Ok(meter.finish(total, capped))
+}
+
+/// 上传用的数据源，记录已被读取的字节数
+struct UploadPayload {
+    remaining: u64,
+    sent: Arc<AtomicU64>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl AsyncRead for UploadPayload {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.cancelled.load(Ordering::Acquire) {
+            return Poll::Ready(Err(io::Error::other("bandwidth test cancelled")));
+        }
+        let len = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        buf[..len].fill(0);
+        self.remaining -= len as u64;
+        self.sent.fetch_add(len as u64, Ordering::Relaxed);
+        Poll::Ready(Ok(len))
+    }
+}
+
+/// 带宽测试，`cancelled` 置位后尽快结束
+pub struct BandwidthTest {
+    pub options: BandwidthOptions,
+    pub proxy_type: ProxyType,
+    pub cancelled: Arc<AtomicBool>,
+}
+
+impl BandwidthTest {
+    fn max_bytes(&self) -> u64 {
+        self.options
+            .max_bytes
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(DEFAULT_MAX_BYTES)
+    }
+
+    fn max_duration(&self) -> Duration {
+        Duration::from_secs(
+            self.options
+                .duration_secs
+                .filter(|secs| *secs > 0)
+                .unwrap_or(DEFAULT_DURATION_SECS),
+        )
+    }
+
+    async fn download<F: FnMut(BandwidthSample)>(
+        &self,
+        on_sample: &mut F,
+    ) -> Result<TransferReport> {
+        let client = NetworkManager::new()
+            .create_request(self.proxy_type, None, None, false)
+            .await?;
+        let mut response = tokio::time::timeout(
+            self.max_duration(),
+            client.get_async(self.options.download_url.as_str()),
+        )
+        .await
+        .map_err(|_| anyhow!("download request timed out"))??;
+        if !response.status().is_success() {
+            bail!("Unexpected status code: {}", response.status());
+        }
+        measure_read(
+            response.body_mut(),
+            self.max_bytes(),
+            self.max_duration(),
+            &self.cancelled,
+            on_sample,
+        )
+        .await
+    }
+
+    async fn upload<F: FnMut(BandwidthSample)>(
+        &self,
+        url: &str,
+        on_sample: &mut F,
+    ) -> Result<TransferReport> {
+        let size = self
+            .options
+            .upload_bytes
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(DEFAULT_UPLOAD_BYTES)
+            .min(self.max_bytes());
+        let sent = Arc::new(AtomicU64::new(0));
+        let payload = UploadPayload {
+            remaining: size,
+            sent: Arc::clone(&sent),
+            cancelled: Arc::clone(&self.cancelled),
+        };
+
+        let client = NetworkManager::new()
+            .create_request(self.proxy_type, None, None, false)
+            .await?;
+        let request =
+            isahc::Request::post(url).body(AsyncBody::from_reader_sized(payload, size))?;
+        let mut meter = RateMeter::new();
+        let send = client.send_async(request);
+        tokio::pin!(send);
+        let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+        let deadline = tokio::time::sleep(self.max_duration());
+        tokio::pin!(deadline);
+        let capped = loop {
+            tokio::select! {
+                response = &mut send => {
+                    let response = response?;
+                    if !response.status().is_success() {
+                        bail!("Unexpected status code: {}", response.status());
+                    }
+                    break false;
+                }
+                _ = &mut deadline => break true,
+                _ = ticker.tick() => {
+                    if self.cancelled.load(Ordering::Acquire) {
+                        bail!("bandwidth test cancelled");
+                    }
+                    if let Some(sample) = meter.update(sent.load(Ordering::Relaxed), false) {
+                        on_sample(sample);
+                    }
+                }
+            }
+        };
+        if self.cancelled.load(Ordering::Acquire) {
+            bail!("bandwidth test cancelled");
+        }
+        Ok(meter.finish(sent.load(Ordering::Relaxed), capped))
+    }
+
+    /// 依次进行下载与上传测试，被取消时返回已完成的部分
+    pub async fn run<F>(&self, mut on_progress: F) -> Result<BandwidthReport>
+    where
+        F: FnMut(BandwidthProgress),
+    {
+        if self.options.download_url.trim().is_empty() {
+            return Err(anyhow!("download url is empty"));
+        }
+        let mut report = BandwidthReport::default();
+
+        let mut on_sample = |sample| {
+            on_progress(BandwidthProgress {
+                direction: BandwidthDirection::Download,
+                sample,
+            })
+        };
+        match self.download(&mut on_sample).await {
+            Ok(download) => report.download = Some(download),
+            Err(_) if self.cancelled.load(Ordering::Acquire) => {
+                report.cancelled = true;
+                return Ok(report);
+            }
+            Err(e) => return Err(e),
+        }
+
+        if let Some(url) = self
+            .options
+            .upload_url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+        {
+            let mut on_sample = |sample| {
+                on_progress(BandwidthProgress {
+                    direction: BandwidthDirection::Upload,
+                    sample,
+                })
+            };
+            match self.upload(url, &mut on_sample).await {
+                Ok(upload) => report.upload = Some(upload),
+                Err(_) if self.cancelled.load(Ordering::Acquire) => report.cancelled = true,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// 正在进行的测试的取消标记
+static CURRENT_TEST: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+/// 测试结束、出错或任务被中断时清除取消标记
+struct CurrentTestGuard;
+
+impl Drop for CurrentTestGuard {
+    fn drop(&mut self) {
+        *CURRENT_TEST.lock() = None;
+    }
+}
+
+/// 通过本地混合端口测试带宽，流量按当前的分流规则经由内核发出
+pub async fn test_bandwidth(options: BandwidthOptions) -> Result<BandwidthReport> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut current = CURRENT_TEST.lock();
+        if current.is_some() {
+            bail!("bandwidth test is already running");
+        }
+        *current = Some(Arc::clone(&cancelled));
+    }
+    let _guard = CurrentTestGuard;
+
+    let test = BandwidthTest {
+        options,
+        proxy_type: ProxyType::Localhost,
+        cancelled,
+    };
+    let result = test
+        .run(|progress| {
+            let _ = handle::Handle::app_handle().emit("verge://bandwidth-progress", progress);
+        })
+        .await;
+
+    if let Err(e) = &result {
+        logging!(warn, Type::Network, "带宽测试失败: {}", e);
+    }
+    result
+}
+
+/// 取消正在进行的带宽测试
+pub fn cancel_bandwidth_test() {
+    if let Some(cancelled) = CURRENT_TEST.lock().as_ref() {
+        cancelled.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    /// 返回固定大小响应体并接收上传数据的本地 HTTP 服务
+    fn mock_server(body_size: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; READ_BUFFER_SIZE];
+                let mut request = Vec::new();
+                while let Ok(len) = stream.read(&mut buf) {
+                    request.extend_from_slice(&buf[..len]);
+                    if len == 0 || request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        break;
+                    }
+                }
+                let request = String::from_utf8_lossy(&request).to_string();
+                if request
+                    .to_ascii_lowercase()
+                    .contains("expect: 100-continue")
+                {
+                    let _ = stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n");
+                }
+                let response = if request.starts_with("POST") {
+                    let length = request
+                        .lines()
+                        .find_map(|line| {
+                            line.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                        })
+                        .unwrap_or(0);
+                    let head_len = request.find("\r\n\r\n").map_or(0, |pos| pos + 4);
+                    let mut received = request.len() - head_len;
+                    while received < length {
+                        match stream.read(&mut buf) {
+                            Ok(0) | Err(_) => break,
+                            Ok(len) => received += len,
+                        }
+                    }
+                    "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {body_size}\r\nConnection: close\r\n\r\n{}",
+                        "0".repeat(body_size)
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_measure_read_cap() {
+        let data = vec![0u8; 1024 * 1024];
+        let mut reader = futures::io::Cursor::new(data);
+        let cancelled = AtomicBool::new(false);
+        let report = measure_read(
+            &mut reader,
+            256 * 1024,
+            Duration::from_secs(5),
+            &cancelled,
+            &mut |_| {},
+        )
+        .await
+        .unwrap();
+        assert!(report.capped);
+        assert!(report.bytes >= 256 * 1024 && report.bytes < 1024 * 1024);
+        assert!(!report.samples.is_empty());
+
+        cancelled.store(true, Ordering::Release);
+        assert!(
+            measure_read(
+                &mut reader,
+                u64::MAX,
+                Duration::from_secs(5),
+                &cancelled,
+                &mut |_| {}
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bandwidth_with_local_server() {
+        let base = mock_server(512 * 1024);
+        let test = BandwidthTest {
+            options: BandwidthOptions {
+                download_url: format!("{base}/download"),
+                upload_url: Some(format!("{base}/upload")),
+                upload_bytes: Some(256 * 1024),
+                ..BandwidthOptions::default()
+            },
+            proxy_type: ProxyType::None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+        let report = test.run(|_| {}).await.unwrap();
+        let download = report.download.unwrap();
+        assert_eq!(download.bytes, 512 * 1024);
+        assert!(!download.capped);
+        assert_eq!(report.upload.unwrap().bytes, 256 * 1024);
+        assert!(!report.cancelled);
+    }
+}
@@ -96,42 +96,6 @@ pub async fn change_clash_mode(mode: String) {
 
 /// Test connection delay to a URL
 pub async fn test_delay(url: String) -> anyhow::Result<u32> {
-    use crate::utils::network::{NetworkManager, ProxyType};
-    use tokio::time::Instant;
-
-    let tun_mode = Config::verge()
-        .await
-        .latest_ref()
-        .enable_tun_mode
-        .unwrap_or(false);
-
-    // 如果是TUN模式，不使用代理，否则使用自身代理
-    let proxy_type = if !tun_mode {
-        ProxyType::Localhost
-    } else {
-        ProxyType::None
-    };
-
-    let user_agent = Some("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0".to_string());
-
-    let start = Instant::now();
-
-    let response = NetworkManager::new()
-        .get_with_interrupt(&url, proxy_type, Some(10), user_agent, false)
-        .await;
-
-    match response {
-        Ok(response) => {
-            log::trace!(target: "app", "test_delay response: {response:#?}");
-            if response.status().is_success() {
-                Ok(start.elapsed().as_millis() as u32)
-            } else {
-                Ok(10000u32)
-            }
-        }
-        Err(err) => {
-            log::trace!(target: "app", "test_delay error: {err:#?}");
-            Err(err)
-        }
-    }
+    let report = super::test_latency(url, Some(1)).await?;
+    Ok(report.min)
 }
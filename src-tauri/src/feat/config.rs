@@ -1,10 +1,11 @@
 use crate::{
-    config::{Config, IVerge},
-    core::{CoreManager, handle, hotkey, sysopt, tray},
+    config::{Config, IVerge, validate_bypass, validate_bypass_str},
+    core::{CoreManager, Timer, handle, hotkey, pac, sysopt, tray},
+    enhance::validate_tun_filter,
     feat::config_flags::{UpdateFlags, analyze_patch},
     logging, logging_error,
     module::lightweight,
-    utils::logging::Type,
+    utils::{lan_server::LanServer, logging::Type},
 };
 use anyhow::Result;
 use serde_yaml_ng::Mapping;
@@ -15,6 +16,7 @@ pub async fn patch_clash(patch: Mapping) -> Result<()> {
         .await
         .draft_mut()
         .patch_config(patch.clone());
+    let allow_lan_changed = patch.get("allow-lan").is_some();
 
     let res = {
         // 激活订阅
@@ -38,6 +40,9 @@ pub async fn patch_clash(patch: Mapping) -> Result<()> {
             // 分离数据获取和异步调用
             let clash_data = Config::clash().await.data_mut().clone();
             clash_data.save_config().await?;
+            if allow_lan_changed {
+                LanServer::global().restart().await;
+            }
             Ok(())
         }
         Err(err) => {
@@ -51,6 +56,25 @@ pub async fn patch_clash(patch: Mapping) -> Result<()> {
 
 /// Patch Verge configuration
 pub async fn patch_verge(patch: IVerge, not_save_file: bool) -> Result<()> {
+    if let Some(bypass) = &patch.system_proxy_bypass {
+        validate_bypass_str(bypass)?;
+    }
+    if let Some(entries) = &patch.bypass_entries {
+        validate_bypass(entries)?;
+    }
+    if let Some(filter) = &patch.tun_filter {
+        validate_tun_filter(filter)?;
+    }
+    if let Some(hotkeys) = &patch.hotkeys {
+        let current = Config::verge()
+            .await
+            .latest_ref()
+            .hotkeys
+            .clone()
+            .unwrap_or_default();
+        hotkey::Hotkey::validate(&current, hotkeys).await?;
+    }
+
     Config::verge()
         .await
         .draft_mut()
@@ -87,6 +111,18 @@ pub async fn patch_verge(patch: IVerge, not_save_file: bool) -> Result<()> {
         if update_flags.contains(UpdateFlags::SYS_PROXY) {
             sysopt::Sysopt::global().update_sysproxy().await?;
         }
+        if update_flags.contains(UpdateFlags::PAC_FILE) {
+            pac::PacManager::global().refresh().await;
+        }
+        if update_flags.contains(UpdateFlags::LAN_SERVER) {
+            LanServer::global().restart().await;
+        }
+        if update_flags.contains(UpdateFlags::UNLOCK_RECHECK) {
+            Timer::global().refresh_unlock_recheck_task().await?;
+        }
+        if update_flags.contains(UpdateFlags::DELAY_PROBE) {
+            Timer::global().refresh_delay_probe_task().await?;
+        }
         if update_flags.contains(UpdateFlags::HOTKEY)
             && let Some(hotkeys) = patch.hotkeys
         {
@@ -97,6 +133,7 @@ pub async fn patch_verge(patch: IVerge, not_save_file: bool) -> Result<()> {
         }
         if update_flags.contains(UpdateFlags::SYSTRAY_ICON) {
             tray::Tray::global().update_icon().await?;
+            tray::speed_rate::SpeedRate::global().sync().await;
         }
         if update_flags.contains(UpdateFlags::SYSTRAY_TOOLTIP) {
             tray::Tray::global().update_tooltip().await?;
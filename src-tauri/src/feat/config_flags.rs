@@ -16,6 +16,10 @@ impl UpdateFlags {
     pub const SYSTRAY_TOOLTIP: Self = Self(1 << 8);
     pub const SYSTRAY_CLICK_BEHAVIOR: Self = Self(1 << 9);
     pub const LIGHT_WEIGHT: Self = Self(1 << 10);
+    pub const PAC_FILE: Self = Self(1 << 11);
+    pub const LAN_SERVER: Self = Self(1 << 12);
+    pub const UNLOCK_RECHECK: Self = Self(1 << 13);
+    pub const DELAY_PROBE: Self = Self(1 << 14);
 
     pub const fn empty() -> Self {
         Self(0)
@@ -52,6 +56,11 @@ pub fn analyze_patch(patch: &IVerge) -> UpdateFlags {
             | UpdateFlags::SYSTRAY_ICON;
     }
 
+    // TUN 包含/排除设置与解锁分组需要重新生成运行时配置
+    if patch.tun_filter.is_some() || patch.unlock_groups.is_some() {
+        flags |= UpdateFlags::CLASH_CONFIG;
+    }
+
     // 全局热键和首页卡片
     if patch.enable_global_hotkey.is_some() || patch.home_cards.is_some() {
         flags |= UpdateFlags::VERGE_CONFIG;
@@ -93,12 +102,36 @@ pub fn analyze_patch(patch: &IVerge) -> UpdateFlags {
 
     // 代理配置
     if patch.system_proxy_bypass.is_some()
+        || patch.bypass_entries.is_some()
+        || patch.bypass_from_rules.is_some()
         || patch.pac_file_content.is_some()
         || patch.proxy_auto_config.is_some()
+        || patch.linux_proxy.is_some()
+        || patch.tool_proxy.is_some()
     {
         flags |= UpdateFlags::SYS_PROXY;
     }
 
+    // PAC 脚本内容
+    if patch.pac_file_content.is_some() || patch.pac_from_rules.is_some() {
+        flags |= UpdateFlags::PAC_FILE;
+    }
+
+    // 局域网 PAC 服务
+    if patch.lan_pac_server.is_some() {
+        flags |= UpdateFlags::LAN_SERVER;
+    }
+
+    // 解锁检测定时复检
+    if patch.unlock_recheck_interval.is_some() {
+        flags |= UpdateFlags::UNLOCK_RECHECK;
+    }
+
+    // 后台节点延迟探测
+    if patch.enable_auto_delay_detection.is_some() || patch.delay_probe_interval.is_some() {
+        flags |= UpdateFlags::DELAY_PROBE;
+    }
+
     // 语言设置
     if patch.language.is_some() {
         flags |= UpdateFlags::SYSTRAY_MENU;
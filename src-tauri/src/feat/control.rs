@@ -0,0 +1,284 @@
+use crate::{
+    cmd,
+    config::{Config, IVerge},
+    core::{CoreManager, handle, tun_manager::TunManager},
+    logging,
+    utils::logging::Type,
+};
+use anyhow::{Result, anyhow, bail};
+use serde::Serialize;
+use serde_yaml_ng::Mapping;
+
+/// 可通过外部控制切换的 Clash 模式
+pub const CONTROL_CLASH_MODES: &[&str] = &["rule", "global", "direct"];
+
+/// 控制 API 令牌的随机字节数
+const CONTROL_API_TOKEN_BYTES: usize = 32;
+
+/// 应用运行状态摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct AppStatus {
+    pub version: String,
+    pub running_mode: String,
+    pub clash_mode: Option<String>,
+    pub current_profile: Option<String>,
+    pub current_profile_name: Option<String>,
+    pub system_proxy: bool,
+    pub tun_mode: bool,
+    pub mixed_port: u16,
+}
+
+/// 订阅列表条目
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSummary {
+    pub uid: String,
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub itype: Option<String>,
+    pub url: Option<String>,
+    pub updated: Option<usize>,
+    pub current: bool,
+}
+
+/// 订阅更新结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileUpdateResult {
+    pub uid: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// 获取应用状态
+pub async fn get_status() -> AppStatus {
+    let (system_proxy, tun_mode, mixed_port) = {
+        let verge = Config::verge().await;
+        let verge = verge.latest_ref();
+        (
+            verge.enable_system_proxy.unwrap_or(false),
+            verge.enable_tun_mode.unwrap_or(false),
+            verge.verge_mixed_port,
+        )
+    };
+    let (clash_mode, clash_mixed_port) = {
+        let clash = Config::clash().await;
+        let clash = clash.latest_ref();
+        (
+            clash
+                .0
+                .get("mode")
+                .and_then(|mode| mode.as_str())
+                .map(String::from),
+            clash.get_mixed_port(),
+        )
+    };
+    let (current_profile, current_profile_name) = {
+        let profiles = Config::profiles().await;
+        let profiles = profiles.latest_ref();
+        let current = profiles.get_current();
+        let name = current
+            .as_ref()
+            .and_then(|uid| profiles.get_item(uid).ok())
+            .and_then(|item| item.name.clone());
+        (current, name)
+    };
+
+    AppStatus {
+        version: env!("CARGO_PKG_VERSION").into(),
+        running_mode: CoreManager::global().get_running_mode().to_string(),
+        clash_mode,
+        current_profile,
+        current_profile_name,
+        system_proxy,
+        tun_mode,
+        mixed_port: mixed_port.unwrap_or(clash_mixed_port),
+    }
+}
+
+/// 列出所有订阅（不含 merge/script 等增强项）
+pub async fn list_profiles() -> Vec<ProfileSummary> {
+    let profiles = Config::profiles().await;
+    let profiles = profiles.latest_ref();
+    let current = profiles.get_current();
+
+    profiles
+        .get_items()
+        .map(|items| {
+            items
+                .iter()
+                .filter(|item| matches!(item.itype.as_deref(), Some("remote") | Some("local")))
+                .filter_map(|item| {
+                    let uid = item.uid.clone()?;
+                    Some(ProfileSummary {
+                        current: current.as_ref() == Some(&uid),
+                        uid,
+                        name: item.name.clone(),
+                        itype: item.itype.clone(),
+                        url: item.url.clone(),
+                        updated: item.updated,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 切换到指定订阅
+pub async fn select_profile(uid: String) -> Result<()> {
+    {
+        let profiles = Config::profiles().await;
+        profiles.latest_ref().get_item(&uid)?;
+    }
+
+    logging!(info, Type::Cmd, "外部控制切换订阅: {}", uid);
+    match cmd::patch_profiles_config_by_profile_index(uid.clone()).await {
+        Ok(true) => Ok(()),
+        Ok(false) => bail!("failed to activate profile {uid}"),
+        Err(err) => Err(anyhow!(err)),
+    }
+}
+
+/// 更新指定订阅，`uid` 为空时更新所有远程订阅
+pub async fn update_profiles(uid: Option<String>) -> Result<Vec<ProfileUpdateResult>> {
+    let targets = match uid {
+        Some(uid) => {
+            let profiles = Config::profiles().await;
+            profiles.latest_ref().get_item(&uid)?;
+            vec![uid]
+        }
+        None => list_profiles()
+            .await
+            .into_iter()
+            .filter(|profile| profile.itype.as_deref() == Some("remote"))
+            .map(|profile| profile.uid)
+            .collect(),
+    };
+
+    let mut results = Vec::with_capacity(targets.len());
+    for uid in targets {
+        logging!(info, Type::Cmd, "外部控制更新订阅: {}", uid);
+        let result = super::update_profile(uid.clone(), None, Some(true)).await;
+        results.push(ProfileUpdateResult {
+            uid,
+            success: result.is_ok(),
+            error: result.err().map(|err| err.to_string()),
+        });
+    }
+    Ok(results)
+}
+
+/// 切换 Clash 模式
+pub async fn set_clash_mode(mode: String) -> Result<()> {
+    let mode = mode.to_lowercase();
+    if !CONTROL_CLASH_MODES.contains(&mode.as_str()) {
+        bail!(
+            "invalid clash mode: {mode}, expected one of {}",
+            CONTROL_CLASH_MODES.join("/")
+        );
+    }
+    if handle::Handle::try_app_handle().is_none() {
+        // 无界面守护模式没有控制器客户端，写入配置后重新加载内核
+        let mut mapping = Mapping::new();
+        mapping.insert("mode".into(), mode.into());
+        Config::clash().await.data_mut().patch_config(mapping);
+        let clash_data = Config::clash().await.data_mut().clone();
+        clash_data.save_config().await?;
+        CoreManager::global().update_config().await?;
+        return Ok(());
+    }
+    super::change_clash_mode(mode).await;
+    Ok(())
+}
+
+/// 重启内核
+pub async fn restart_core() -> Result<()> {
+    CoreManager::global().restart_core().await?;
+    handle::Handle::refresh_clash();
+    Ok(())
+}
+
+/// 设置系统代理开关
+pub async fn set_system_proxy(enable: bool) -> Result<()> {
+    let current = { Config::verge().await.latest_ref().enable_system_proxy }.unwrap_or(false);
+    if current == enable {
+        return Ok(());
+    }
+
+    logging!(info, Type::Cmd, "外部控制设置系统代理: {}", enable);
+    super::patch_verge(
+        IVerge {
+            enable_system_proxy: Some(enable),
+            ..IVerge::default()
+        },
+        false,
+    )
+    .await?;
+    handle::Handle::refresh_verge();
+    Ok(())
+}
+
+/// 设置 TUN 模式开关
+pub async fn set_tun_mode(enable: bool) -> Result<()> {
+    let current = { Config::verge().await.latest_ref().enable_tun_mode }.unwrap_or(false);
+    if current == enable {
+        return Ok(());
+    }
+    if enable {
+        TunManager::can_enable_tun().await?;
+    }
+
+    logging!(info, Type::Cmd, "外部控制设置 TUN 模式: {}", enable);
+    super::patch_verge(
+        IVerge {
+            enable_tun_mode: Some(enable),
+            ..IVerge::default()
+        },
+        false,
+    )
+    .await?;
+    handle::Handle::refresh_verge();
+    Ok(())
+}
+
+/// 重新生成运行时配置并应用
+pub async fn trigger_enhance() -> Result<()> {
+    logging!(info, Type::Cmd, "外部控制触发配置增强");
+    super::enhance_profiles().await?;
+    handle::Handle::refresh_clash();
+    Ok(())
+}
+
+fn generate_control_api_token() -> Result<String> {
+    let mut bytes = [0u8; CONTROL_API_TOKEN_BYTES];
+    getrandom::fill(&mut bytes)?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// 确保控制 API 令牌存在，首次启动时自动生成
+pub async fn ensure_control_api_token() -> Result<()> {
+    let exists = Config::verge()
+        .await
+        .latest_ref()
+        .control_api_token
+        .as_ref()
+        .is_some_and(|token| !token.is_empty());
+    if exists {
+        return Ok(());
+    }
+
+    regenerate_control_api_token().await.map(|_| ())
+}
+
+/// 重新生成控制 API 令牌，旧令牌立即失效
+pub async fn regenerate_control_api_token() -> Result<String> {
+    let token = generate_control_api_token()?;
+    super::patch_verge(
+        IVerge {
+            control_api_token: Some(token.clone()),
+            ..IVerge::default()
+        },
+        false,
+    )
+    .await?;
+    logging!(info, Type::Config, "控制 API 令牌已更新");
+    Ok(token)
+}
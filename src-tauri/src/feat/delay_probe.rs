@@ -0,0 +1,381 @@
+//! 后台节点延迟探测
+//!
+//! 定时通过内核对全部节点测速并保留每个节点最近的结果，据此计算由延迟、抖动与失败率
+//! 组成的稳定性评分，供界面和托盘查询。探测记录保存在应用目录，首次访问时在锁外异步加载。
+
+use std::{
+    cmp::Ordering as CmpOrdering,
+    collections::{HashMap, VecDeque},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::Result;
+use futures::{StreamExt, stream};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::{
+    config::{Config, IVerge},
+    core::handle,
+    logging,
+    module::lightweight::is_in_lightweight_mode,
+    singleton_lazy,
+    utils::{dirs, help, logging::Type},
+};
+
+use super::latency::{average, round};
+
+const HISTORY_FILE: &str = "delay_history.yaml";
+/// 默认探测间隔（分钟）
+pub const DEFAULT_DELAY_PROBE_INTERVAL: u64 = 10;
+/// 每个节点保留的探测结果数量
+const HISTORY_CAPACITY: usize = 60;
+/// 同时测速的节点数量
+const MAX_CONCURRENCY: usize = 8;
+/// 平均延迟达到此值时延迟得分为 0
+const LATENCY_CEILING_MS: f64 = 1000.0;
+/// 抖动达到此值时抖动得分为 0
+const JITTER_CEILING_MS: f64 = 300.0;
+const LATENCY_WEIGHT: f64 = 0.7;
+/// 内核内置的非代理出站，不参与探测
+const BUILTIN_OUTBOUNDS: [&str; 5] = ["DIRECT", "REJECT", "REJECT-DROP", "PASS", "COMPATIBLE"];
+
+/// 一次探测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelaySample {
+    /// 毫秒时间戳
+    pub time: i64,
+    /// 延迟（毫秒），超时或失败时为空
+    pub delay: Option<u32>,
+}
+
+/// 节点的稳定性统计，时间单位为毫秒
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeStability {
+    pub node: String,
+    pub samples: u32,
+    pub failures: u32,
+    /// 失败率（百分比）
+    pub failure_rate: f64,
+    pub avg: Option<f64>,
+    /// 相邻两次成功探测差值的平均值
+    pub jitter: Option<f64>,
+    pub last: Option<u32>,
+    /// 0 - 100，越高越稳定
+    pub score: u8,
+}
+
+impl NodeStability {
+    fn from_history(node: &str, history: &VecDeque<DelaySample>) -> Option<Self> {
+        if history.is_empty() {
+            return None;
+        }
+        let delays = history
+            .iter()
+            .filter_map(|sample| sample.delay)
+            .map(f64::from)
+            .collect::<Vec<_>>();
+        let samples = history.len() as u32;
+        let failures = samples - delays.len() as u32;
+        let success_rate = delays.len() as f64 / f64::from(samples);
+        let avg = average(delays.iter().copied());
+        let jitter = avg.map(|_| {
+            average(delays.windows(2).map(|pair| (pair[1] - pair[0]).abs())).unwrap_or(0.0)
+        });
+
+        let score = match (avg, jitter) {
+            (Some(avg), Some(jitter)) => {
+                let latency_score = (1.0 - avg / LATENCY_CEILING_MS).clamp(0.0, 1.0);
+                let jitter_score = (1.0 - jitter / JITTER_CEILING_MS).clamp(0.0, 1.0);
+                let quality =
+                    LATENCY_WEIGHT * latency_score + (1.0 - LATENCY_WEIGHT) * jitter_score;
+                (100.0 * success_rate * quality).round() as u8
+            }
+            _ => 0,
+        };
+
+        Some(Self {
+            node: node.to_string(),
+            samples,
+            failures,
+            failure_rate: round((1.0 - success_rate) * 100.0),
+            avg: avg.map(round),
+            jitter: jitter.map(round),
+            last: history.back().and_then(|sample| sample.delay),
+            score,
+        })
+    }
+}
+
+type History = HashMap<String, VecDeque<DelaySample>>;
+
+/// 探测结束、出错或任务被取消时清除运行标记
+struct RunningGuard<'a>(&'a AtomicBool);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DelayProber {
+    /// 首次访问时从文件加载
+    history: Mutex<Option<History>>,
+    running: AtomicBool,
+}
+
+singleton_lazy!(DelayProber, DELAY_PROBER, DelayProber::default);
+
+impl DelayProber {
+    async fn load() -> History {
+        let Ok(path) = dirs::app_home_dir().map(|dir| dir.join(HISTORY_FILE)) else {
+            return History::new();
+        };
+        if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            return History::new();
+        }
+        help::read_yaml(&path).await.unwrap_or_else(|e| {
+            logging!(warn, Type::Network, "读取节点延迟记录失败: {}", e);
+            History::new()
+        })
+    }
+
+    /// 首次访问前加载探测记录，读取与解析都不持有锁
+    async fn ensure_loaded(&self) {
+        if self.history.lock().is_some() {
+            return;
+        }
+        let loaded = Self::load().await;
+        self.history.lock().get_or_insert(loaded);
+    }
+
+    async fn save(history: &History) {
+        let result = match dirs::app_home_dir() {
+            Ok(dir) => {
+                help::save_yaml(
+                    &dir.join(HISTORY_FILE),
+                    history,
+                    Some("# Node delay probe history"),
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            logging!(warn, Type::Network, "保存节点延迟记录失败: {}", e);
+        }
+    }
+
+    /// 访问已加载的探测记录，调用前需先 `ensure_loaded`
+    fn with_history<R>(&self, f: impl FnOnce(&mut History) -> R) -> R {
+        let mut history = self.history.lock();
+        f(history.get_or_insert_with(History::new))
+    }
+
+    fn record(&self, results: Vec<(String, Option<u32>)>) {
+        let time = chrono::Local::now().timestamp_millis();
+        self.with_history(|history| {
+            for (node, delay) in results {
+                let samples = history.entry(node).or_default();
+                if samples.len() >= HISTORY_CAPACITY {
+                    samples.pop_front();
+                }
+                samples.push_back(DelaySample { time, delay });
+            }
+        });
+    }
+
+    /// 节点的探测记录，按时间升序
+    pub async fn history(&self, node: &str) -> Vec<DelaySample> {
+        self.ensure_loaded().await;
+        self.with_history(|history| {
+            history
+                .get(node)
+                .map(|samples| samples.iter().copied().collect())
+                .unwrap_or_default()
+        })
+    }
+
+    /// 全部节点的稳定性，按评分降序
+    pub async fn stabilities(&self) -> Vec<NodeStability> {
+        self.ensure_loaded().await;
+        let mut result = self.with_history(|history| {
+            history
+                .iter()
+                .filter_map(|(node, samples)| NodeStability::from_history(node, samples))
+                .collect::<Vec<_>>()
+        });
+        result.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.node.cmp(&b.node)));
+        result
+    }
+
+    /// 按评分降序排列节点，没有探测记录的节点保持原有顺序排在最后
+    pub async fn sort_by_score(&self, nodes: &[String]) -> Vec<String> {
+        self.ensure_loaded().await;
+        self.with_history(|history| {
+            let score = |node: &String| {
+                history
+                    .get(node)
+                    .and_then(|samples| NodeStability::from_history(node, samples))
+                    .map(|stability| stability.score)
+            };
+            let mut sorted = nodes.to_vec();
+            sorted.sort_by(|a, b| match (score(a), score(b)) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (Some(_), None) => CmpOrdering::Less,
+                (None, Some(_)) => CmpOrdering::Greater,
+                (None, None) => CmpOrdering::Equal,
+            });
+            sorted
+        })
+    }
+
+    /// 对全部节点测速一轮，轻量模式下跳过
+    pub async fn probe_all(&self) -> Result<()> {
+        if is_in_lightweight_mode() {
+            logging!(debug, Type::Network, "轻量模式下跳过节点延迟探测");
+            return Ok(());
+        }
+        // 无界面守护模式没有控制器客户端
+        if handle::Handle::try_app_handle().is_none() {
+            return Ok(());
+        }
+        if self.running.swap(true, Ordering::AcqRel) {
+            logging!(debug, Type::Network, "节点延迟探测正在进行，跳过本次");
+            return Ok(());
+        }
+        {
+            let _guard = RunningGuard(&self.running);
+            self.probe_nodes().await?;
+        }
+
+        let _ = handle::Handle::app_handle().emit("verge://delay-probe-updated", ());
+        self.sort_favorites().await
+    }
+
+    async fn probe_nodes(&self) -> Result<()> {
+        let (url, timeout) = super::proxy::latency_test_settings().await;
+        let proxies = handle::Handle::mihomo().await.get_proxies().await?;
+        let nodes = proxies
+            .proxies
+            .iter()
+            .filter(|(name, proxy)| {
+                proxy.all.is_none() && !BUILTIN_OUTBOUNDS.contains(&name.as_str())
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        logging!(
+            debug,
+            Type::Network,
+            "开始节点延迟探测，共 {} 个节点",
+            nodes.len()
+        );
+
+        let results = stream::iter(nodes)
+            .map(|node| {
+                let url = url.as_str();
+                async move {
+                    let delay = handle::Handle::mihomo()
+                        .await
+                        .delay_proxy_by_name(&node, url, timeout)
+                        .await
+                        .ok()
+                        .map(|result| result.delay)
+                        .filter(|delay| *delay > 0);
+                    (node, delay)
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+        self.ensure_loaded().await;
+        self.record(results);
+        let snapshot = self.with_history(|history| history.clone());
+        Self::save(&snapshot).await;
+        Ok(())
+    }
+
+    /// 开启后按稳定性重新排列收藏的节点
+    async fn sort_favorites(&self) -> Result<()> {
+        let favorites = {
+            let verge = Config::verge().await;
+            let verge = verge.latest_ref();
+            if !verge.sort_favorites_by_stability.unwrap_or(false) {
+                return Ok(());
+            }
+            verge.favorite_proxies.clone().unwrap_or_default()
+        };
+        let sorted = self.sort_by_score(&favorites).await;
+        if sorted == favorites {
+            return Ok(());
+        }
+
+        super::patch_verge(
+            IVerge {
+                favorite_proxies: Some(sorted),
+                ..IVerge::default()
+            },
+            false,
+        )
+        .await?;
+        handle::Handle::refresh_verge();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn history(delays: &[Option<u32>]) -> VecDeque<DelaySample> {
+        delays
+            .iter()
+            .map(|&delay| DelaySample { time: 0, delay })
+            .collect()
+    }
+
+    #[test]
+    fn test_stability_score() {
+        assert_eq!(NodeStability::from_history("A", &VecDeque::new()), None);
+
+        let stable = NodeStability::from_history("A", &history(&[Some(100), Some(100)])).unwrap();
+        assert_eq!((stable.failures, stable.failure_rate), (0, 0.0));
+        assert_eq!((stable.avg, stable.jitter), (Some(100.0), Some(0.0)));
+        // 0.7 * 0.9 + 0.3 * 1.0
+        assert_eq!(stable.score, 93);
+
+        let flaky =
+            NodeStability::from_history("B", &history(&[Some(100), None, Some(400)])).unwrap();
+        assert_eq!(flaky.failures, 1);
+        assert_eq!(flaky.failure_rate, 33.3);
+        assert_eq!((flaky.avg, flaky.jitter), (Some(250.0), Some(300.0)));
+        assert_eq!(flaky.last, Some(400));
+        assert!(flaky.score < stable.score);
+
+        let down = NodeStability::from_history("C", &history(&[None, None])).unwrap();
+        assert_eq!((down.score, down.avg, down.failure_rate), (0, None, 100.0));
+    }
+
+    #[tokio::test]
+    async fn test_sort_by_score() {
+        let prober = DelayProber {
+            history: Mutex::new(Some(History::new())),
+            ..DelayProber::default()
+        };
+        prober.record(vec![
+            ("slow".into(), Some(800)),
+            ("fast".into(), Some(50)),
+            ("down".into(), None),
+        ]);
+        let nodes = ["new", "down", "slow", "fast", "other"].map(String::from);
+        assert_eq!(
+            prober.sort_by_score(&nodes).await,
+            ["fast", "slow", "down", "new", "other"]
+        );
+        assert_eq!(prober.history("fast").await.len(), 1);
+        assert_eq!(prober.stabilities().await[0].node, "fast");
+    }
+}
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+use crate::{
+    config::Config,
+    utils::network::{NetworkManager, ProxyType, RequestTiming},
+};
+
+const DEFAULT_SAMPLES: u32 = 5;
+const MAX_SAMPLES: u32 = 20;
+const SAMPLE_TIMEOUT_SECS: u64 = 10;
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0";
+
+/// 多次采样的延迟统计，时间单位为毫秒
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LatencyReport {
+    pub url: String,
+    pub samples: u32,
+    pub received: u32,
+    /// 丢包率（百分比）
+    pub loss: f64,
+    pub min: u32,
+    pub max: u32,
+    pub avg: f64,
+    pub median: f64,
+    pub p95: u32,
+    /// 相邻两次成功采样差值的平均值
+    pub jitter: f64,
+    /// 以下为各阶段平均耗时，无法测量时为空
+    pub dns: Option<f64>,
+    pub tcp: Option<f64>,
+    pub tls: Option<f64>,
+    pub ttfb: Option<f64>,
+    /// 失败采样的错误信息
+    pub errors: Vec<String>,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+pub(super) fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0u32), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / f64::from(count))
+}
+
+pub(super) fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// 汇总采样结果，全部失败时返回第一个错误
+pub fn summarize(url: &str, results: Vec<Result<RequestTiming>>) -> Result<LatencyReport> {
+    let samples = results.len() as u32;
+    let mut timings = Vec::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(timing) => timings.push(timing),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    if timings.is_empty() {
+        return Err(errors
+            .into_iter()
+            .next()
+            .map_or_else(|| anyhow!("no latency samples"), |e| anyhow!(e)));
+    }
+
+    let totals = timings
+        .iter()
+        .map(|timing| millis(timing.total))
+        .collect::<Vec<_>>();
+    let mut sorted = totals.clone();
+    sorted.sort_by(f64::total_cmp);
+    let count = sorted.len();
+    let median = if count % 2 == 0 {
+        (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+    } else {
+        sorted[count / 2]
+    };
+    // 最近秩法
+    let p95 = sorted[(count * 95).div_ceil(100).saturating_sub(1)];
+    let jitter = average(totals.windows(2).map(|pair| (pair[1] - pair[0]).abs())).unwrap_or(0.0);
+    let phase = |select: fn(&RequestTiming) -> Option<Duration>| {
+        average(timings.iter().filter_map(select).map(millis)).map(round)
+    };
+
+    let received = count as u32;
+    Ok(LatencyReport {
+        url: url.to_string(),
+        samples,
+        received,
+        loss: round(f64::from(samples - received) * 100.0 / f64::from(samples)),
+        min: sorted[0].round() as u32,
+        max: sorted[count - 1].round() as u32,
+        avg: round(average(totals.iter().copied()).unwrap_or(0.0)),
+        median: round(median),
+        p95: p95.round() as u32,
+        jitter: round(jitter),
+        dns: phase(|timing| timing.dns),
+        tcp: phase(|timing| timing.tcp),
+        tls: phase(|timing| timing.tls),
+        ttfb: phase(|timing| timing.ttfb),
+        errors,
+    })
+}
+
+/// 对 URL 进行多次延迟采样
+pub async fn test_latency(url: String, samples: Option<u32>) -> Result<LatencyReport> {
+    let tun_mode = Config::verge()
+        .await
+        .latest_ref()
+        .enable_tun_mode
+        .unwrap_or(false);
+
+    // 如果是TUN模式，不使用代理，否则使用自身代理
+    let proxy_type = if !tun_mode {
+        ProxyType::Localhost
+    } else {
+        ProxyType::None
+    };
+
+    let samples = samples.unwrap_or(DEFAULT_SAMPLES).clamp(1, MAX_SAMPLES);
+    let manager = NetworkManager::new();
+    let mut results = Vec::with_capacity(samples as usize);
+    for _ in 0..samples {
+        let result = manager
+            .probe_timing(
+                &url,
+                proxy_type,
+                Some(SAMPLE_TIMEOUT_SECS),
+                Some(USER_AGENT.into()),
+            )
+            .await;
+        if let Err(err) = &result {
+            log::trace!(target: "app", "test_latency error: {err:#?}");
+        }
+        results.push(result);
+    }
+
+    summarize(&url, results)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn timing(total: u64, dns: Option<u64>) -> Result<RequestTiming> {
+        Ok(RequestTiming {
+            total: Duration::from_millis(total),
+            dns: dns.map(Duration::from_millis),
+            ..RequestTiming::default()
+        })
+    }
+
+    #[test]
+    fn test_summarize() {
+        let report = summarize(
+            "http://test",
+            vec![
+                timing(100, Some(10)),
+                timing(140, None),
+                Err(anyhow!("timeout")),
+                timing(120, Some(20)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(report.samples, 4);
+        assert_eq!(report.received, 3);
+        assert_eq!(report.loss, 25.0);
+        assert_eq!((report.min, report.max, report.p95), (100, 140, 140));
+        assert_eq!(report.avg, 120.0);
+        assert_eq!(report.median, 120.0);
+        assert_eq!(report.jitter, 30.0);
+        assert_eq!(report.dns, Some(15.0));
+        assert_eq!(report.tls, None);
+        assert_eq!(report.errors, ["timeout"]);
+    }
+
+    #[test]
+    fn test_summarize_all_failed() {
+        let err = summarize("http://test", vec![Err(anyhow!("refused"))]).unwrap_err();
+        assert_eq!(err.to_string(), "refused");
+        assert!(summarize("http://test", Vec::new()).is_err());
+    }
+}
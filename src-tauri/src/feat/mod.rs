@@ -1,15 +1,23 @@
 mod backup;
+mod bandwidth;
 mod clash;
 mod config;
 mod config_flags;
+mod control;
+mod delay_probe;
+mod latency;
 mod profile;
 mod proxy;
 mod window;
 
 // Re-export all functions from modules
 pub use backup::*;
+pub use bandwidth::*;
 pub use clash::*;
 pub use config::*;
+pub use control::*;
+pub use delay_probe::*;
+pub use latency::*;
 pub use profile::*;
 pub use proxy::*;
 pub use window::*;
@@ -22,6 +22,39 @@ pub async fn toggle_proxy_profile(profile_index: String) {
     }
 }
 
+/// Switch to the previous or next profile in list order, returns the profile name
+pub async fn switch_adjacent_profile(forward: bool) -> Option<String> {
+    let (uid, name) = {
+        let profiles = Config::profiles().await;
+        let profiles = profiles.latest_ref();
+        // 只在订阅（远程/本地）之间切换，跳过扩展脚本等
+        let items: Vec<(String, String)> = profiles
+            .get_items()?
+            .iter()
+            .filter(|item| matches!(item.itype.as_deref(), Some("remote" | "local")))
+            .filter_map(|item| Some((item.uid.clone()?, item.name.clone().unwrap_or_default())))
+            .collect();
+        let current = profiles.get_current();
+        let len = items.len();
+        let index = match items
+            .iter()
+            .position(|(uid, _)| current.as_ref() == Some(uid))
+        {
+            Some(index) if forward => (index + 1) % len,
+            Some(index) => (index + len - 1) % len,
+            None => 0,
+        };
+        let target = items.into_iter().nth(index)?;
+        if current.as_ref() == Some(&target.0) {
+            return None;
+        }
+        target
+    };
+
+    toggle_proxy_profile(uid).await;
+    Some(name)
+}
+
 /// Update a profile
 /// If updating current profile, activate it
 /// auto_refresh: 是否自动更新配置和刷新前端
@@ -169,6 +202,17 @@ pub async fn update_profile(
         }
     }
 
+    // 托盘显示当前订阅的用量与更新时间
+    let is_current = Config::profiles().await.latest_ref().get_current() == Some(uid);
+    if is_current {
+        if let Err(err) = tray::Tray::global().update_tooltip().await {
+            logging!(error, Type::Tray, "更新托盘提示失败: {}", err);
+        }
+        if let Err(err) = tray::Tray::global().update_menu().await {
+            logging!(error, Type::Tray, "更新菜单失败: {}", err);
+        }
+    }
+
     Ok(())
 }
 
@@ -2,9 +2,14 @@ use crate::{
     config::{Config, IVerge},
     core::handle,
 };
+use anyhow::{Result, bail};
 use std::env;
+use tauri::Emitter;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
+const DEFAULT_LATENCY_TEST_URL: &str = "https://cp.cloudflare.com/generate_204";
+const DEFAULT_LATENCY_TIMEOUT: u32 = 10000;
+
 /// Toggle system proxy on/off
 pub async fn toggle_system_proxy() {
     // 获取当前系统代理状态
@@ -121,3 +126,58 @@ pub async fn copy_clash_env() {
         log::error!(target: "app", "Failed to write to clipboard");
     }
 }
+
+/// Select the next node of a proxy group, returns the selected node
+pub async fn select_next_node(group: &str) -> Result<String> {
+    let proxies = handle::Handle::mihomo().await.get_proxies().await?;
+    let Some(group_data) = proxies.proxies.get(group) else {
+        bail!("proxy group not found: {group}");
+    };
+    let all = group_data.all.as_deref().unwrap_or_default();
+    let now = group_data.now.as_deref().unwrap_or_default();
+    // 当前节点不在列表中时从第一个开始
+    let index = all
+        .iter()
+        .position(|name| name == now)
+        .map_or(0, |index| index + 1);
+    let Some(next) = all.get(index).or_else(|| all.first()).cloned() else {
+        bail!("proxy group {group} has no nodes");
+    };
+
+    handle::Handle::mihomo()
+        .await
+        .select_node_for_group(group, &next)
+        .await?;
+    let _ = handle::Handle::app_handle().emit("verge://refresh-proxy-config", ());
+    Ok(next)
+}
+
+/// Latency test url and timeout (ms) from the settings
+pub(super) async fn latency_test_settings() -> (String, u32) {
+    let verge = Config::verge().await;
+    let verge = verge.latest_ref();
+    (
+        verge
+            .default_latency_test
+            .clone()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_LATENCY_TEST_URL.into()),
+        verge
+            .default_latency_timeout
+            .and_then(|timeout| u32::try_from(timeout).ok())
+            .filter(|timeout| *timeout > 0)
+            .unwrap_or(DEFAULT_LATENCY_TIMEOUT),
+    )
+}
+
+/// Run a latency test for all nodes of a proxy group
+pub async fn test_group_delay(group: &str) -> Result<()> {
+    let (url, timeout) = latency_test_settings().await;
+
+    handle::Handle::mihomo()
+        .await
+        .delay_group(group, &url, timeout)
+        .await?;
+    let _ = handle::Handle::app_handle().emit("verge://refresh-proxy-config", ());
+    Ok(())
+}
@@ -134,6 +134,8 @@ async fn clean_async() -> bool {
                     let _ = autoproxy.set_auto_proxy();
                 }
 
+                crate::core::tool_proxy::ToolProxy::global().restore_all();
+
                 return true;
             }
 
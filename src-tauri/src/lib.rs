@@ -1,6 +1,7 @@
 #![allow(non_snake_case)]
 #![recursion_limit = "512"]
 
+pub mod cli;
 mod cmd;
 pub mod config;
 mod core;
@@ -167,6 +168,10 @@ mod app_init {
             cmd::sync_tun_status,
             cmd::reapply_tun_config,
             cmd::toggle_tun_mode,
+            cmd::check_core_capabilities,
+            cmd::tun_preflight,
+            cmd::grant_core_capabilities,
+            cmd::revoke_core_capabilities,
             // Clash core commands
             cmd::get_clash_info,
             cmd::patch_clash_config,
@@ -181,22 +186,37 @@ mod app_init {
             cmd::invoke_uwp_tool,
             cmd::copy_clash_env,
             cmd::sync_tray_proxy_selection,
+            cmd::get_tool_proxy_status,
             cmd::save_dns_config,
             cmd::apply_dns_config,
             cmd::check_dns_config_exists,
             cmd::get_dns_config_content,
             cmd::validate_dns_config,
             cmd::get_clash_logs,
+            cmd::query_clash_logs,
+            cmd::export_clash_logs,
+            cmd::clear_clash_logs,
             // Verge configuration
             cmd::get_verge_config,
             cmd::patch_verge_config,
+            cmd::regenerate_control_api_token,
             cmd::test_delay,
+            cmd::test_latency,
+            cmd::test_bandwidth,
+            cmd::cancel_bandwidth_test,
+            cmd::get_node_stability,
+            cmd::get_delay_history,
+            cmd::get_favorite_suggestions,
             cmd::get_app_dir,
             cmd::copy_icon_file,
             cmd::download_icon_cache,
             cmd::open_devtools,
             cmd::exit_app,
             cmd::get_network_interfaces_info,
+            cmd::get_network_snapshot,
+            cmd::get_network_switch_history,
+            cmd::get_proxy_guard_history,
+            cmd::clear_proxy_guard_history,
             // Profile management
             cmd::get_profiles,
             cmd::enhance_profiles,
@@ -228,9 +248,13 @@ mod app_init {
             // Diagnostics and system info
             cmd::export_diagnostic_info,
             cmd::get_system_info,
+            cmd::get_core_resource_history,
             // Media unlock checker
             cmd::get_unlock_items,
             cmd::check_media_unlock,
+            cmd::check_group_media_unlock,
+            cmd::cancel_group_media_unlock,
+            cmd::get_unlock_history,
         ]
     }
 }
@@ -11,5 +11,10 @@ fn main() {
         }
     }
 
+    // Dispatch CLI subcommands to the running instance
+    if let Some(code) = app_lib::cli::run(&args) {
+        std::process::exit(code);
+    }
+
     app_lib::run();
 }
@@ -0,0 +1,181 @@
+//! Linux 文件能力（file capabilities）支持
+//!
+//! 为内核二进制授予 `cap_net_admin,cap_net_bind_service` 后，
+//! 无需服务或 root 即可以普通用户运行 TUN。
+
+use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path, path::PathBuf, process::Command};
+
+use anyhow::{Result, bail};
+
+use crate::{
+    config::Config,
+    logging,
+    utils::{dirs, logging::Type},
+};
+
+const CAP_NET_BIND_SERVICE: u32 = 10;
+const CAP_NET_ADMIN: u32 = 12;
+
+/// TUN 所需的能力集合，setcap 参数格式
+const TUN_CAPABILITIES: &str = "cap_net_admin,cap_net_bind_service=+ep";
+
+const XATTR_NAME_CAPS: &str = "security.capability";
+const VFS_CAP_REVISION_MASK: u32 = 0xFF00_0000;
+const VFS_CAP_REVISION_1: u32 = 0x0100_0000;
+const VFS_CAP_REVISION_2: u32 = 0x0200_0000;
+const VFS_CAP_REVISION_3: u32 = 0x0300_0000;
+const VFS_CAP_FLAGS_EFFECTIVE: u32 = 0x0000_0001;
+
+/// 解析后的文件能力
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileCaps {
+    pub permitted: u64,
+    pub effective: bool,
+}
+
+impl FileCaps {
+    fn has(&self, cap: u32) -> bool {
+        self.permitted & (1u64 << cap) != 0
+    }
+
+    /// 是否具备运行 TUN 所需的能力
+    pub fn allows_tun(&self) -> bool {
+        self.effective && self.has(CAP_NET_ADMIN) && self.has(CAP_NET_BIND_SERVICE)
+    }
+}
+
+fn read_u32(raw: &[u8], offset: usize) -> Option<u32> {
+    raw.get(offset..offset + 4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u32::from_le_bytes)
+}
+
+/// 解析 `security.capability` 扩展属性（`struct vfs_cap_data`）
+pub fn parse_file_caps(raw: &[u8]) -> Option<FileCaps> {
+    let magic = read_u32(raw, 0)?;
+    let permitted_low = read_u32(raw, 4)?;
+    let permitted_high = match magic & VFS_CAP_REVISION_MASK {
+        VFS_CAP_REVISION_1 => 0,
+        VFS_CAP_REVISION_2 | VFS_CAP_REVISION_3 => read_u32(raw, 12)?,
+        _ => return None,
+    };
+    Some(FileCaps {
+        permitted: u64::from(permitted_high) << 32 | u64::from(permitted_low),
+        effective: magic & VFS_CAP_FLAGS_EFFECTIVE != 0,
+    })
+}
+
+/// 读取文件上的能力，未设置时返回 `None`
+pub fn read_file_caps(path: &Path) -> Option<FileCaps> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let name = CString::new(XATTR_NAME_CAPS).ok()?;
+    let mut buf = [0u8; 24];
+    let len = unsafe {
+        libc::getxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+        )
+    };
+    let len = usize::try_from(len).ok()?;
+    parse_file_caps(buf.get(..len)?)
+}
+
+/// 当前配置的内核二进制路径
+pub async fn core_binary_path() -> Result<PathBuf> {
+    let clash_core = Config::verge().await.latest_ref().get_valid_clash_core();
+    dirs::core_binary_path(&clash_core)
+}
+
+/// 当前内核二进制是否已具备 TUN 所需能力
+pub async fn core_has_tun_capabilities() -> bool {
+    match core_binary_path().await {
+        Ok(path) => read_file_caps(&path).is_some_and(|caps| caps.allows_tun()),
+        Err(_) => false,
+    }
+}
+
+async fn run_setcap(args: Vec<String>) -> Result<()> {
+    use users::get_effective_uid;
+
+    let elevator = crate::utils::help::linux_elevator();
+    let status = tokio::task::spawn_blocking(move || match get_effective_uid() {
+        0 => Command::new("setcap").args(&args).status(),
+        _ => Command::new(elevator).arg("setcap").args(&args).status(),
+    })
+    .await??;
+
+    if !status.success() {
+        bail!(
+            "failed to run setcap with status {}",
+            status.code().unwrap_or(-1)
+        );
+    }
+    Ok(())
+}
+
+/// 为内核二进制授予 TUN 所需能力
+pub async fn grant_tun_capabilities() -> Result<()> {
+    let path = core_binary_path().await?;
+    logging!(info, Type::Core, "为内核授予网络能力: {}", path.display());
+    run_setcap(vec![
+        TUN_CAPABILITIES.into(),
+        path.to_string_lossy().into_owned(),
+    ])
+    .await?;
+
+    if !read_file_caps(&path).is_some_and(|caps| caps.allows_tun()) {
+        bail!("capabilities were not applied to {}", path.display());
+    }
+    Ok(())
+}
+
+/// 移除内核二进制上的能力
+pub async fn revoke_tun_capabilities() -> Result<()> {
+    let path = core_binary_path().await?;
+    logging!(info, Type::Core, "移除内核网络能力: {}", path.display());
+    run_setcap(vec!["-r".into(), path.to_string_lossy().into_owned()]).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vfs_cap(magic: u32, permitted: [u32; 2]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(&magic.to_le_bytes());
+        raw.extend_from_slice(&permitted[0].to_le_bytes());
+        raw.extend_from_slice(&0u32.to_le_bytes());
+        raw.extend_from_slice(&permitted[1].to_le_bytes());
+        raw.extend_from_slice(&0u32.to_le_bytes());
+        raw
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_parse_tun_caps() {
+        let permitted = (1 << CAP_NET_ADMIN) | (1 << CAP_NET_BIND_SERVICE);
+        let raw = vfs_cap(VFS_CAP_REVISION_2 | VFS_CAP_FLAGS_EFFECTIVE, [permitted, 0]);
+        let caps = parse_file_caps(&raw).unwrap();
+        assert!(caps.allows_tun());
+    }
+
+    #[test]
+    fn test_parse_incomplete_caps() {
+        // 缺少 effective 标志
+        let permitted = (1 << CAP_NET_ADMIN) | (1 << CAP_NET_BIND_SERVICE);
+        let raw = vfs_cap(VFS_CAP_REVISION_2, [permitted, 0]);
+        assert!(!parse_file_caps(&raw).is_some_and(|caps| caps.allows_tun()));
+
+        // 仅有 cap_net_admin
+        let raw = vfs_cap(
+            VFS_CAP_REVISION_2 | VFS_CAP_FLAGS_EFFECTIVE,
+            [1 << CAP_NET_ADMIN, 0],
+        );
+        assert!(!parse_file_caps(&raw).is_some_and(|caps| caps.allows_tun()));
+
+        assert_eq!(parse_file_caps(&[0, 0, 0]), None);
+        assert_eq!(parse_file_caps(&vfs_cap(0x0400_0000, [0, 0])), None);
+    }
+}
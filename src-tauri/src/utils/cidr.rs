@@ -0,0 +1,122 @@
+//! IP 网段（CIDR）解析与匹配
+
+use std::{fmt, net::IpAddr, str::FromStr};
+
+use anyhow::{Result, anyhow, bail};
+
+/// 私有及本地网段
+pub const PRIVATE_CIDRS: &[&str] = &[
+    "127.0.0.0/8",
+    "10.0.0.0/8",
+    "172.16.0.0/12",
+    "192.168.0.0/16",
+    "169.254.0.0/16",
+    "::1/128",
+    "fc00::/7",
+    "fe80::/10",
+];
+
+/// 形如 `192.168.0.0/16`、`fd00::/8` 的网段，不带前缀时视为单个地址
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let (addr, prefix) = value
+            .split_once('/')
+            .map_or((value, None), |(addr, prefix)| (addr, Some(prefix)));
+        let addr: IpAddr = addr
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("invalid ip address: {value}"))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .trim()
+                .parse::<u8>()
+                .map_err(|_| anyhow!("invalid cidr prefix: {value}"))?,
+            None => max,
+        };
+        if prefix > max {
+            bail!("invalid cidr prefix: {value}");
+        }
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_cidr_contains() {
+        let lan: IpCidr = "192.168.0.0/16".parse().unwrap();
+        assert!(lan.contains("192.168.31.8".parse().unwrap()));
+        assert!(lan.contains("::ffff:192.168.1.1".parse().unwrap()));
+        assert!(!lan.contains("192.169.0.1".parse().unwrap()));
+        assert!(!lan.contains("fd00::1".parse().unwrap()));
+
+        let ula: IpCidr = "fc00::/7".parse().unwrap();
+        assert!(ula.contains("fd12:3456::1".parse().unwrap()));
+
+        let any: IpCidr = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains("8.8.8.8".parse().unwrap()));
+
+        let host: IpCidr = "10.0.0.5".parse().unwrap();
+        assert_eq!(host.to_string(), "10.0.0.5/32");
+        assert!(!host.contains("10.0.0.6".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_cidr_invalid() {
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("example.com/8".parse::<IpCidr>().is_err());
+        assert!("10.0.0.0/x".parse::<IpCidr>().is_err());
+        assert!(
+            PRIVATE_CIDRS
+                .iter()
+                .all(|cidr| cidr.parse::<IpCidr>().is_ok())
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! 本地控制 API，挂载在 embedded server 的 `/api/v1` 下
+//!
+//! 所有请求需携带 `Authorization: Bearer <control_api_token>`，
+//! 返回统一的 `{ ok, data, error }` JSON。
+
+use crate::{config::Config, feat};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use warp::{Filter, Rejection, Reply, http::StatusCode};
+
+pub const API_PREFIX: &str = "api/v1";
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+#[derive(Deserialize)]
+struct ModeBody {
+    mode: String,
+}
+
+#[derive(Deserialize)]
+struct SwitchBody {
+    enable: bool,
+}
+
+/// 统一返回格式
+#[derive(Serialize)]
+struct ApiReply<T: Serialize> {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn api_reply<T: Serialize>(result: Result<T>) -> Result<impl Reply, Rejection> {
+    let (status, reply) = match result {
+        Ok(data) => (
+            StatusCode::OK,
+            ApiReply {
+                ok: true,
+                data: Some(data),
+                error: None,
+            },
+        ),
+        Err(err) => (
+            StatusCode::BAD_REQUEST,
+            ApiReply {
+                ok: false,
+                data: None,
+                error: Some(err.to_string()),
+            },
+        ),
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&reply), status))
+}
+
+/// 逐字节比较，避免提前返回泄露令牌前缀
+fn token_matches(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn with_auth() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(|header: Option<String>| async move {
+            let expected = Config::verge().await.latest_ref().control_api_token.clone();
+            let provided = header
+                .as_deref()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::trim);
+            match (expected, provided) {
+                (Some(expected), Some(provided))
+                    if !expected.is_empty() && token_matches(&expected, provided) =>
+                {
+                    Ok(())
+                }
+                _ => Err(warp::reject::custom(Unauthorized)),
+            }
+        })
+        .untuple_one()
+}
+
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
+    if err.find::<Unauthorized>().is_none() {
+        return Err(err);
+    }
+    let reply = ApiReply::<()> {
+        ok: false,
+        data: None,
+        error: Some("unauthorized".into()),
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&reply),
+        StatusCode::UNAUTHORIZED,
+    ))
+}
+
+/// `/api/v1` 路由
+pub fn routes() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let status = warp::path!("status")
+        .and(warp::get())
+        .and_then(|| async { api_reply(Ok(feat::get_status().await)) });
+
+    let profiles = warp::path!("profiles")
+        .and(warp::get())
+        .and_then(|| async { api_reply(Ok(feat::list_profiles().await)) });
+
+    let profiles_update = warp::path!("profiles" / "update")
+        .and(warp::post())
+        .and_then(|| async { api_reply(feat::update_profiles(None).await) });
+
+    let profile_update = warp::path!("profiles" / String / "update")
+        .and(warp::post())
+        .and_then(|uid: String| async move { api_reply(feat::update_profiles(Some(uid)).await) });
+
+    let profile_select = warp::path!("profiles" / String / "select")
+        .and(warp::post())
+        .and_then(|uid: String| async move { api_reply(feat::select_profile(uid).await) });
+
+    let mode = warp::path!("mode")
+        .and(warp::put())
+        .and(warp::body::json::<ModeBody>())
+        .and_then(|body: ModeBody| async move { api_reply(feat::set_clash_mode(body.mode).await) });
+
+    let system_proxy = warp::path!("system-proxy")
+        .and(warp::put())
+        .and(warp::body::json::<SwitchBody>())
+        .and_then(|body: SwitchBody| async move {
+            api_reply(feat::set_system_proxy(body.enable).await)
+        });
+
+    let tun = warp::path!("tun")
+        .and(warp::put())
+        .and(warp::body::json::<SwitchBody>())
+        .and_then(
+            |body: SwitchBody| async move { api_reply(feat::set_tun_mode(body.enable).await) },
+        );
+
+    let enhance = warp::path!("enhance")
+        .and(warp::post())
+        .and_then(|| async { api_reply(feat::trigger_enhance().await) });
+
+    let core_restart = warp::path!("core" / "restart")
+        .and(warp::post())
+        .and_then(|| async { api_reply(feat::restart_core().await) });
+
+    warp::path("api")
+        .and(warp::path("v1"))
+        .and(with_auth())
+        .and(
+            status
+                .or(profiles)
+                .or(profiles_update)
+                .or(profile_update)
+                .or(profile_select)
+                .or(mode)
+                .or(system_proxy)
+                .or(tun)
+                .or(enhance)
+                .or(core_restart),
+        )
+        .recover(handle_rejection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::token_matches;
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("abc123", "abc123"));
+        assert!(!token_matches("abc123", "abc124"));
+        assert!(!token_matches("abc123", "abc12"));
+        assert!(!token_matches("abc123", ""));
+    }
+}
@@ -51,8 +51,12 @@ pub fn app_home_dir() -> Result<PathBuf> {
         return Ok(PathBuf::from(app_dir).join(".config").join(APP_ID));
     }
 
-    // 避免在Handle未初始化时崩溃
-    let app_handle = handle::Handle::app_handle();
+    // 无界面守护模式与命令行前端没有 AppHandle
+    let Some(app_handle) = handle::Handle::try_app_handle() else {
+        return dirs::data_dir()
+            .map(|dir| dir.join(APP_ID))
+            .ok_or(anyhow::anyhow!("Failed to get the app home directory"));
+    };
 
     match app_handle.path().data_dir() {
         Ok(dir) => Ok(dir.join(APP_ID)),
@@ -63,10 +67,18 @@ pub fn app_home_dir() -> Result<PathBuf> {
     }
 }
 
+/// get the verge app home dir without the app handle, used by the cli front end
+pub fn app_home_dir_standalone() -> Result<PathBuf> {
+    init_portable_flag()?;
+    app_home_dir()
+}
+
 /// get the resources dir
 pub fn app_resources_dir() -> Result<PathBuf> {
-    // 避免在Handle未初始化时崩溃
-    let app_handle = handle::Handle::app_handle();
+    // 无界面守护模式下按安装布局查找
+    let Some(app_handle) = handle::Handle::try_app_handle() else {
+        return standalone_resources_dir();
+    };
 
     match app_handle.path().resource_dir() {
         Ok(dir) => Ok(dir.join("resources")),
@@ -77,6 +89,22 @@ pub fn app_resources_dir() -> Result<PathBuf> {
     }
 }
 
+/// 与 Tauri 的资源目录布局一致：可执行文件同级，Linux 安装包位于 /usr/lib/<productName>
+fn standalone_resources_dir() -> Result<PathBuf> {
+    use tauri::utils::platform::current_exe;
+
+    let app_exe = dunce::canonicalize(current_exe()?)?;
+    let exe_dir = app_exe
+        .parent()
+        .ok_or(anyhow::anyhow!("failed to get the app dir"))?;
+    let resources = exe_dir.join("resources");
+    #[cfg(target_os = "linux")]
+    if !resources.exists() && exe_dir.ends_with("bin") {
+        return Ok(PathBuf::from("/usr/lib/NeedyClash/resources"));
+    }
+    Ok(resources)
+}
+
 /// profiles dir
 pub fn app_profiles_dir() -> Result<PathBuf> {
     Ok(app_home_dir()?.join("profiles"))
@@ -152,6 +180,22 @@ pub fn service_path() -> Result<PathBuf> {
     Ok(res_dir.join("clash-verge-service.exe"))
 }
 
+/// 与可执行文件同目录的内核程序，和 sidecar 的查找方式一致
+pub fn core_binary_path(clash_core: &str) -> Result<PathBuf> {
+    use tauri::utils::platform::current_exe;
+
+    let app_exe = dunce::canonicalize(current_exe()?)?;
+    let exe_dir = app_exe
+        .parent()
+        .ok_or(anyhow::anyhow!("failed to get the app dir"))?;
+    let name = if cfg!(windows) {
+        format!("{clash_core}.exe")
+    } else {
+        clash_core.to_string()
+    };
+    Ok(exe_dir.join(name))
+}
+
 pub fn sidecar_log_dir() -> Result<PathBuf> {
     let log_dir = app_logs_dir()?.join("sidecar");
     let _ = std::fs::create_dir_all(&log_dir);
@@ -143,6 +143,39 @@ pub fn linux_elevator() -> String {
     }
 }
 
+/// 以管理员权限写入系统文件
+///
+/// 内容经 stdin 交给提权后的 `tee`，不落地到其他用户可预测、可替换的临时文件
+#[cfg(target_os = "linux")]
+pub fn write_file_elevated(path: &std::path::Path, content: &str) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(linux_elevator())
+        .args([
+            "sh",
+            "-c",
+            r#"mkdir -p -- "$(dirname -- "$1")" && tee -- "$1" > /dev/null"#,
+            "sh",
+        ])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    let written = child
+        .stdin
+        .take()
+        .context("failed to open stdin of the elevated process")?
+        .write_all(content.as_bytes());
+    // stdin 在此之前已被丢弃，tee 读到 EOF 后退出
+    let status = child.wait()?;
+    written?;
+    if !status.success() {
+        bail!("failed to write {}", path.display());
+    }
+    Ok(())
+}
+
 /// return the string literal error
 #[macro_export]
 macro_rules! ret_err {
@@ -501,7 +501,6 @@ pub fn init_scheme() -> Result<()> {
 }
 
 pub async fn startup_script() -> Result<()> {
-    let app_handle = handle::Handle::app_handle();
     let script_path = {
         let verge = Config::verge().await;
         let verge = verge.latest_ref();
@@ -531,13 +530,25 @@ pub async fn startup_script() -> Result<()> {
     let parent_dir = script_dir.parent();
     let working_dir = parent_dir.unwrap_or(script_dir.as_ref());
 
-    app_handle
-        .shell()
-        .command(shell_type)
-        .current_dir(working_dir)
-        .args(&[script_path])
-        .output()
-        .await?;
+    match handle::Handle::try_app_handle() {
+        Some(app_handle) => {
+            app_handle
+                .shell()
+                .command(shell_type)
+                .current_dir(working_dir)
+                .args(&[script_path])
+                .output()
+                .await?;
+        }
+        // 无界面守护模式没有 shell 插件，直接启动进程
+        None => {
+            tokio::process::Command::new(shell_type)
+                .current_dir(working_dir)
+                .arg(&script_path)
+                .output()
+                .await?;
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,322 @@
+//! 局域网 PAC 服务
+//!
+//! 开启 allow-lan 后，为手机、电视等设备提供 PAC（本机代理地址替换为局域网地址）、
+//! WPAD 兼容的 `/wpad.dat` 以及带二维码的连接说明页面，仅允许指定网段访问。
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use port_scanner::local_port_available;
+use qrcode::{QrCode, render::svg};
+use serde_yaml_ng::Value;
+use tokio::sync::oneshot;
+use warp::{Filter, Rejection, Reply, http::StatusCode};
+
+use crate::{
+    config::Config,
+    core::pac::PacManager,
+    logging,
+    process::AsyncHandler,
+    singleton_lazy,
+    utils::{
+        cidr::{IpCidr, PRIVATE_CIDRS},
+        logging::Type,
+    },
+};
+
+const DEFAULT_PORT: u16 = 33332;
+const PAC_CONTENT_TYPE: &str = "application/x-ns-proxy-autoconfig";
+
+#[derive(Debug)]
+struct Forbidden;
+
+impl warp::reject::Reject for Forbidden {}
+
+/// 解析允许访问的网段，未配置或全部无效时只允许私有网段
+fn allowed_cidrs(cidrs: Option<&[String]>) -> Vec<IpCidr> {
+    let parsed: Vec<IpCidr> = cidrs
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|cidr| match cidr.parse() {
+            Ok(cidr) => Some(cidr),
+            Err(e) => {
+                logging!(warn, Type::Network, "忽略无效的局域网访问网段: {}", e);
+                None
+            }
+        })
+        .collect();
+    if !parsed.is_empty() {
+        return parsed;
+    }
+    PRIVATE_CIDRS
+        .iter()
+        .filter_map(|cidr| cidr.parse().ok())
+        .collect()
+}
+
+/// 客户端访问本机时所用的本机地址
+fn local_ip_for(remote: IpAddr) -> Option<IpAddr> {
+    let bind: SocketAddr = match remote {
+        IpAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        IpAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    // UDP connect 不发送数据，仅按路由表选择源地址
+    let socket = UdpSocket::bind(bind).ok()?;
+    socket.connect((remote, 9)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+fn host_string(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => format!("[{ip}]"),
+    }
+}
+
+/// 将 PAC 中指向本机混合端口的代理指令替换为局域网可访问的地址，其余内容保持不变
+fn lan_pac(content: &str, host: &str, port: u16) -> String {
+    let mut content = content.replace("%mixed-port%", &port.to_string());
+    for scheme in ["PROXY", "SOCKS", "SOCKS5"] {
+        content = content.replace(
+            &format!("{scheme} 127.0.0.1:{port}"),
+            &format!("{scheme} {host}:{port}"),
+        );
+    }
+    content
+}
+
+fn qr_svg(data: &str) -> String {
+    QrCode::new(data.as_bytes())
+        .map(|code| {
+            code.render::<svg::Color<'_>>()
+                .min_dimensions(200, 200)
+                .build()
+        })
+        .unwrap_or_default()
+}
+
+fn instructions_page(host: &str, server_port: u16, port: u16, pac_url: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Proxy Setup</title>
+<style>
+body {{ font-family: sans-serif; max-width: 480px; margin: 24px auto; padding: 0 16px; line-height: 1.6; }}
+code {{ background: #f2f2f2; padding: 2px 6px; border-radius: 4px; word-break: break-all; }}
+.qr {{ text-align: center; margin: 16px 0; }}
+</style>
+</head>
+<body>
+<h2>Proxy Setup</h2>
+<div class="qr">{qr}</div>
+<h3>Automatic (PAC)</h3>
+<p>Set the proxy to <b>Automatic</b> and use the URL:<br><code>{pac_url}</code></p>
+<p>Devices with WPAD enabled can also fetch <code>http://{host}:{server_port}/wpad.dat</code>.</p>
+<h3>Manual</h3>
+<p>Server: <code>{host}</code><br>Port: <code>{port}</code></p>
+</body>
+</html>
+"#,
+        qr = qr_svg(pac_url),
+    )
+}
+
+async fn mixed_port() -> u16 {
+    let verge_port = Config::verge().await.latest_ref().verge_mixed_port;
+    match verge_port {
+        Some(port) => port,
+        None => Config::clash().await.latest_ref().get_mixed_port(),
+    }
+}
+
+fn with_access(
+    cidrs: Arc<Vec<IpCidr>>,
+) -> impl Filter<Extract = (IpAddr,), Error = Rejection> + Clone {
+    warp::addr::remote().and_then(move |remote: Option<SocketAddr>| {
+        let cidrs = Arc::clone(&cidrs);
+        async move {
+            match remote {
+                Some(remote) if cidrs.iter().any(|cidr| cidr.contains(remote.ip())) => {
+                    Ok(remote.ip())
+                }
+                _ => Err(warp::reject::custom(Forbidden)),
+            }
+        }
+    })
+}
+
+async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
+    if err.find::<Forbidden>().is_none() {
+        return Err(err);
+    }
+    Ok(warp::reply::with_status("forbidden", StatusCode::FORBIDDEN))
+}
+
+fn routes(
+    cidrs: Arc<Vec<IpCidr>>,
+    server_port: u16,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let access = with_access(cidrs);
+
+    let pac = warp::path!("proxy.pac")
+        .or(warp::path!("wpad.dat"))
+        .unify()
+        .and(warp::get())
+        .and(access.clone())
+        .and_then(|remote: IpAddr| async move {
+            let host = local_ip_for(remote).map_or_else(|| "127.0.0.1".into(), host_string);
+            let content = lan_pac(&PacManager::global().content(), &host, mixed_port().await);
+            Ok::<_, Rejection>(
+                warp::http::Response::builder()
+                    .header("Content-Type", PAC_CONTENT_TYPE)
+                    .body(content)
+                    .unwrap_or_default(),
+            )
+        });
+
+    let index =
+        warp::path::end()
+            .and(warp::get())
+            .and(access)
+            .and_then(move |remote: IpAddr| async move {
+                let host = local_ip_for(remote).map_or_else(|| "127.0.0.1".into(), host_string);
+                let pac_url = format!("http://{host}:{server_port}/proxy.pac");
+                let page = instructions_page(&host, server_port, mixed_port().await, &pac_url);
+                Ok::<_, Rejection>(warp::reply::html(page))
+            });
+
+    pac.or(index).recover(handle_rejection)
+}
+
+/// 局域网 PAC 服务，随设置与 allow-lan 变化启停
+#[derive(Default)]
+pub struct LanServer {
+    shutdown: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+singleton_lazy!(LanServer, LAN_SERVER, LanServer::default);
+
+impl LanServer {
+    /// 按当前设置重新启动，未启用或 allow-lan 关闭时仅停止
+    pub async fn restart(&self) {
+        if self.stop() {
+            // 等待旧监听释放端口
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        }
+
+        let settings = Config::verge()
+            .await
+            .latest_ref()
+            .lan_pac_server
+            .clone()
+            .unwrap_or_default();
+        let allow_lan = Config::clash()
+            .await
+            .latest_ref()
+            .0
+            .get("allow-lan")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if !settings.enabled.unwrap_or(false) || !allow_lan {
+            return;
+        }
+
+        let port = settings.port.unwrap_or(DEFAULT_PORT);
+        if !local_port_available(port) {
+            logging!(
+                error,
+                Type::Network,
+                "局域网 PAC 服务端口 {} 已被占用",
+                port
+            );
+            return;
+        }
+        let cidrs = Arc::new(allowed_cidrs(settings.allowed_cidrs.as_deref()));
+        logging!(
+            info,
+            Type::Network,
+            "启动局域网 PAC 服务: 0.0.0.0:{}, 允许网段: {:?}",
+            port,
+            cidrs.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        *self.shutdown.lock() = Some(shutdown_tx);
+        AsyncHandler::spawn(move || async move {
+            warp::serve(routes(cidrs, port))
+                .bind(([0, 0, 0, 0], port))
+                .await
+                .graceful(async {
+                    shutdown_rx.await.ok();
+                })
+                .run()
+                .await;
+        });
+    }
+
+    /// 停止服务，返回之前是否在运行
+    pub fn stop(&self) -> bool {
+        let Some(sender) = self.shutdown.lock().take() else {
+            return false;
+        };
+        logging!(info, Type::Network, "停止局域网 PAC 服务");
+        sender.send(()).ok();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lan_pac() {
+        let pac = "return \"PROXY 127.0.0.1:%mixed-port%; SOCKS5 127.0.0.1:%mixed-port%; DIRECT\";";
+        assert_eq!(
+            lan_pac(pac, "192.168.1.10", 7897),
+            "return \"PROXY 192.168.1.10:7897; SOCKS5 192.168.1.10:7897; DIRECT\";"
+        );
+        // 规则中的地址与其他端口保持不变
+        let pac = "if (host === \"127.0.0.1\") return \"PROXY 127.0.0.1:9\";";
+        assert_eq!(lan_pac(pac, "192.168.1.10", 7897), pac);
+        assert_eq!(
+            host_string(IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1))),
+            "[fd00::1]"
+        );
+    }
+
+    #[test]
+    fn test_allowed_cidrs() {
+        let custom = allowed_cidrs(Some(&["10.1.0.0/16".into(), "bad".into()]));
+        assert_eq!(custom.len(), 1);
+        assert!(custom[0].contains(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3))));
+
+        // 未配置有效网段时回退到私有网段
+        let fallback = allowed_cidrs(Some(&["bad".into()]));
+        assert_eq!(fallback.len(), PRIVATE_CIDRS.len());
+        assert!(
+            !fallback
+                .iter()
+                .any(|cidr| cidr.contains(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8))))
+        );
+    }
+
+    #[test]
+    fn test_instructions_page() {
+        let page = instructions_page(
+            "192.168.1.10",
+            33332,
+            7897,
+            "http://192.168.1.10:33332/proxy.pac",
+        );
+        assert!(page.contains("<svg"));
+        assert!(page.contains("http://192.168.1.10:33332/wpad.dat"));
+        assert!(page.contains("<code>7897</code>"));
+    }
+}
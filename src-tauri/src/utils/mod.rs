@@ -1,10 +1,15 @@
 pub mod autostart;
+#[cfg(target_os = "linux")]
+pub mod capability;
+pub mod cidr;
+pub mod control_api;
 pub mod dirs;
 pub mod draft;
 pub mod format;
 pub mod help;
 pub mod i18n;
 pub mod init;
+pub mod lan_server;
 #[cfg(target_os = "linux")]
 pub mod linux;
 pub mod logging;
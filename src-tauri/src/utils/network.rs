@@ -46,6 +46,18 @@ impl HttpResponse {
     }
 }
 
+/// 一次请求的各阶段耗时，无法测量的阶段为 None
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestTiming {
+    /// 收到响应头的总耗时
+    pub total: Duration,
+    pub dns: Option<Duration>,
+    pub tcp: Option<Duration>,
+    pub tls: Option<Duration>,
+    /// 首字节时间
+    pub ttfb: Option<Duration>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum ProxyType {
     None,
@@ -257,4 +269,49 @@ impl NetworkManager {
 
         Ok(response)
     }
+
+    /// 发送一次 GET 请求并记录各阶段耗时，非 2xx 状态视为失败
+    /// 经代理时 DNS 与 TCP 耗时为到本地代理的耗时
+    pub async fn probe_timing(
+        &self,
+        url: &str,
+        proxy_type: ProxyType,
+        timeout_secs: Option<u64>,
+        user_agent: Option<String>,
+    ) -> Result<RequestTiming> {
+        let client = self
+            .create_request(proxy_type, timeout_secs, user_agent, false)
+            .await?;
+        let request = isahc::Request::get(url).metrics(true).body(())?;
+
+        let timeout_duration = Duration::from_secs(timeout_secs.unwrap_or(20));
+        let start = Instant::now();
+        let response = match timeout(timeout_duration, client.send_async(request)).await {
+            Ok(response) => response?,
+            Err(_) => {
+                self.record_connection_error(&format!("Request interrupted: {}", url))
+                    .await;
+                return Err(anyhow::anyhow!(
+                    "Request interrupted after {}s",
+                    timeout_duration.as_secs()
+                ));
+            }
+        };
+        let total = start.elapsed();
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("Unexpected status code: {}", status));
+        }
+
+        let measured = |duration: Duration| (!duration.is_zero()).then_some(duration);
+        let metrics = response.metrics();
+        Ok(RequestTiming {
+            total,
+            dns: metrics.and_then(|metrics| measured(metrics.name_lookup_time())),
+            tcp: metrics.and_then(|metrics| measured(metrics.connect_time())),
+            tls: metrics.and_then(|metrics| measured(metrics.secure_connect_time())),
+            ttfb: metrics.and_then(|metrics| measured(metrics.transfer_start_time())),
+        })
+    }
 }
@@ -14,6 +14,44 @@ pub enum NotificationEvent<'a> {
     AppQuit,
     #[cfg(target_os = "macos")]
     AppHidden,
+    ProfileSwitched {
+        profile: &'a str,
+    },
+    NodeSelected {
+        group: &'a str,
+        node: &'a str,
+    },
+    GroupDelayTested {
+        group: &'a str,
+    },
+    ClashEnvCopied,
+    UnlockBlocked {
+        service: &'a str,
+        node: &'a str,
+    },
+    UnlockRegionChanged {
+        service: &'a str,
+        node: &'a str,
+        region: &'a str,
+    },
+    CoreMemoryExceeded {
+        memory: &'a str,
+        limit: &'a str,
+        seconds: &'a str,
+        restarted: bool,
+    },
+    NetworkSwitched {
+        rule: &'a str,
+    },
+    ProxyGuardTampered {
+        pac: bool,
+        count: &'a str,
+        window: &'a str,
+    },
+    ProfileUpdateFailed {
+        profiles: &'a str,
+        error: &'a str,
+    },
 }
 
 fn notify(app: &AppHandle, title: &str, body: &str) {
@@ -38,7 +76,7 @@ pub async fn notify_event<'a>(app: AppHandle, event: NotificationEvent<'a>) {
             notify(
                 &app,
                 &t("ClashModeChangedTitle").await,
-                &t_with_args("ClashModeChangedBody", mode).await,
+                &t_with_args("ClashModeChangedBody", &[("mode", mode)]).await,
             );
         }
         NotificationEvent::SystemProxyToggled => {
@@ -69,10 +107,113 @@ pub async fn notify_event<'a>(app: AppHandle, event: NotificationEvent<'a>) {
         NotificationEvent::AppHidden => {
             notify(&app, &t("AppHiddenTitle").await, &t("AppHiddenBody").await);
         }
+        NotificationEvent::ProfileSwitched { profile } => {
+            notify(
+                &app,
+                &t("ProfileSwitchedTitle").await,
+                &t_with_args("ProfileSwitchedBody", &[("profile", profile)]).await,
+            );
+        }
+        NotificationEvent::NodeSelected { group, node } => {
+            notify(
+                &app,
+                &t("NodeSelectedTitle").await,
+                &t_with_args("NodeSelectedBody", &[("group", group), ("node", node)]).await,
+            );
+        }
+        NotificationEvent::GroupDelayTested { group } => {
+            notify(
+                &app,
+                &t("GroupDelayTestedTitle").await,
+                &t_with_args("GroupDelayTestedBody", &[("group", group)]).await,
+            );
+        }
+        NotificationEvent::ClashEnvCopied => {
+            notify(
+                &app,
+                &t("ClashEnvCopiedTitle").await,
+                &t("ClashEnvCopiedBody").await,
+            );
+        }
+        NotificationEvent::UnlockBlocked { service, node } => {
+            notify(
+                &app,
+                &t("UnlockBlockedTitle").await,
+                &t_with_args("UnlockBlockedBody", &[("service", service), ("node", node)]).await,
+            );
+        }
+        NotificationEvent::UnlockRegionChanged {
+            service,
+            node,
+            region,
+        } => {
+            notify(
+                &app,
+                &t("UnlockRegionChangedTitle").await,
+                &t_with_args(
+                    "UnlockRegionChangedBody",
+                    &[("service", service), ("node", node), ("region", region)],
+                )
+                .await,
+            );
+        }
+        NotificationEvent::CoreMemoryExceeded {
+            memory,
+            limit,
+            seconds,
+            restarted,
+        } => {
+            let (title, body) = if restarted {
+                ("CoreMemoryRestartedTitle", "CoreMemoryRestartedBody")
+            } else {
+                ("CoreMemoryExceededTitle", "CoreMemoryExceededBody")
+            };
+            notify(
+                &app,
+                &t(title).await,
+                &t_with_args(
+                    body,
+                    &[("memory", memory), ("limit", limit), ("seconds", seconds)],
+                )
+                .await,
+            );
+        }
+        NotificationEvent::NetworkSwitched { rule } => {
+            notify(
+                &app,
+                &t("NetworkSwitchedTitle").await,
+                &t_with_args("NetworkSwitchedBody", &[("rule", rule)]).await,
+            );
+        }
+        NotificationEvent::ProxyGuardTampered { pac, count, window } => {
+            let body = if pac {
+                "PacTamperedBody"
+            } else {
+                "SysproxyTamperedBody"
+            };
+            notify(
+                &app,
+                &t("ProxyGuardTamperedTitle").await,
+                &t_with_args(body, &[("count", count), ("window", window)]).await,
+            );
+        }
+        NotificationEvent::ProfileUpdateFailed { profiles, error } => {
+            notify(
+                &app,
+                &t("ProfileUpdateFailedTitle").await,
+                &t_with_args(
+                    "ProfileUpdateFailedBody",
+                    &[("profiles", profiles), ("error", error)],
+                )
+                .await,
+            );
+        }
     }
 }
 
 // 辅助函数，带参数的i18n
-async fn t_with_args(key: &str, mode: &str) -> String {
-    t(key).await.replace("{mode}", mode)
+async fn t_with_args(key: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(t(key).await, |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value)
+    })
 }
@@ -0,0 +1,91 @@
+//! 无界面守护模式
+//!
+//! 不构建 Tauri 应用：没有窗口、托盘与全局快捷键，仅在 tokio 运行时上加载配置、
+//! 启动内核、定时任务与 embedded server（控制 API），收到终止信号后清理退出。
+
+use crate::{
+    config::Config,
+    core::handle,
+    logging, logging_error,
+    process::AsyncHandler,
+    utils::{dirs, init, logging::Type, server},
+};
+
+use super::{
+    init_control_api_token, init_core_manager, init_core_resource_monitor, init_lan_server,
+    init_network_switcher, init_resources, init_service_manager, init_startup_script,
+    init_system_proxy, init_system_proxy_guard, init_timer, init_verge_config, init_work_config,
+    resolve_reset_async,
+};
+
+/// 以守护模式运行直到收到终止信号，返回进程退出码
+pub fn run() -> i32 {
+    AsyncHandler::block_on(async {
+        if let Err(e) = dirs::init_portable_flag() {
+            eprintln!("error: failed to init portable flag: {e}");
+            return 1;
+        }
+        logging_error!(Type::Setup, init::init_logger().await);
+        logging!(
+            info,
+            Type::Setup,
+            "以无界面守护模式运行，版本: {}",
+            env!("CARGO_PKG_VERSION")
+        );
+
+        futures::join!(init_service_manager(), init_work_config(), init_resources());
+        futures::join!(init_startup_script(), init_verge_config());
+        init_control_api_token().await;
+
+        if server::check_singleton().await.is_err() {
+            logging!(error, Type::Setup, "已有实例在运行，守护进程退出");
+            return 1;
+        }
+        server::embed_server();
+
+        Config::verify_config_initialization().await;
+        init_core_manager().await;
+        init_core_resource_monitor();
+        init_network_switcher();
+        init_lan_server().await;
+        init_system_proxy().await;
+        AsyncHandler::spawn_blocking(init_system_proxy_guard);
+        init_timer().await;
+
+        wait_exit_signal().await;
+        logging!(info, Type::System, "收到终止信号，开始退出守护进程");
+
+        handle::Handle::global().set_is_exiting();
+        server::shutdown_embedded_server();
+        match resolve_reset_async().await {
+            Ok(()) => 0,
+            Err(e) => {
+                logging!(error, Type::System, "守护进程清理失败: {}", e);
+                1
+            }
+        }
+    })
+}
+
+async fn wait_exit_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => logging!(error, Type::Setup, "Failed to listen for SIGTERM: {}", e),
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        logging!(error, Type::Setup, "Failed to listen for Ctrl+C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
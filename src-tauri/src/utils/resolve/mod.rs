@@ -5,17 +5,20 @@ use crate::{
     core::{
         CoreManager, Timer, handle,
         hotkey::Hotkey,
+        network_switch::NetworkSwitcher,
+        resource_monitor::CoreResourceMonitor,
         service::{SERVICE_MANAGER, ServiceManager, is_service_ipc_path_exists},
         sysopt,
         tray::Tray,
     },
-    logging, logging_error,
+    feat, logging, logging_error,
     module::lightweight::{auto_lightweight_mode_init, run_once_auto_lightweight},
     process::AsyncHandler,
-    utils::{init, logging::Type, server, window_manager::WindowManager},
+    utils::{init, lan_server::LanServer, logging::Type, server, window_manager::WindowManager},
 };
 
 pub mod dns;
+pub mod headless;
 pub mod scheme;
 pub mod ui;
 pub mod window;
@@ -62,12 +65,18 @@ pub fn resolve_setup_async() {
             init_once_auto_lightweight(),
             init_auto_lightweight_mode(),
         );
+        init_control_api_token().await;
 
         // 配置验证
         Config::verify_config_initialization().await;
 
         // 核心管理器初始化
         init_core_manager().await;
+        // 快捷键初始化时运行时配置可能尚未生成，按生成后的代理组重新注册
+        Hotkey::global().refresh_targets().await;
+        init_core_resource_monitor();
+        init_network_switcher();
+        init_lan_server().await;
 
         // 系统代理设置
         init_system_proxy().await;
@@ -195,6 +204,11 @@ pub(super) async fn init_verge_config() {
     logging_error!(Type::Setup, Config::init_config().await);
 }
 
+pub(super) async fn init_control_api_token() {
+    logging!(info, Type::Setup, "Initializing control api token...");
+    logging_error!(Type::Setup, feat::ensure_control_api_token().await);
+}
+
 pub(super) async fn init_service_manager() {
     logging!(info, Type::Setup, "Initializing service manager...");
     clash_verge_service_ipc::set_config(ServiceManager::config()).await;
@@ -237,6 +251,21 @@ pub(super) async fn init_core_manager() {
     logging_error!(Type::Setup, CoreManager::global().init().await);
 }
 
+pub(super) fn init_core_resource_monitor() {
+    logging!(info, Type::Setup, "Initializing core resource monitor...");
+    CoreResourceMonitor::global().start();
+}
+
+pub(super) fn init_network_switcher() {
+    logging!(info, Type::Setup, "Initializing network switcher...");
+    NetworkSwitcher::global().start();
+}
+
+pub(super) async fn init_lan_server() {
+    logging!(info, Type::Setup, "Initializing LAN PAC server...");
+    LanServer::global().restart().await;
+}
+
 pub(super) async fn init_system_proxy() {
     logging!(info, Type::Setup, "Initializing system proxy...");
     logging_error!(
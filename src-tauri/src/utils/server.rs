@@ -2,11 +2,12 @@ use std::time::Duration;
 
 use super::resolve;
 use crate::{
-    config::{Config, DEFAULT_PAC, IVerge},
+    config::{Config, IVerge},
+    core::{handle, pac::PacManager},
     logging, logging_error,
     module::lightweight,
     process::AsyncHandler,
-    utils::{logging::Type, window_manager::WindowManager},
+    utils::{control_api, lan_server::LanServer, logging::Type, window_manager::WindowManager},
 };
 use anyhow::{Result, bail};
 use once_cell::sync::OnceCell;
@@ -57,8 +58,8 @@ pub async fn check_singleton() -> Result<()> {
     Ok(())
 }
 
-/// The embed server only be used to implement singleton process
-/// maybe it can be used as pac server later
+/// The embed server implements the singleton process, the pac server
+/// and the token protected control api (`/api/v1`)
 pub fn embed_server() {
     let (shutdown_tx, shutdown_rx) = oneshot::channel();
     #[allow(clippy::expect_used)]
@@ -70,7 +71,9 @@ pub fn embed_server() {
     AsyncHandler::spawn(move || async move {
         let visible = warp::path!("commands" / "visible").and_then(|| async {
             logging!(info, Type::Window, "检测到从单例模式恢复应用窗口");
-            if !lightweight::exit_lightweight_mode().await {
+            if handle::Handle::try_app_handle().is_none() {
+                logging!(info, Type::Window, "无界面守护模式没有应用窗口，忽略");
+            } else if !lightweight::exit_lightweight_mode().await {
                 WindowManager::show_main_window().await;
             } else {
                 logging!(error, Type::Window, "轻量模式退出失败，无法恢复应用窗口");
@@ -84,22 +87,18 @@ pub fn embed_server() {
         let verge_config = Config::verge().await;
         let clash_config = Config::clash().await;
 
-        let content = verge_config
-            .latest_ref()
-            .pac_file_content
-            .clone()
-            .unwrap_or(DEFAULT_PAC.into());
+        PacManager::global().refresh().await;
 
         let mixed_port = verge_config
             .latest_ref()
             .verge_mixed_port
             .unwrap_or(clash_config.latest_ref().get_mixed_port());
 
-        // Clone the content and port for the closure to avoid borrowing issues
-        let pac_content = content.clone();
         let pac_port = mixed_port;
         let pac = warp::path!("commands" / "pac").map(move || {
-            let processed_content = pac_content.replace("%mixed-port%", &format!("{pac_port}"));
+            let processed_content = PacManager::global()
+                .content()
+                .replace("%mixed-port%", &format!("{pac_port}"));
             warp::http::Response::builder()
                 .header("Content-Type", "application/x-ns-proxy-autoconfig")
                 .body(processed_content)
@@ -118,7 +117,7 @@ pub fn embed_server() {
                 warp::reply::with_status::<String>("ok".into(), warp::http::StatusCode::OK)
             });
 
-        let commands = visible.or(scheme).or(pac);
+        let commands = visible.or(scheme).or(pac).or(control_api::routes());
         warp::serve(commands)
             .bind(([127, 0, 0, 1], port))
             .await
@@ -132,6 +131,7 @@ pub fn embed_server() {
 
 pub fn shutdown_embedded_server() {
     log::info!("shutting down embedded server");
+    LanServer::global().stop();
     if let Some(sender) = SHUTDOWN_SENDER.get()
         && let Some(sender) = sender.lock().take()
     {
@@ -55,7 +55,7 @@ export const SystemInfoCard = () => {
   const { t } = useTranslation();
   const { verge, patchVerge } = useVerge();
   const navigate = useNavigate();
-  const { isAdminMode, isSidecarMode } = useSystemState();
+  const { isAdminMode, isSidecarMode, isCapabilityMode } = useSystemState();
 
   // 系统信息状态
   const [systemState, dispatchSystemState] = useReducer(systemStateReducer, {
@@ -189,6 +189,13 @@ export const SystemInfoCard = () => {
           titleAccess={t("Sidecar Mode")}
         />
       );
+    } else if (isCapabilityMode) {
+      return (
+        <ExtensionOutlined
+          sx={{ color: "success.main", fontSize: 16 }}
+          titleAccess={t("Capability Mode")}
+        />
+      );
     } else {
       return (
         <DnsOutlined
@@ -207,6 +214,8 @@ export const SystemInfoCard = () => {
       return t("Administrator Mode");
     } else if (isSidecarMode) {
       return t("Sidecar Mode");
+    } else if (isCapabilityMode) {
+      return t("Capability Mode");
     } else {
       return t("Service Mode");
     }
@@ -1,9 +1,13 @@
+import { AddRounded, DeleteRounded } from "@mui/icons-material";
+import { IconButton, MenuItem, Select, Stack } from "@mui/material";
 import { useLockFn } from "ahooks";
 import { forwardRef, useImperativeHandle, useState } from "react";
 import { useTranslation } from "react-i18next";
 
 import { BaseDialog, DialogRef, Switch } from "@/components/base";
+import { useProfiles } from "@/hooks/use-profiles";
 import { useVerge } from "@/hooks/use-verge";
+import { useAppData } from "@/providers/app-data-context";
 import { showNotice } from "@/services/noticeService";
 
 import {
@@ -21,18 +25,82 @@ const HOTKEY_FUNC = [
   "toggle_system_proxy",
   "toggle_tun_mode",
   "entry_lightweight_mode",
+  "copy_env",
+  "next_profile",
+  "previous_profile",
 ];
 
+// 带参数的功能，保存为 `function:argument`
+const PARAM_HOTKEY_FUNC = [
+  "switch_profile",
+  "select_next_node",
+  "test_group_delay",
+];
+
+// 快捷键不含逗号，按最后一个逗号拆分，参数中允许出现逗号
+const splitBinding = (text: string) => {
+  const index = text.lastIndexOf(",");
+  if (index < 0) return [];
+  return [text.slice(0, index).trim(), text.slice(index + 1).trim()];
+};
+
 export const HotkeyViewer = forwardRef<DialogRef>((props, ref) => {
   const { t } = useTranslation();
   const [open, setOpen] = useState(false);
 
   const { verge, patchVerge } = useVerge();
+  const { profiles } = useProfiles();
+  const { proxies } = useAppData();
 
   const [hotkeyMap, setHotkeyMap] = useState<Record<string, string[]>>({});
   const [enableGlobalHotkey, setEnableGlobalHotkey] = useState(
     verge?.enable_global_hotkey ?? true,
   );
+  const [paramFunc, setParamFunc] = useState(PARAM_HOTKEY_FUNC[0]);
+  const [paramArg, setParamArg] = useState("");
+
+  const profileOptions: { value: string; label: string }[] = (
+    profiles?.items ?? []
+  )
+    .filter((item) => item.type === "remote" || item.type === "local")
+    .map((item) => ({ value: item.uid, label: item.name ?? item.uid }));
+  const groupOptions: { value: string; label: string }[] = (
+    proxies?.groups ?? []
+  ).map((group: { name: string }) => ({
+    value: group.name,
+    label: group.name,
+  }));
+  const argOptions =
+    paramFunc === "switch_profile" ? profileOptions : groupOptions;
+
+  const paramBindings = Object.keys(hotkeyMap).filter((func) =>
+    PARAM_HOTKEY_FUNC.some((name) => func.startsWith(`${name}:`)),
+  );
+
+  const bindingLabel = (func: string) => {
+    const index = func.indexOf(":");
+    const name = func.slice(0, index);
+    const arg = func.slice(index + 1);
+    const argLabel =
+      name === "switch_profile"
+        ? (profileOptions.find((item) => item.value === arg)?.label ?? arg)
+        : arg;
+    return `${t(name)}: ${argLabel}`;
+  };
+
+  const onAddBinding = () => {
+    if (!paramArg) return;
+    const func = `${paramFunc}:${paramArg}`;
+    setHotkeyMap((m) => (func in m ? m : { ...m, [func]: [] }));
+    setParamArg("");
+  };
+
+  const onRemoveBinding = (func: string) => {
+    setHotkeyMap((m) => {
+      const { [func]: _, ...rest } = m;
+      return rest;
+    });
+  };
 
   useImperativeHandle(ref, () => ({
     open: () => {
@@ -41,7 +109,7 @@ export const HotkeyViewer = forwardRef<DialogRef>((props, ref) => {
       const map = {} as typeof hotkeyMap;
 
       verge?.hotkeys?.forEach((text) => {
-        const [func, key] = text.split(",").map((e) => e.trim());
+        const [func, key] = splitBinding(text);
 
         if (!func || !key) return;
 
@@ -116,6 +184,63 @@ export const HotkeyViewer = forwardRef<DialogRef>((props, ref) => {
           </EnhancedFormItem>
         ))}
       </EnhancedFormGroup>
+
+      <EnhancedFormGroup title={t("Parameterized Hotkeys")}>
+        {paramBindings.map((func) => (
+          <EnhancedFormItem key={func} label={bindingLabel(func)}>
+            <Stack direction="row" alignItems="center" spacing={0.5}>
+              <HotkeyInput
+                value={hotkeyMap[func] ?? []}
+                onChange={(v) => setHotkeyMap((m) => ({ ...m, [func]: v }))}
+              />
+              <IconButton size="small" onClick={() => onRemoveBinding(func)}>
+                <DeleteRounded fontSize="inherit" />
+              </IconButton>
+            </Stack>
+          </EnhancedFormItem>
+        ))}
+        <Stack direction="row" alignItems="center" spacing={1} sx={{ py: 1 }}>
+          <Select
+            size="small"
+            value={paramFunc}
+            sx={{ flex: 1 }}
+            onChange={(e) => {
+              setParamFunc(e.target.value);
+              setParamArg("");
+            }}
+          >
+            {PARAM_HOTKEY_FUNC.map((func) => (
+              <MenuItem key={func} value={func}>
+                {t(func)}
+              </MenuItem>
+            ))}
+          </Select>
+          <Select
+            size="small"
+            displayEmpty
+            value={paramArg}
+            sx={{ flex: 1 }}
+            onChange={(e) => setParamArg(e.target.value)}
+          >
+            <MenuItem value="" disabled>
+              {paramFunc === "switch_profile" ? t("Profile") : t("Proxy Group")}
+            </MenuItem>
+            {argOptions.map((item) => (
+              <MenuItem key={item.value} value={item.value}>
+                {item.label}
+              </MenuItem>
+            ))}
+          </Select>
+          <IconButton
+            size="small"
+            color="primary"
+            disabled={!paramArg}
+            onClick={onAddBinding}
+          >
+            <AddRounded />
+          </IconButton>
+        </Stack>
+      </EnhancedFormGroup>
     </BaseDialog>
   );
 });
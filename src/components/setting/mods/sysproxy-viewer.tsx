@@ -98,6 +98,7 @@ export const SysproxyViewer = forwardRef<DialogRef>((props, ref) => {
     enable_system_proxy: enabled,
     proxy_auto_config,
     pac_file_content,
+    pac_from_rules,
     enable_proxy_guard,
     use_default_bypass,
     system_proxy_bypass,
@@ -112,6 +113,7 @@ export const SysproxyViewer = forwardRef<DialogRef>((props, ref) => {
     use_default: use_default_bypass ?? true,
     pac: proxy_auto_config,
     pac_content: pac_file_content ?? DEFAULT_PAC,
+    pac_from_rules: pac_from_rules ?? false,
     proxy_host: proxy_host ?? "127.0.0.1",
   });
 
@@ -203,6 +205,7 @@ export const SysproxyViewer = forwardRef<DialogRef>((props, ref) => {
         use_default: use_default_bypass ?? true,
         pac: proxy_auto_config,
         pac_content: pac_file_content ?? DEFAULT_PAC,
+        pac_from_rules: pac_from_rules ?? false,
         proxy_host: proxy_host ?? "127.0.0.1",
       });
       getSystemProxy().then((p) => setSysproxy(p));
@@ -321,6 +324,9 @@ export const SysproxyViewer = forwardRef<DialogRef>((props, ref) => {
     if (value.use_default !== use_default_bypass) {
       patch.use_default_bypass = value.use_default;
     }
+    if (value.pac_from_rules !== (pac_from_rules ?? false)) {
+      patch.pac_from_rules = value.pac_from_rules;
+    }
 
     let pacContent = value.pac_content;
     if (pacContent) {
@@ -578,18 +584,32 @@ export const SysproxyViewer = forwardRef<DialogRef>((props, ref) => {
       {/* PAC 脚本编辑 */}
       {value.pac && (
         <EnhancedFormGroup title={t("PAC Script")}>
-          <EnhancedFormItem label={t("PAC Script Content")} fullWidth>
-            <Button
-              startIcon={<EditRounded />}
-              variant="outlined"
-              fullWidth
-              onClick={() => {
-                setEditorOpen(true);
-              }}
-            >
-              {t("Edit")} PAC
-            </Button>
+          <EnhancedFormItem
+            label={t("Generate PAC From Rules")}
+            description={t("Generate PAC From Rules Info")}
+          >
+            <Switch
+              edge="end"
+              checked={value.pac_from_rules}
+              onChange={(_, e) =>
+                setValue((v) => ({ ...v, pac_from_rules: e }))
+              }
+            />
           </EnhancedFormItem>
+          {!value.pac_from_rules && (
+            <EnhancedFormItem label={t("PAC Script Content")} fullWidth>
+              <Button
+                startIcon={<EditRounded />}
+                variant="outlined"
+                fullWidth
+                onClick={() => {
+                  setEditorOpen(true);
+                }}
+              >
+                {t("Edit")} PAC
+              </Button>
+            </EnhancedFormItem>
+          )}
         </EnhancedFormGroup>
       )}
 
@@ -49,8 +49,13 @@ export const TestItem = ({
 
   const onDelay = useCallback(async () => {
     setDelay(-2);
-    const result = await cmdTestDelay(url);
-    setDelay(result);
+    try {
+      const result = await cmdTestDelay(url);
+      setDelay(result);
+    } catch (err) {
+      console.error(`[TestItem] 测试 ${url} 失败:`, err);
+      setDelay(1e6); // error
+    }
   }, [url]);
 
   const initIconCachePath = useCallback(async () => {
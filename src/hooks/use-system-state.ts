@@ -24,6 +24,7 @@ export function useSystemState() {
   } = useSWR("getRunningMode", getRunningMode, systemStateSWRConfig);
   const isSidecarMode = runningMode === "Sidecar";
   const isServiceMode = runningMode === "Service";
+  const isCapabilityMode = runningMode === "Capability";
 
   const { data: isAdminMode = false, isLoading: isAdminLoading } = useSWR(
     "isAdmin",
@@ -43,7 +44,7 @@ export function useSystemState() {
 
   const isLoading = runningModeLoading || isAdminLoading || isServiceLoading;
 
-  const isTunModeAvailable = isAdminMode || isServiceOk;
+  const isTunModeAvailable = isAdminMode || isServiceOk || isCapabilityMode;
 
   useEffect(() => {
     let mounted = true;
@@ -92,6 +93,7 @@ export function useSystemState() {
     isAdminMode,
     isSidecarMode,
     isServiceMode,
+    isCapabilityMode,
     isServiceOk,
     isTunModeAvailable: isTunModeAvailable,
     mutateRunningMode,
@@ -113,6 +113,10 @@ export async function syncTrayProxySelection() {
   return invoke<void>("sync_tray_proxy_selection");
 }
 
+export async function getToolProxyStatus() {
+  return invoke<string[]>("get_tool_proxy_status");
+}
+
 export async function calcuProxies(): Promise<{
   global: IProxyGroupItem;
   direct: IProxyItem;
@@ -239,6 +243,18 @@ export async function getClashLogs() {
   }, []);
 }
 
+export async function queryClashLogs(query: ICoreLogQuery) {
+  return invoke<ICoreLogPage>("query_clash_logs", { query });
+}
+
+export async function exportClashLogs(query: ICoreLogQuery, path: string) {
+  return invoke<number>("export_clash_logs", { query, path });
+}
+
+export async function clearClashLogs() {
+  return invoke<void>("clear_clash_logs");
+}
+
 export async function clearLogs() {
   return invoke<void>("clear_logs");
 }
@@ -251,6 +267,10 @@ export async function patchVergeConfig(payload: IVergeConfig) {
   return invoke<void>("patch_verge_config", { payload });
 }
 
+export async function regenerateControlApiToken() {
+  return invoke<string>("regenerate_control_api_token");
+}
+
 export async function getSystemProxy() {
   return invoke<{
     enable: boolean;
@@ -371,6 +391,30 @@ export async function cmdTestDelay(url: string) {
   return invoke<number>("test_delay", { url });
 }
 
+export async function cmdTestLatency(url: string, samples?: number) {
+  return invoke<ILatencyReport>("test_latency", { url, samples });
+}
+
+export async function cmdTestBandwidth(options: IBandwidthOptions) {
+  return invoke<IBandwidthReport>("test_bandwidth", { options });
+}
+
+export async function cmdCancelBandwidthTest() {
+  return invoke<void>("cancel_bandwidth_test");
+}
+
+export async function getNodeStability() {
+  return invoke<INodeStability[]>("get_node_stability");
+}
+
+export async function getDelayHistory(node: string) {
+  return invoke<IDelaySample[]>("get_delay_history", { node });
+}
+
+export async function getFavoriteSuggestions() {
+  return invoke<string[]>("get_favorite_suggestions");
+}
+
 export async function invoke_uwp_tool() {
   return invoke<void>("invoke_uwp_tool").catch((err) =>
     showNotice("error", err?.message || err.toString(), 1500),
@@ -393,6 +437,10 @@ export async function exportDiagnosticInfo() {
   return invoke("export_diagnostic_info");
 }
 
+export async function getCoreResourceHistory(limit?: number) {
+  return invoke<ICoreResourceSample[]>("get_core_resource_history", { limit });
+}
+
 export async function getSystemInfo() {
   return invoke<string>("get_system_info");
 }
@@ -432,6 +480,36 @@ export async function getNetworkInterfacesInfo() {
   return invoke<INetworkInterface[]>("get_network_interfaces_info");
 }
 
+export async function getNetworkSnapshot() {
+  return invoke<INetworkSnapshot>("get_network_snapshot");
+}
+
+export async function getNetworkSwitchHistory(limit?: number) {
+  return invoke<INetworkSwitchRecord[]>("get_network_switch_history", {
+    limit,
+  });
+}
+
+export async function getUnlockHistory(
+  service: string,
+  node?: string,
+  limit?: number,
+) {
+  return invoke<IUnlockRecord[]>("get_unlock_history", {
+    service,
+    node,
+    limit,
+  });
+}
+
+export async function getProxyGuardHistory(limit?: number) {
+  return invoke<IProxyGuardEvent[]>("get_proxy_guard_history", { limit });
+}
+
+export async function clearProxyGuardHistory() {
+  return invoke<void>("clear_proxy_guard_history");
+}
+
 export async function createWebdavBackup() {
   return invoke<void>("create_webdav_backup");
 }
@@ -497,6 +575,22 @@ export const getRunningMode = async () => {
   return invoke<string>("get_running_mode");
 };
 
+export async function checkCoreCapabilities() {
+  return invoke<boolean>("check_core_capabilities");
+}
+
+export async function tunPreflight() {
+  return invoke<ITunPreflightCheck[]>("tun_preflight");
+}
+
+export async function grantCoreCapabilities() {
+  return invoke<void>("grant_core_capabilities");
+}
+
+export async function revokeCoreCapabilities() {
+  return invoke<void>("revoke_core_capabilities");
+}
+
 // 获取应用运行时间
 export const getAppUptime = async () => {
   return invoke<number>("get_app_uptime");
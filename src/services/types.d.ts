@@ -206,6 +206,30 @@ interface ILogItem {
   payload: string;
 }
 
+type CoreLogLevel = "debug" | "info" | "warning" | "error" | "fatal";
+
+interface ICoreLogRecord {
+  time: number;
+  level: CoreLogLevel;
+  source: string;
+  message: string;
+}
+
+interface ICoreLogQuery {
+  levels?: CoreLogLevel[];
+  start?: number;
+  end?: number;
+  text?: string;
+  regex?: string;
+  offset?: number;
+  limit?: number;
+}
+
+interface ICoreLogPage {
+  total: number;
+  records: ICoreLogRecord[];
+}
+
 interface IConnectionsItem {
   id: string;
   metadata: {
@@ -824,6 +848,7 @@ interface IVergeConfig {
   enable_tray_icon?: boolean;
   tray_inline_proxy_groups?: boolean;
   enable_tun_mode?: boolean;
+  tun_filter?: ITunFilter; // TUN 包含/排除设置
   enable_auto_light_weight_mode?: boolean;
   auto_light_weight_minutes?: number;
   enable_auto_launch?: boolean;
@@ -833,6 +858,7 @@ interface IVergeConfig {
   enable_dns_settings?: boolean;
   proxy_auto_config?: boolean;
   pac_file_content?: string;
+  pac_from_rules?: boolean; // 根据运行时规则生成 PAC
   proxy_host?: string;
   enable_random_port?: boolean;
   verge_mixed_port?: number;
@@ -849,6 +875,8 @@ interface IVergeConfig {
   use_default_bypass?: boolean;
   proxy_guard_duration?: number;
   system_proxy_bypass?: string;
+  bypass_entries?: IBypassEntry[]; // 设置后优先于 system_proxy_bypass
+  bypass_from_rules?: boolean; // 追加从 DIRECT 规则推导的绕过条目
   web_ui_list?: string[];
   hotkeys?: string[];
   window_use_system_titlebar?: boolean;
@@ -919,6 +947,24 @@ interface IVergeConfig {
   enable_external_controller?: boolean;
   favorite_proxies?: string[]; // 收藏的节点名称列表
   traffic_quota_reminder?: ITrafficQuotaReminder; // 流量配额提醒设置
+  core_resource_monitor?: ICoreResourceMonitor; // 内核资源监控设置
+  control_api_token?: string; // 本地控制 API 令牌
+  network_switch?: INetworkSwitch; // 按网络环境自动切换
+  proxy_guard_alert?: IProxyGuardAlert; // 代理被频繁篡改时提醒
+  linux_proxy?: ILinuxProxy; // Linux 系统代理存储后端
+  lan_pac_server?: ILanPacServer; // 局域网 PAC 与连接说明服务
+  tool_proxy?: IToolProxy; // 随系统代理写入开发工具的代理设置
+  unlock_recheck_interval?: number; // 解锁定时复检间隔（分钟），0 为关闭
+  unlock_recheck_group?: string; // 定时复检的代理组，为空时使用第一个代理组
+  unlock_groups?: IUnlockGroups; // 按解锁结果自动生成的代理组
+  delay_probe_interval?: number; // 后台延迟探测间隔（分钟）
+  sort_favorites_by_stability?: boolean;
+}
+
+interface IUnlockGroups {
+  enabled?: boolean;
+  services?: string[]; // 与解锁检测项名称一致
+  group_type?: "select" | "url-test";
 }
 
 interface ITrafficQuotaReminder {
@@ -927,6 +973,193 @@ interface ITrafficQuotaReminder {
   last_reminder?: number; // 最后一次提醒的时间戳
 }
 
+interface ICoreResourceMonitor {
+  enabled?: boolean;
+  interval?: number; // 采样间隔（秒）
+  memory_limit?: number; // 内存阈值（MB）
+  sustained_secs?: number; // 持续超限多久后触发（秒）
+  action?: "warn" | "restart";
+}
+
+interface ILanPacServer {
+  enabled?: boolean; // 仅在 allow-lan 开启时监听
+  port?: number; // 默认 33332
+  allowed_cidrs?: string[]; // 为空时只允许私有网段
+}
+
+interface ITunPreflightCheck {
+  id: "tun_device" | "core_privilege" | "conflicts" | "dns" | "tooling";
+  status: "pass" | "warn" | "fail";
+  message: string; // 翻译键，按 params 插值
+  fix?: string | null; // 修复建议的翻译键
+  params: Record<string, string>;
+}
+
+interface ITunFilter {
+  include_uid?: string[]; // "1000" 或 "1000-1999"
+  exclude_uid?: string[];
+  include_package?: string[];
+  exclude_package?: string[];
+  include_interface?: string[]; // 与 exclude_interface 互斥
+  exclude_interface?: string[];
+  route_exclude_address?: string[];
+}
+
+interface IToolProxy {
+  enabled?: boolean;
+  tools?: ("git" | "npm" | "pnpm" | "pip" | "cargo" | "docker" | "apt")[]; // 默认 git、npm、pnpm、pip、cargo
+}
+
+interface IBypassEntry {
+  kind: "domain" | "wildcard" | "cidr" | "local";
+  value: string;
+}
+
+interface ILinuxProxy {
+  backend?: "auto" | "gsettings" | "kde" | "env";
+  environment_d?: boolean; // 写入 ~/.config/environment.d/
+  environment_file?: string; // 额外管理的 /etc/environment 风格文件
+}
+
+interface IProxyGuardAlert {
+  enabled?: boolean;
+  max_events?: number; // 时间窗口内的篡改次数阈值
+  window_secs?: number; // 统计时间窗口（秒）
+}
+
+type IGuardProxySnapshot =
+  | { enable: boolean; host: string; port: number; bypass: string }
+  | { enable: boolean; url: string };
+
+interface IProxyGuardEvent {
+  time: number;
+  target: "sysproxy" | "pac";
+  expected: IGuardProxySnapshot;
+  actual: IGuardProxySnapshot;
+  action: "restored" | "restore_failed";
+  restored: IGuardProxySnapshot;
+  error?: string;
+}
+
+interface INetworkSwitchRule {
+  name?: string;
+  enabled?: boolean;
+  interfaces?: string[]; // 存在任一网卡
+  gateway?: string; // 默认网关
+  ssid?: string; // Wi-Fi SSID
+  probe_host?: string; // 可达的探测主机 host:port
+  profile?: string; // 切换到的订阅 uid
+  mode?: "rule" | "global" | "direct";
+  system_proxy?: boolean;
+  tun?: boolean;
+}
+
+interface INetworkSwitch {
+  enabled?: boolean;
+  interval?: number; // 检测间隔（秒）
+  rules?: INetworkSwitchRule[];
+}
+
+interface INetworkSnapshot {
+  interfaces: string[];
+  gateway?: string;
+  ssid?: string;
+}
+
+interface INetworkSwitchRecord {
+  time: number;
+  rule: string;
+  network: INetworkSnapshot;
+  actions: string[];
+  errors: string[];
+}
+
+interface ILatencyReport {
+  url: string;
+  samples: number;
+  received: number;
+  loss: number; // 丢包率（百分比）
+  min: number;
+  max: number;
+  avg: number;
+  median: number;
+  p95: number;
+  jitter: number;
+  dns?: number;
+  tcp?: number;
+  tls?: number;
+  ttfb?: number;
+  errors: string[];
+}
+
+interface IBandwidthOptions {
+  download_url: string;
+  upload_url?: string;
+  upload_bytes?: number;
+  max_bytes?: number;
+  duration_secs?: number;
+}
+
+interface IBandwidthSample {
+  elapsed_ms: number;
+  bytes: number;
+  mbps: number;
+}
+
+// verge://bandwidth-progress 事件的数据
+interface IBandwidthProgress extends IBandwidthSample {
+  direction: "download" | "upload";
+}
+
+interface ITransferReport {
+  bytes: number;
+  elapsed_ms: number;
+  mbps: number;
+  peak_mbps: number;
+  samples: IBandwidthSample[];
+  capped: boolean;
+}
+
+interface IBandwidthReport {
+  download?: ITransferReport;
+  upload?: ITransferReport;
+  cancelled: boolean;
+}
+
+interface IDelaySample {
+  time: number;
+  delay?: number; // 为空表示超时或失败
+}
+
+interface INodeStability {
+  node: string;
+  samples: number;
+  failures: number;
+  failure_rate: number; // 失败率（百分比）
+  avg?: number;
+  jitter?: number;
+  last?: number;
+  score: number; // 0 - 100
+}
+
+interface IUnlockRecord {
+  time: number;
+  profile?: string;
+  node?: string;
+  service: string;
+  status: string;
+  region?: string;
+}
+
+interface ICoreResourceSample {
+  time: number;
+  pid: number;
+  cpu_usage: number;
+  memory: number;
+  open_files?: number | null;
+  threads?: number | null;
+}
+
 interface IWebDavFile {
   filename: string;
   href: string;